@@ -1,6 +1,7 @@
 use proc_macro::TokenStream;
-use quote::quote;
-use syn::{parse_macro_input, Data, DeriveInput, Fields};
+use quote::{format_ident, quote};
+use syn::meta::ParseNestedMeta;
+use syn::{parse_macro_input, Data, DeriveInput, Field, Fields, Ident, Lit, LitStr};
 
 #[proc_macro_derive(SystemBase)]
 pub fn derive_system_base(input: TokenStream) -> TokenStream {
@@ -53,11 +54,11 @@ pub fn derive_system_base(input: TokenStream) -> TokenStream {
                     .collect()
             }
 
-            fn cache_entries(&self) -> &Vec<CacheEntry> {
+            fn cache_entries(&self) -> &CacheEntries {
                 &self.cache_entries
             }
 
-            fn cache_entries_mut(&mut self) -> &mut Vec<CacheEntry> {
+            fn cache_entries_mut(&mut self) -> &mut CacheEntries {
                 &mut self.cache_entries
             }
 
@@ -91,7 +92,7 @@ pub fn derive_system_base(input: TokenStream) -> TokenStream {
         }
     };
 
-    impl_system_base.into()
+    with_framework_imports(impl_system_base).into()
 }
 
 #[proc_macro_derive(AbstractSystem)]
@@ -119,14 +120,88 @@ pub fn derive_abstract_system(input: TokenStream) -> TokenStream {
         }
     };
 
-    impl_abstract_system.into()
+    with_framework_imports(impl_abstract_system).into()
 }
 
-#[proc_macro_derive(System)]
+// Struct-level #[system(...)] attributes name the methods overriding the leaf system defaults:
+// time_derivatives = "method" for do_calc_time_derivatives, and direct_feedthroughs = "method"
+// for get_direct_feedthroughs. The flag scalar_convertible forwards do_to_scalar_type to the
+// struct's ScalarConvertible implementation.
+#[proc_macro_derive(System, attributes(system))]
 pub fn derive_system(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let name = &input.ident;
 
+    let mut time_derivatives = None;
+    let mut direct_feedthroughs = None;
+    let mut scalar_convertible = false;
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("system"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("scalar_convertible") {
+                scalar_convertible = true;
+                return Ok(());
+            }
+            let method: LitStr = meta.value()?.parse()?;
+            let method = Ident::new(&method.value(), method.span());
+            if meta.path.is_ident("time_derivatives") {
+                time_derivatives = Some(method);
+            } else if meta.path.is_ident("direct_feedthroughs") {
+                direct_feedthroughs = Some(method);
+            } else {
+                return Err(meta.error("unsupported system attribute"));
+            }
+            Ok(())
+        })
+        .unwrap_or_else(|error| panic!("{}", error));
+    }
+
+    let impl_time_derivatives = time_derivatives.map(|method| {
+        quote! {
+            fn do_calc_time_derivatives(
+                &self,
+                context: &Self::CN,
+                derivatives: &mut <<Self::CN as Context<T>>::S as State<T>>::CS,
+            ) {
+                Self::#method(self, context, derivatives)
+            }
+        }
+    });
+    let impl_scalar_conversion = scalar_convertible.then(|| {
+        quote! {
+            fn do_to_scalar_type(&self, scalar_type: TypeId) -> Option<Box<dyn Any>> {
+                convert_to_scalar_type(self, scalar_type)
+            }
+        }
+    });
+    let impl_direct_feedthroughs = match direct_feedthroughs {
+        Some(method) => quote! {
+            fn has_direct_feedthrough(
+                &self,
+                input_port_index: &InputPortIndex,
+                output_port_index: &OutputPortIndex,
+            ) -> bool {
+                Self::#method(self).contains(&(input_port_index.clone(), output_port_index.clone()))
+            }
+
+            fn get_direct_feedthroughs(&self) -> Vec<(InputPortIndex, OutputPortIndex)> {
+                Self::#method(self)
+            }
+        },
+        None => quote! {
+            fn has_direct_feedthrough(
+                &self,
+                input_port_index: &InputPortIndex,
+                output_port_index: &OutputPortIndex,
+            ) -> bool {
+                LeafSystem::<T>::has_direct_feedthrough(self, input_port_index, output_port_index)
+            }
+        },
+    };
+
     let _fields = match &input.data {
         Data::Struct(data) => match &data.fields {
             Fields::Named(fields) => &fields.named,
@@ -188,10 +263,6 @@ pub fn derive_system(input: TokenStream) -> TokenStream {
                 self.system_weak_link.clone().unwrap()
             }
 
-            fn time_derivatives_cache_index(&self) -> &CacheIndex {
-                &self.time_derivatives_cache_index
-            }
-
             fn allocate_context(&self) -> Rc<RefCell<Self::CN>> {
                 LeafSystem::<T>::allocate_context(self)
             }
@@ -200,17 +271,53 @@ pub fn derive_system(input: TokenStream) -> TokenStream {
                 LeafSystem::<T>::do_allocate_input(self, input_port)
             }
 
-            fn allocate_time_derivatives(&mut self) -> Box<<<Self::CN as Context<T>>::S as State<T>>::CS> {
+            fn allocate_time_derivatives(&self) -> Box<<<Self::CN as Context<T>>::S as State<T>>::CS> {
                 LeafSystem::<T>::allocate_time_derivatives(self)
             }
 
             fn set_default_state(&self, context: &mut Self::CN) {
                 LeafSystem::<T>::set_default_state(self, context)
             }
+
+            fn set_default_parameters(&self, context: &mut Self::CN) {
+                LeafSystem::<T>::set_default_parameters(self, context)
+            }
+
+            #impl_direct_feedthroughs
+
+            fn periodic_discrete_update_timings(&self) -> Vec<PeriodicEventData> {
+                LeafSystem::<T>::periodic_discrete_update_timings(self)
+            }
+
+            fn periodic_unrestricted_update_timings(&self) -> Vec<PeriodicEventData> {
+                LeafSystem::<T>::periodic_unrestricted_update_timings(self)
+            }
+
+            fn calc_discrete_variable_updates(
+                &self,
+                context: &Self::CN,
+                timings: &[PeriodicEventData],
+                discrete_state: &mut DiscreteValues<T>,
+            ) {
+                LeafSystem::<T>::calc_discrete_variable_updates(self, context, timings, discrete_state)
+            }
+
+            fn calc_unrestricted_updates(
+                &self,
+                context: &Self::CN,
+                timings: &[PeriodicEventData],
+                state: &mut <Self::CN as Context<T>>::S,
+            ) {
+                LeafSystem::<T>::calc_unrestricted_updates(self, context, timings, state)
+            }
+
+            #impl_time_derivatives
+
+            #impl_scalar_conversion
         }
     };
 
-    impl_system.into()
+    with_framework_imports(impl_system).into()
 }
 
 #[proc_macro_derive(LeafSystem)]
@@ -244,6 +351,46 @@ pub fn derive_leaf_system(input: TokenStream) -> TokenStream {
                 &mut self.model_continuous_state_vector
             }
 
+            fn model_discrete_state(&self) -> &DiscreteValues<T> {
+                &self.model_discrete_state
+            }
+
+            fn model_discrete_state_mut(&mut self) -> &mut DiscreteValues<T> {
+                &mut self.model_discrete_state
+            }
+
+            fn discrete_update_events(&self) -> &Vec<DiscreteUpdateEvent<T>> {
+                &self.discrete_update_events
+            }
+
+            fn discrete_update_events_mut(&mut self) -> &mut Vec<DiscreteUpdateEvent<T>> {
+                &mut self.discrete_update_events
+            }
+
+            fn model_abstract_state(&self) -> &AbstractValues {
+                &self.model_abstract_state
+            }
+
+            fn model_abstract_state_mut(&mut self) -> &mut AbstractValues {
+                &mut self.model_abstract_state
+            }
+
+            fn unrestricted_update_events(&self) -> &Vec<UnrestrictedUpdateEvent<T>> {
+                &self.unrestricted_update_events
+            }
+
+            fn unrestricted_update_events_mut(&mut self) -> &mut Vec<UnrestrictedUpdateEvent<T>> {
+                &mut self.unrestricted_update_events
+            }
+
+            fn model_parameters(&self) -> &Parameters<T> {
+                &self.model_parameters
+            }
+
+            fn model_parameters_mut(&mut self) -> &mut Parameters<T> {
+                &mut self.model_parameters
+            }
+
             fn leaf_output_port(&self, output_port_index: &OutputPortIndex) -> &LeafOutputPort<T> {
                 &self.output_ports[output_port_index]
             }
@@ -261,5 +408,390 @@ pub fn derive_leaf_system(input: TokenStream) -> TokenStream {
         }
     };
 
-    impl_leaf_system.into()
+    with_framework_imports(impl_leaf_system).into()
+}
+
+// Declares a leaf system from only its own fields. The attribute adds the bookkeeping fields every
+// leaf system needs, derives SystemBase, AbstractSystem, System and LeafSystem, and generates a
+// constructor `new` taking the remaining fields in order. The System is named after the struct in
+// snake_case, e.g. pid_controller for PIDController, unless given as #[leaf_system(name = "...")].
+//
+// Fields of type InputPortIndex or OutputPortIndex become ports named after the field:
+//   #[input(vector, size = N)]
+//   #[input(abstract, model = "expr")]
+//   #[output(vector, size = N, calc = "method")]
+//   #[output(abstract, model = "expr", calc = "method")]
+// Outputs also take prerequisites = "expr", a Vec<DependencyTicket> that defaults to all sources.
+//
+// The continuous state is declared on the struct with
+//   #[continuous_state(q = N, v = N, z = N, derivatives = "method")]
+// where method has the signature of System::do_calc_time_derivatives.
+//
+// Sizes are integer literals or expressions in quotes, which can use the constructor's arguments.
+//
+// Fields marked #[default] start as Default::default() instead of being constructor arguments.
+// Systems whose ports, state or parameters the attributes cannot express, e.g. a number of ports
+// given at construction, write their own `new`: #[leaf_system(constructor = "allocate")] renames
+// the generated constructor to a private `allocate`, which `new` calls before declaring the rest.
+#[proc_macro_attribute]
+pub fn leaf_system(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as DeriveInput);
+    let name = &input.ident;
+    let vis = &input.vis;
+    let generics = &input.generics;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let mut system_name = to_snake_case(&name.to_string());
+    let mut constructor = None;
+    let attr_parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("name") {
+            system_name = meta.value()?.parse::<LitStr>()?.value();
+            Ok(())
+        } else if meta.path.is_ident("constructor") {
+            constructor = Some(meta.value()?.parse::<LitStr>()?.parse::<Ident>()?);
+            Ok(())
+        } else {
+            Err(meta.error("unsupported leaf_system attribute"))
+        }
+    });
+    parse_macro_input!(attr with attr_parser);
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => panic!("leaf_system can only be used on structs with named fields"),
+        },
+        _ => panic!("leaf_system can only be used on structs"),
+    };
+
+    // Continuous state
+    let mut attrs = vec![];
+    let mut declare_continuous_state = None;
+    let mut time_derivatives = None;
+    for attr in input.attrs.iter() {
+        if !attr.path().is_ident("continuous_state") {
+            attrs.push(attr);
+            continue;
+        }
+        let (mut q, mut v, mut z) = (quote! { 0 }, quote! { 0 }, quote! { 0 });
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("q") {
+                q = parse_size(&meta)?;
+            } else if meta.path.is_ident("v") {
+                v = parse_size(&meta)?;
+            } else if meta.path.is_ident("z") {
+                z = parse_size(&meta)?;
+            } else if meta.path.is_ident("derivatives") {
+                time_derivatives = Some(meta.value()?.parse::<LitStr>()?);
+            } else {
+                return Err(meta.error("unsupported continuous_state attribute"));
+            }
+            Ok(())
+        })
+        .unwrap_or_else(|error| panic!("{}", error));
+        declare_continuous_state = Some(quote! {
+            let num_q: usize = #q;
+            let num_v: usize = #v;
+            let num_z: usize = #z;
+        });
+    }
+    let system_attr =
+        time_derivatives.map(|method| quote! { #[system(time_derivatives = #method)] });
+
+    // Ports
+    let mut user_fields = vec![];
+    let mut params = vec![];
+    let mut field_inits = vec![];
+    let mut port_values = vec![];
+    let mut declare_input_ports = vec![];
+    let mut declare_output_ports = vec![];
+    for field in fields.iter() {
+        let ident = field.ident.as_ref().unwrap();
+        let port_name = ident.to_string();
+        let mut user_field = field.clone();
+        user_field.attrs.retain(|attr| {
+            !attr.path().is_ident("input")
+                && !attr.path().is_ident("output")
+                && !attr.path().is_ident("default")
+        });
+        user_fields.push(user_field);
+
+        if field
+            .attrs
+            .iter()
+            .any(|attr| attr.path().is_ident("default"))
+        {
+            field_inits.push(quote! { #ident: Default::default() });
+            continue;
+        }
+        let Some(port) = parse_port(field) else {
+            let ty = &field.ty;
+            params.push(quote! { #ident: #ty });
+            field_inits.push(quote! { #ident });
+            continue;
+        };
+        let size = format_ident!("{}_size", ident);
+        let model = format_ident!("{}_model", ident);
+        if let Some(size_value) = &port.size {
+            port_values.push(quote! { let #size: usize = #size_value; });
+        }
+        if let Some(model_value) = &port.model {
+            port_values.push(quote! { let #model = #model_value; });
+        }
+        if port.is_input {
+            let index = declare_input_ports.len();
+            field_inits.push(quote! { #ident: InputPortIndex::new(#index) });
+            declare_input_ports.push(if port.is_vector {
+                quote! { system.borrow_mut().declare_vector_input_port(#port_name.to_string(), #size); }
+            } else {
+                quote! {
+                    system
+                        .borrow_mut()
+                        .declare_abstract_input_port(#port_name.to_string(), &Value::new(#model));
+                }
+            });
+        } else {
+            let index = declare_output_ports.len();
+            field_inits.push(quote! { #ident: OutputPortIndex::new(#index) });
+            let calc = port
+                .calc
+                .unwrap_or_else(|| panic!("output {} needs a calc method", port_name));
+            let prerequisites = port
+                .prerequisites
+                .unwrap_or_else(|| quote! { vec![DependencyTicket::AllSources] });
+            declare_output_ports.push(if port.is_vector {
+                quote! {
+                    let calc = system.borrow().make_vector_output_calc(Self::#calc);
+                    system.borrow_mut().declare_vector_output_port_with_prerequisites(
+                        #port_name.to_string(),
+                        #size,
+                        calc,
+                        #prerequisites,
+                    );
+                }
+            } else {
+                quote! {
+                    let calc = system.borrow().make_abstract_output_calc(Self::#calc);
+                    system.borrow_mut().declare_abstract_output_port_with_prerequisites(
+                        #port_name.to_string(),
+                        <Self as LeafSystem<T>>::make_allocate_callback(#model),
+                        calc,
+                        #prerequisites,
+                    );
+                }
+            });
+        }
+    }
+    let continuous_state = declare_continuous_state.as_ref().map(|_| {
+        quote! { system.borrow_mut().declare_continuous_state(num_q, num_v, num_z); }
+    });
+
+    let framework = quote! { ::atlas::systems::framework };
+    let leaf_system = quote! {
+        #[derive(
+            ::atlas_derives::LeafSystem,
+            ::atlas_derives::AbstractSystem,
+            ::atlas_derives::System,
+            ::atlas_derives::SystemBase
+        )]
+        #system_attr
+        #(#attrs)*
+        #vis struct #name #generics #where_clause {
+            #(#user_fields,)*
+            name: String,
+            input_ports: Vec<#framework::input_port::InputPort<T>>,
+            output_ports: Vec<#framework::leaf_output_port::LeafOutputPort<T>>,
+            cache_entries: #framework::cache_entry::CacheEntries,
+            context_sizes: #framework::system_base::ContextSizes,
+            system_id: #framework::framework_common::SystemId,
+            system_weak_link: Option<#framework::diagram::SystemWeakLink<T>>,
+            parent_service: Option<
+                ::std::rc::Weak<
+                    ::std::cell::RefCell<dyn #framework::framework_common::SystemParentServiceInterface>
+                >
+            >,
+            model_input_values: #framework::model_values::ModelValues,
+            model_continuous_state_vector: #framework::basic_vector::BasicVector<T>,
+            model_discrete_state: #framework::discrete_values::DiscreteValues<T>,
+            discrete_update_events: Vec<#framework::event::DiscreteUpdateEvent<T>>,
+            model_abstract_state: #framework::abstract_values::AbstractValues,
+            unrestricted_update_events: Vec<#framework::event::UnrestrictedUpdateEvent<T>>,
+            model_parameters: #framework::parameters::Parameters<T>,
+            implicit_time_derivatives_residual_size: Option<usize>,
+        }
+    };
+    // A renamed constructor is private, for the system's own constructor to build on.
+    let signature = match constructor {
+        Some(constructor) => quote! { fn #constructor },
+        None => quote! { pub fn new },
+    };
+    let impl_new = quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            #[allow(clippy::too_many_arguments)]
+            #signature(#(#params),*) -> Rc<RefCell<Self>> {
+                // Sizes and models may depend on the arguments, which move into the system.
+                #declare_continuous_state
+                #(#port_values)*
+
+                let system = make_leaf_system(|system_weak_link| Self {
+                    #(#field_inits,)*
+                    name: #system_name.to_string(),
+                    input_ports: vec![],
+                    output_ports: vec![],
+                    cache_entries: vec![],
+                    context_sizes: ContextSizes::default(),
+                    system_id: SystemId::get_new_id(),
+                    system_weak_link: Some(system_weak_link),
+                    parent_service: None,
+                    model_input_values: ModelValues::default(),
+                    model_continuous_state_vector: BasicVector::<T>::zeros(0),
+                    model_discrete_state: DiscreteValues::default(),
+                    discrete_update_events: vec![],
+                    model_abstract_state: AbstractValues::default(),
+                    unrestricted_update_events: vec![],
+                    model_parameters: Parameters::default(),
+                    implicit_time_derivatives_residual_size: None,
+                });
+
+                #continuous_state
+                #(#declare_input_ports)*
+                #(#declare_output_ports)*
+
+                system
+            }
+        }
+    };
+
+    let impl_new = with_framework_imports(impl_new);
+    quote! {
+        #leaf_system
+        #impl_new
+    }
+    .into()
+}
+
+// Wraps the generated items in an anonymous const that imports the names they use, so that the
+// module expanding a macro only imports what its own code needs. The paths start at ::atlas, which
+// the atlas crate also names itself, so that the macros expand both in atlas and in its users.
+fn with_framework_imports(items: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    quote! {
+        #[allow(unused_imports)]
+        const _: () = {
+            use ::std::any::{Any, TypeId};
+            use ::std::cell::RefCell;
+            use ::std::rc::{Rc, Weak};
+
+            use ::atlas::common::atlas_scalar::AtlasScalar;
+            use ::atlas::common::value::{AbstractValue, Value};
+            use ::atlas::systems::framework::abstract_values::AbstractValues;
+            use ::atlas::systems::framework::basic_vector::BasicVector;
+            use ::atlas::systems::framework::cache_entry::{CacheEntries, CacheEntry};
+            use ::atlas::systems::framework::context::Context;
+            use ::atlas::systems::framework::diagram::SystemWeakLink;
+            use ::atlas::systems::framework::discrete_values::DiscreteValues;
+            use ::atlas::systems::framework::event::{
+                DiscreteUpdateEvent, PeriodicEventData, UnrestrictedUpdateEvent,
+            };
+            use ::atlas::systems::framework::framework_common::{
+                CacheIndex, DependencyTicket, InputPortIndex, OutputPortIndex, SystemId,
+                SystemParentServiceInterface,
+            };
+            use ::atlas::systems::framework::input_port::InputPort;
+            use ::atlas::systems::framework::input_port_base::InputPortBase;
+            use ::atlas::systems::framework::leaf_context::LeafContext;
+            use ::atlas::systems::framework::leaf_output_port::LeafOutputPort;
+            use ::atlas::systems::framework::leaf_system::{make_leaf_system, LeafSystem};
+            use ::atlas::systems::framework::model_values::ModelValues;
+            use ::atlas::systems::framework::output_port::OutputPort;
+            use ::atlas::systems::framework::output_port_base::OutputPortBase;
+            use ::atlas::systems::framework::parameters::Parameters;
+            use ::atlas::systems::framework::state::State;
+            use ::atlas::systems::framework::system::{AbstractSystem, System};
+            use ::atlas::systems::framework::system_base::{ContextSizes, SystemBase};
+            use ::atlas::systems::framework::system_scalar_converter::convert_to_scalar_type;
+
+            #items
+        };
+    }
+}
+
+struct PortAttribute {
+    is_input: bool,
+    is_vector: bool,
+    size: Option<proc_macro2::TokenStream>,
+    model: Option<proc_macro2::TokenStream>,
+    calc: Option<Ident>,
+    prerequisites: Option<proc_macro2::TokenStream>,
+}
+
+fn parse_port(field: &Field) -> Option<PortAttribute> {
+    let attr = field
+        .attrs
+        .iter()
+        .find(|attr| attr.path().is_ident("input") || attr.path().is_ident("output"))?;
+    let mut port = PortAttribute {
+        is_input: attr.path().is_ident("input"),
+        is_vector: true,
+        size: None,
+        model: None,
+        calc: None,
+        prerequisites: None,
+    };
+    attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("vector") {
+            port.is_vector = true;
+        } else if meta.path.is_ident("abstract") {
+            port.is_vector = false;
+        } else if meta.path.is_ident("size") {
+            port.size = Some(parse_size(&meta)?);
+        } else if meta.path.is_ident("model") {
+            port.model = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+        } else if meta.path.is_ident("calc") {
+            let method = meta.value()?.parse::<LitStr>()?;
+            port.calc = Some(Ident::new(&method.value(), method.span()));
+        } else if meta.path.is_ident("prerequisites") && !port.is_input {
+            port.prerequisites = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+        } else {
+            return Err(meta.error("unsupported port attribute"));
+        }
+        Ok(())
+    })
+    .unwrap_or_else(|error| panic!("{}", error));
+
+    let ident = field.ident.as_ref().unwrap();
+    if port.is_vector && port.size.is_none() {
+        panic!("vector port {} needs a size", ident);
+    }
+    if !port.is_vector && port.model.is_none() {
+        panic!("abstract port {} needs a model", ident);
+    }
+    Some(port)
+}
+
+// Parses a size given as an integer literal or as an expression in quotes.
+fn parse_size(meta: &ParseNestedMeta) -> syn::Result<proc_macro2::TokenStream> {
+    match meta.value()?.parse::<Lit>()? {
+        Lit::Int(size) => Ok(quote! { #size }),
+        Lit::Str(size) => size.parse(),
+        _ => Err(meta.error("a size must be an integer or an expression in quotes")),
+    }
+}
+
+// Starts a word at each capital following a lowercase letter or a digit, and at the last capital
+// of a run followed by a lowercase letter, so that PIDController becomes pid_controller.
+fn to_snake_case(name: &str) -> String {
+    let chars = name.chars().collect::<Vec<_>>();
+    let mut snake_case = String::new();
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let after_word = chars[i - 1].is_lowercase() || chars[i - 1].is_ascii_digit();
+            let ends_run = chars[i - 1].is_uppercase()
+                && chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+            if after_word || ends_run {
+                snake_case.push('_');
+            }
+        }
+        snake_case.extend(c.to_lowercase());
+    }
+    snake_case
 }
@@ -1,4 +1,6 @@
+pub mod atlas_error;
 pub mod atlas_scalar;
+pub mod autodiff;
 pub mod identifier;
 pub mod type_safe_index;
 pub mod value;
@@ -0,0 +1,150 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::systems::framework::framework_common::PortDataType;
+
+// The recoverable errors reported while building a Diagram, validating a context or converting a
+// System to another scalar type. Ports are identified by the name of their System and their own
+// name. A connection runs from a source port, either an output port or the subsystem input port
+// that first defined an exported Diagram input, to a destination input port.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AtlasError {
+    PortDataTypeMismatch {
+        source_system: String,
+        source_port: String,
+        source_data_type: PortDataType,
+        destination_system: String,
+        destination_port: String,
+        destination_data_type: PortDataType,
+    },
+    PortSizeMismatch {
+        source_system: String,
+        source_port: String,
+        source_size: usize,
+        destination_system: String,
+        destination_port: String,
+        destination_size: usize,
+    },
+    AbstractTypeMismatch {
+        source_system: String,
+        source_port: String,
+        destination_system: String,
+        destination_port: String,
+    },
+    AlreadyConnected {
+        input_system: String,
+        input_port: String,
+    },
+    UnknownSystem {
+        system: String,
+    },
+    // The (system, port) pairs around the loop, alternating between an output port and the input
+    // port it feeds.
+    AlgebraicLoop {
+        cycle: Vec<(String, String)>,
+    },
+    ContextMismatch {
+        system: String,
+        system_id: usize,
+        context_system: String,
+        context_system_id: usize,
+    },
+    UnsupportedScalarType {
+        system: String,
+        scalar_type: String,
+    },
+    EmptyBuilder,
+}
+
+impl fmt::Display for AtlasError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AtlasError::PortDataTypeMismatch {
+                source_system,
+                source_port,
+                source_data_type,
+                destination_system,
+                destination_port,
+                destination_data_type,
+            } => write!(
+                f,
+                "Cannot mix vector-valued and abstract-valued ports while connecting port {} of System {} ({:?}) to input port {} of System {} ({:?})",
+                source_port,
+                source_system,
+                source_data_type,
+                destination_port,
+                destination_system,
+                destination_data_type
+            ),
+            AtlasError::PortSizeMismatch {
+                source_system,
+                source_port,
+                source_size,
+                destination_system,
+                destination_port,
+                destination_size,
+            } => write!(
+                f,
+                "Mismatched vector sizes while connecting port {} of System {} (size {}) to input port {} of System {} (size {})",
+                source_port,
+                source_system,
+                source_size,
+                destination_port,
+                destination_system,
+                destination_size
+            ),
+            AtlasError::AbstractTypeMismatch {
+                source_system,
+                source_port,
+                destination_system,
+                destination_port,
+            } => write!(
+                f,
+                "Mismatched value types while connecting port {} of System {} to input port {} of System {}",
+                source_port, source_system, destination_port, destination_system
+            ),
+            AtlasError::AlreadyConnected {
+                input_system,
+                input_port,
+            } => write!(
+                f,
+                "Input port {} of System {} is already connected",
+                input_port, input_system
+            ),
+            AtlasError::UnknownSystem { system } => write!(
+                f,
+                "System {} has not been registered to this DiagramBuilder",
+                system
+            ),
+            AtlasError::AlgebraicLoop { cycle } => {
+                write!(f, "Algebraic loop detected through the ports")?;
+                for (index, (system, port)) in cycle.iter().enumerate() {
+                    let separator = if index == 0 { ": " } else { " -> " };
+                    write!(f, "{}{} of System {}", separator, port, system)?;
+                }
+                Ok(())
+            }
+            AtlasError::ContextMismatch {
+                system,
+                system_id,
+                context_system,
+                context_system_id,
+            } => write!(
+                f,
+                "A context created for System {} (id {}) was passed to System {} (id {})",
+                context_system, context_system_id, system, system_id
+            ),
+            AtlasError::UnsupportedScalarType {
+                system,
+                scalar_type,
+            } => write!(
+                f,
+                "System {} cannot be converted to the scalar type {}",
+                system, scalar_type
+            ),
+            AtlasError::EmptyBuilder => write!(f, "Cannot compile an empty DiagramBuilder"),
+        }
+    }
+}
+
+impl Error for AtlasError {}
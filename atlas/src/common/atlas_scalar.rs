@@ -1,6 +1,57 @@
 use num_traits::NumAssign;
+use std::any::Any;
 use std::fmt::Debug;
 
-pub trait AtlasScalar: NumAssign + Clone + Debug + Default + 'static {}
+// Scalars are ordered, so Systems can compare signals to limits.
+pub trait AtlasScalar: NumAssign + PartialOrd + Clone + Debug + Default + 'static {
+    // Values move between scalar types through f64, which keeps only the value itself.
+    fn from_f64(value: f64) -> Self;
+    fn as_f64(&self) -> f64;
 
-impl AtlasScalar for f64 {}
+    // The trigonometric functions, which scalars carrying derivatives implement by the chain rule.
+    fn sin(&self) -> Self;
+    fn cos(&self) -> Self;
+
+    fn cast_scalar<U: AtlasScalar>(&self) -> U {
+        match (self as &dyn Any).downcast_ref::<U>() {
+            Some(value) => value.clone(),
+            None => U::from_f64(self.as_f64()),
+        }
+    }
+}
+
+impl AtlasScalar for f64 {
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+
+    fn as_f64(&self) -> f64 {
+        *self
+    }
+
+    fn sin(&self) -> Self {
+        f64::sin(*self)
+    }
+
+    fn cos(&self) -> Self {
+        f64::cos(*self)
+    }
+}
+
+impl AtlasScalar for f32 {
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+
+    fn as_f64(&self) -> f64 {
+        *self as f64
+    }
+
+    fn sin(&self) -> Self {
+        f32::sin(*self)
+    }
+
+    fn cos(&self) -> Self {
+        f32::cos(*self)
+    }
+}
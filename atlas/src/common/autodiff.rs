@@ -0,0 +1,270 @@
+use std::cmp::Ordering;
+use std::ops::{
+    Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, RemAssign, Sub, SubAssign,
+};
+
+extern crate nalgebra as na;
+
+use num_traits::{Num, One, Zero};
+
+use crate::common::atlas_scalar::AtlasScalar;
+
+// A scalar for forward-mode automatic differentiation: a value with its partial derivatives with
+// respect to some independent variables. Empty derivatives stand for a constant, whose partials
+// are all zero, so constants mix with variables of any number of partials.
+#[derive(Clone, Debug, Default)]
+pub struct AutoDiffXd {
+    value: f64,
+    derivatives: na::DVector<f64>,
+}
+
+impl AutoDiffXd {
+    pub fn new(value: f64, derivatives: na::DVector<f64>) -> Self {
+        Self { value, derivatives }
+    }
+
+    pub fn constant(value: f64) -> Self {
+        Self::new(value, na::DVector::zeros(0))
+    }
+
+    // The independent variable with the given index among num_variables.
+    pub fn variable(value: f64, index: usize, num_variables: usize) -> Self {
+        let mut derivatives = na::DVector::zeros(num_variables);
+        derivatives[index] = 1.0;
+        Self::new(value, derivatives)
+    }
+
+    // Independent variables for each element of values, with the partials of values[i] in
+    // column i.
+    pub fn variables(values: &na::DVector<f64>) -> na::DVector<Self> {
+        na::DVector::from_iterator(
+            values.len(),
+            values
+                .iter()
+                .enumerate()
+                .map(|(index, value)| Self::variable(*value, index, values.len())),
+        )
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    pub fn derivatives(&self) -> &na::DVector<f64> {
+        &self.derivatives
+    }
+
+    // The linear combination a * self.derivatives + b * other.derivatives, treating empty
+    // derivatives as zeros.
+    fn combine_derivatives(&self, a: f64, other: &Self, b: f64) -> na::DVector<f64> {
+        match (self.derivatives.is_empty(), other.derivatives.is_empty()) {
+            (true, true) => na::DVector::zeros(0),
+            (false, true) => &self.derivatives * a,
+            (true, false) => &other.derivatives * b,
+            (false, false) => {
+                assert_eq!(
+                    self.derivatives.len(),
+                    other.derivatives.len(),
+                    "AutoDiffXd: the numbers of partial derivatives differ"
+                );
+                &self.derivatives * a + &other.derivatives * b
+            }
+        }
+    }
+}
+
+// Derivatives are not compared, which lets a constant equal a variable of the same value.
+impl PartialEq for AutoDiffXd {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl PartialOrd for AutoDiffXd {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.value.partial_cmp(&other.value)
+    }
+}
+
+impl Add for AutoDiffXd {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        let derivatives = self.combine_derivatives(1.0, &other, 1.0);
+        Self::new(self.value + other.value, derivatives)
+    }
+}
+
+impl Sub for AutoDiffXd {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        let derivatives = self.combine_derivatives(1.0, &other, -1.0);
+        Self::new(self.value - other.value, derivatives)
+    }
+}
+
+impl Mul for AutoDiffXd {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        let derivatives = self.combine_derivatives(other.value, &other, self.value);
+        Self::new(self.value * other.value, derivatives)
+    }
+}
+
+impl Div for AutoDiffXd {
+    type Output = Self;
+
+    fn div(self, other: Self) -> Self {
+        let value = self.value / other.value;
+        let derivatives = self.combine_derivatives(1.0 / other.value, &other, -value / other.value);
+        Self::new(value, derivatives)
+    }
+}
+
+impl Rem for AutoDiffXd {
+    type Output = Self;
+
+    // self % other is self - trunc(self / other) * other.
+    fn rem(self, other: Self) -> Self {
+        let quotient = (self.value / other.value).trunc();
+        let derivatives = self.combine_derivatives(1.0, &other, -quotient);
+        Self::new(self.value % other.value, derivatives)
+    }
+}
+
+impl Neg for AutoDiffXd {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.value, -self.derivatives)
+    }
+}
+
+impl AddAssign for AutoDiffXd {
+    fn add_assign(&mut self, other: Self) {
+        *self = self.clone() + other;
+    }
+}
+
+impl SubAssign for AutoDiffXd {
+    fn sub_assign(&mut self, other: Self) {
+        *self = self.clone() - other;
+    }
+}
+
+impl MulAssign for AutoDiffXd {
+    fn mul_assign(&mut self, other: Self) {
+        *self = self.clone() * other;
+    }
+}
+
+impl DivAssign for AutoDiffXd {
+    fn div_assign(&mut self, other: Self) {
+        *self = self.clone() / other;
+    }
+}
+
+impl RemAssign for AutoDiffXd {
+    fn rem_assign(&mut self, other: Self) {
+        *self = self.clone() % other;
+    }
+}
+
+impl Zero for AutoDiffXd {
+    fn zero() -> Self {
+        Self::constant(0.0)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.value == 0.0
+    }
+}
+
+impl One for AutoDiffXd {
+    fn one() -> Self {
+        Self::constant(1.0)
+    }
+}
+
+impl Num for AutoDiffXd {
+    type FromStrRadixErr = <f64 as Num>::FromStrRadixErr;
+
+    fn from_str_radix(str: &str, radix: u32) -> Result<Self, Self::FromStrRadixErr> {
+        f64::from_str_radix(str, radix).map(Self::constant)
+    }
+}
+
+impl AtlasScalar for AutoDiffXd {
+    fn from_f64(value: f64) -> Self {
+        Self::constant(value)
+    }
+
+    fn as_f64(&self) -> f64 {
+        self.value
+    }
+
+    fn sin(&self) -> Self {
+        Self::new(self.value.sin(), &self.derivatives * self.value.cos())
+    }
+
+    fn cos(&self) -> Self {
+        Self::new(self.value.cos(), &self.derivatives * -self.value.sin())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arithmetic() {
+        let x = AutoDiffXd::variable(2.0, 0, 2);
+        let y = AutoDiffXd::variable(3.0, 1, 2);
+
+        // f(x, y) = (x * y + x) / y - 1
+        let f = (x.clone() * y.clone() + x) / y - AutoDiffXd::one();
+        assert_eq!(f.value(), 2.0 * 3.0 / 3.0 + 2.0 / 3.0 - 1.0);
+        // df/dx = 1 + 1 / y, df/dy = -x / y^2
+        assert!((f.derivatives()[0] - (1.0 + 1.0 / 3.0)).abs() < 1e-12);
+        assert!((f.derivatives()[1] + 2.0 / 9.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_constants() {
+        let x = AutoDiffXd::variable(2.0, 0, 1);
+        let mut f = AutoDiffXd::from_f64(3.0);
+        f *= x;
+        f -= AutoDiffXd::from_f64(1.0);
+        assert_eq!(f.value(), 5.0);
+        assert_eq!(*f.derivatives(), na::DVector::from_vec(vec![3.0]));
+        assert!((AutoDiffXd::constant(1.0) + AutoDiffXd::constant(2.0))
+            .derivatives()
+            .is_empty());
+        assert_eq!(f.cast_scalar::<f64>(), 5.0);
+    }
+
+    #[test]
+    fn test_trigonometric_functions() {
+        let x = AutoDiffXd::variable(0.5, 0, 1);
+        let y = (x.clone() * x.clone()).sin() + x.cos();
+        assert_eq!(y.value(), 0.25f64.sin() + 0.5f64.cos());
+        // dy/dx = 2 x cos(x^2) - sin(x)
+        assert!((y.derivatives()[0] - (0.25f64.cos() - 0.5f64.sin())).abs() < 1e-12);
+        assert!(AutoDiffXd::constant(1.0).sin().derivatives().is_empty());
+    }
+
+    #[test]
+    fn test_matrix_product() {
+        let a = na::DMatrix::from_row_slice(2, 2, &[1.0, 2.0, 3.0, 4.0]);
+        let x = AutoDiffXd::variables(&na::DVector::from_vec(vec![1.0, -1.0]));
+        let y = a.map(AutoDiffXd::constant) * x;
+
+        assert_eq!(y[0].value(), -1.0);
+        assert_eq!(y[1].value(), -1.0);
+        // The Jacobian of A x is A.
+        assert_eq!(*y[0].derivatives(), na::DVector::from_vec(vec![1.0, 2.0]));
+        assert_eq!(*y[1].derivatives(), na::DVector::from_vec(vec![3.0, 4.0]));
+    }
+}
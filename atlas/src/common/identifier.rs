@@ -1,5 +1,10 @@
 use std::cmp::PartialEq;
 use std::marker::PhantomData;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Shared by every identifier type. It starts at 1, so that a default identifier never matches
+// one handed out by get_new_id.
+static NEXT_IDENTIFIER_VALUE: AtomicUsize = AtomicUsize::new(1);
 
 #[derive(Clone, Default, PartialEq)]
 pub struct Identifier<Tag: Default> {
@@ -14,6 +19,9 @@ impl<Tag: Default> Identifier<Tag> {
             _marker: PhantomData::<Tag>,
         }
     }
+    pub fn get_new_id() -> Self {
+        Self::new(NEXT_IDENTIFIER_VALUE.fetch_add(1, Ordering::Relaxed))
+    }
     pub fn value(&self) -> &usize {
         &self.value
     }
@@ -1,3 +1,6 @@
+// Lets the paths the atlas-derives macros generate, which start at ::atlas, resolve in this crate.
+extern crate self as atlas;
+
 pub mod common;
 pub mod prelude;
 pub mod systems;
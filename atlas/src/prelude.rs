@@ -1,3 +1,6 @@
+pub use crate::common::atlas_error::AtlasError;
+pub use crate::common::autodiff::AutoDiffXd;
+pub use crate::systems::analysis::simulator::Simulator;
 pub use crate::systems::framework::basic_vector::BasicVector;
 pub use crate::systems::framework::diagram::{Diagram, DiagramExt, SystemLinkExt};
 pub use crate::systems::framework::diagram_builder::DiagramBuilder;
@@ -1,3 +1,4 @@
+pub mod analysis;
 pub mod controllers;
 pub mod framework;
 pub mod primitives;
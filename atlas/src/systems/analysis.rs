@@ -0,0 +1,13 @@
+pub mod bogacki_shampine3_integrator;
+pub mod explicit_euler_integrator;
+pub mod implicit_euler_integrator;
+pub mod implicit_integrator;
+pub mod integrator_base;
+pub mod linearize;
+pub mod radau3_integrator;
+pub mod runge_kutta4_integrator;
+pub mod runge_kutta5_integrator;
+pub mod semi_explicit_euler_integrator;
+pub mod simulator;
+#[cfg(test)]
+pub mod test_utilities;
@@ -0,0 +1,227 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+extern crate nalgebra as na;
+
+use num_traits::Float;
+
+use crate::common::atlas_scalar::AtlasScalar;
+use crate::systems::analysis::integrator_base::{
+    ErrorControl, IntegratorBase, IntegratorStatistics,
+};
+use crate::systems::framework::system::System;
+
+// Third-order Bogacki-Shampine integration with an embedded second-order error estimate.
+pub struct BogackiShampine3Integrator<T, S>
+where
+    T: AtlasScalar + Float,
+    S: System<T> + ?Sized,
+{
+    system: Rc<RefCell<S>>,
+    context: Option<Rc<RefCell<S::CN>>>,
+    max_step_size: T,
+    statistics: IntegratorStatistics<T>,
+    is_initialized: bool,
+    error_control: ErrorControl<T>,
+    error_estimate: Option<na::DVector<T>>,
+}
+
+impl<T, S> BogackiShampine3Integrator<T, S>
+where
+    T: AtlasScalar + Float,
+    S: System<T> + ?Sized,
+{
+    pub fn new(
+        system: &Rc<RefCell<S>>,
+        max_step_size: T,
+        context: Option<Rc<RefCell<S::CN>>>,
+    ) -> Self {
+        BogackiShampine3Integrator {
+            system: system.clone(),
+            context,
+            max_step_size,
+            statistics: IntegratorStatistics::default(),
+            is_initialized: false,
+            error_control: ErrorControl::default(),
+            error_estimate: None,
+        }
+    }
+}
+
+impl<T, S> IntegratorBase<T, S> for BogackiShampine3Integrator<T, S>
+where
+    T: AtlasScalar + Float,
+    S: System<T> + ?Sized,
+{
+    fn system(&self) -> &Rc<RefCell<S>> {
+        &self.system
+    }
+
+    fn context(&self) -> &Option<Rc<RefCell<S::CN>>> {
+        &self.context
+    }
+
+    fn context_mut(&mut self) -> &mut Option<Rc<RefCell<S::CN>>> {
+        &mut self.context
+    }
+
+    fn maximum_step_size(&self) -> T {
+        self.max_step_size
+    }
+
+    fn set_maximum_step_size(&mut self, max_step_size: T) {
+        self.max_step_size = max_step_size;
+    }
+
+    fn statistics(&self) -> &IntegratorStatistics<T> {
+        &self.statistics
+    }
+
+    fn statistics_mut(&mut self) -> &mut IntegratorStatistics<T> {
+        &mut self.statistics
+    }
+
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+
+    fn set_is_initialized(&mut self, is_initialized: bool) {
+        self.is_initialized = is_initialized;
+    }
+
+    fn supports_error_estimation(&self) -> bool {
+        true
+    }
+
+    fn error_estimate_order(&self) -> usize {
+        3
+    }
+
+    fn error_control(&self) -> Option<&ErrorControl<T>> {
+        Some(&self.error_control)
+    }
+
+    fn error_control_mut(&mut self) -> Option<&mut ErrorControl<T>> {
+        Some(&mut self.error_control)
+    }
+
+    fn error_estimate(&self) -> Option<&na::DVector<T>> {
+        self.error_estimate.as_ref()
+    }
+
+    fn do_step(&mut self, h: T) -> bool {
+        let c = |value: f64| T::from(value).unwrap();
+
+        let t0 = self.time();
+        let x0 = self.continuous_state();
+        let k1 = self.eval_time_derivatives();
+
+        self.set_time_and_continuous_state(t0 + h * c(0.5), &(&x0 + &k1 * (h * c(0.5))));
+        let k2 = self.eval_time_derivatives();
+
+        self.set_time_and_continuous_state(t0 + h * c(0.75), &(&x0 + &k2 * (h * c(0.75))));
+        let k3 = self.eval_time_derivatives();
+
+        let x1 = &x0 + (&k1 * c(2.0 / 9.0) + &k2 * c(1.0 / 3.0) + &k3 * c(4.0 / 9.0)) * h;
+        self.set_time_and_continuous_state(t0 + h, &x1);
+        let k4 = self.eval_time_derivatives();
+
+        // Difference between the third-order solution and the embedded second-order one.
+        self.error_estimate = Some(
+            (k1 * c(-5.0 / 72.0) + k2 * c(1.0 / 12.0) + k3 * c(1.0 / 9.0) + k4 * c(-1.0 / 8.0)) * h,
+        );
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate nalgebra as na;
+
+    use super::*;
+    use crate::systems::analysis::test_utilities::spring_mass_system::SpringMassSystem;
+    use crate::systems::framework::context::Context;
+
+    #[test]
+    fn test_spring_mass_accuracy() {
+        let spring_mass_system = SpringMassSystem::<f64>::new(1.0);
+        let context = spring_mass_system.borrow().create_default_context();
+        spring_mass_system
+            .borrow()
+            .set_position_and_velocity(&mut context.borrow_mut(), 1.0, 0.0);
+
+        let mut integrator =
+            BogackiShampine3Integrator::new(&spring_mass_system, 0.1, Some(context.clone()));
+        integrator.set_relative_tolerance(1e-8);
+        integrator.set_absolute_tolerance(1e-8);
+        integrator.initialize();
+        while integrator.integrate_no_further_than(1.0) < 1.0 {}
+
+        let context = context.borrow();
+        assert_eq!(*context.time(), 1.0);
+        let state = context.continuous_state_vector().copy_to_vector();
+        let expected = na::DVector::<f64>::from_vec(vec![1.0_f64.cos(), -1.0_f64.sin()]);
+        assert!((state - expected).norm() < 1e-6);
+        assert!(integrator.smallest_step_size_taken().unwrap() < 0.1);
+    }
+
+    #[test]
+    fn test_step_shrinks_on_error() {
+        let spring_mass_system = SpringMassSystem::<f64>::new(100.0);
+        let context = spring_mass_system.borrow().create_default_context();
+        spring_mass_system
+            .borrow()
+            .set_position_and_velocity(&mut context.borrow_mut(), 1.0, 0.0);
+
+        let mut integrator =
+            BogackiShampine3Integrator::new(&spring_mass_system, 1.0, Some(context.clone()));
+        integrator.set_relative_tolerance(1e-6);
+        integrator.initialize();
+        integrator.integrate_no_further_than(1.0);
+
+        assert!(integrator.num_step_shrinkages_from_error_control() > 0);
+        assert!(*context.borrow().time() < 1.0);
+    }
+
+    #[test]
+    fn test_state_weights() {
+        let spring_mass_system = SpringMassSystem::<f64>::new(1.0);
+        let context = spring_mass_system.borrow().create_default_context();
+        spring_mass_system
+            .borrow()
+            .set_position_and_velocity(&mut context.borrow_mut(), 1.0, 0.0);
+
+        // Ignoring the error in every state accepts the largest step outright.
+        let mut integrator =
+            BogackiShampine3Integrator::new(&spring_mass_system, 0.5, Some(context.clone()));
+        integrator.set_state_weights(
+            na::DVector::<f64>::zeros(1),
+            na::DVector::<f64>::zeros(1),
+            na::DVector::<f64>::zeros(0),
+        );
+        integrator.initialize();
+        integrator.integrate_no_further_than(1.0);
+
+        assert_eq!(*context.borrow().time(), 0.5);
+        assert_eq!(integrator.num_step_shrinkages_from_error_control(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "minimum step size")]
+    fn test_minimum_step_size_violation() {
+        let spring_mass_system = SpringMassSystem::<f64>::new(100.0);
+        let context = spring_mass_system.borrow().create_default_context();
+        spring_mass_system
+            .borrow()
+            .set_position_and_velocity(&mut context.borrow_mut(), 1.0, 0.0);
+
+        let mut integrator =
+            BogackiShampine3Integrator::new(&spring_mass_system, 1.0, Some(context.clone()));
+        integrator.set_relative_tolerance(1e-10);
+        integrator.set_absolute_tolerance(1e-10);
+        integrator.set_minimum_step_size(0.1);
+        integrator.initialize();
+        integrator.integrate_no_further_than(1.0);
+    }
+}
@@ -0,0 +1,123 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use num_traits::Float;
+
+use crate::common::atlas_scalar::AtlasScalar;
+use crate::systems::analysis::integrator_base::{IntegratorBase, IntegratorStatistics};
+use crate::systems::framework::system::System;
+
+// First-order explicit Euler integration: x(t+h) = x(t) + h * xdot(t, x(t)).
+pub struct ExplicitEulerIntegrator<T, S>
+where
+    T: AtlasScalar + Float,
+    S: System<T> + ?Sized,
+{
+    system: Rc<RefCell<S>>,
+    context: Option<Rc<RefCell<S::CN>>>,
+    max_step_size: T,
+    statistics: IntegratorStatistics<T>,
+    is_initialized: bool,
+}
+
+impl<T, S> ExplicitEulerIntegrator<T, S>
+where
+    T: AtlasScalar + Float,
+    S: System<T> + ?Sized,
+{
+    pub fn new(
+        system: &Rc<RefCell<S>>,
+        max_step_size: T,
+        context: Option<Rc<RefCell<S::CN>>>,
+    ) -> Self {
+        ExplicitEulerIntegrator {
+            system: system.clone(),
+            context,
+            max_step_size,
+            statistics: IntegratorStatistics::default(),
+            is_initialized: false,
+        }
+    }
+}
+
+impl<T, S> IntegratorBase<T, S> for ExplicitEulerIntegrator<T, S>
+where
+    T: AtlasScalar + Float,
+    S: System<T> + ?Sized,
+{
+    fn system(&self) -> &Rc<RefCell<S>> {
+        &self.system
+    }
+
+    fn context(&self) -> &Option<Rc<RefCell<S::CN>>> {
+        &self.context
+    }
+
+    fn context_mut(&mut self) -> &mut Option<Rc<RefCell<S::CN>>> {
+        &mut self.context
+    }
+
+    fn maximum_step_size(&self) -> T {
+        self.max_step_size
+    }
+
+    fn set_maximum_step_size(&mut self, max_step_size: T) {
+        self.max_step_size = max_step_size;
+    }
+
+    fn statistics(&self) -> &IntegratorStatistics<T> {
+        &self.statistics
+    }
+
+    fn statistics_mut(&mut self) -> &mut IntegratorStatistics<T> {
+        &mut self.statistics
+    }
+
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+
+    fn set_is_initialized(&mut self, is_initialized: bool) {
+        self.is_initialized = is_initialized;
+    }
+
+    fn do_step(&mut self, h: T) -> bool {
+        let t0 = self.time();
+        let x0 = self.continuous_state();
+        let xdot0 = self.eval_time_derivatives();
+
+        self.set_time_and_continuous_state(t0 + h, &(x0 + xdot0 * h));
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate nalgebra as na;
+
+    use super::*;
+    use crate::systems::analysis::test_utilities::spring_mass_system::SpringMassSystem;
+    use crate::systems::framework::context::Context;
+
+    #[test]
+    fn test_single_step() {
+        let spring_mass_system = SpringMassSystem::<f64>::new(2.0);
+        let context = spring_mass_system.borrow().create_default_context();
+        spring_mass_system
+            .borrow()
+            .set_position_and_velocity(&mut context.borrow_mut(), 1.0, 0.5);
+
+        let mut integrator =
+            ExplicitEulerIntegrator::new(&spring_mass_system, 0.1, Some(context.clone()));
+        integrator.initialize();
+        integrator.integrate_with_single_fixed_step(0.1);
+
+        let context = context.borrow();
+        assert_eq!(*context.time(), 0.1);
+        let state = context.continuous_state_vector().copy_to_vector();
+        assert!((state - na::DVector::<f64>::from_vec(vec![1.05, 0.3])).norm() < 1e-12);
+        assert_eq!(integrator.num_steps_taken(), 1);
+        assert_eq!(integrator.num_derivative_evaluations(), 1);
+    }
+}
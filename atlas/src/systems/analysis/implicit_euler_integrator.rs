@@ -0,0 +1,213 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use num_traits::Float;
+
+use crate::common::atlas_scalar::AtlasScalar;
+use crate::systems::analysis::implicit_integrator::ImplicitIntegrator;
+use crate::systems::analysis::integrator_base::{IntegratorBase, IntegratorStatistics};
+use crate::systems::framework::system::System;
+
+// First-order implicit Euler integration: x(t+h) = x(t) + h * xdot, where xdot satisfies the
+// implicit dynamics at (t+h, x(t+h)).
+pub struct ImplicitEulerIntegrator<T, S>
+where
+    T: AtlasScalar + Float,
+    S: System<T> + ?Sized,
+{
+    system: Rc<RefCell<S>>,
+    context: Option<Rc<RefCell<S::CN>>>,
+    max_step_size: T,
+    statistics: IntegratorStatistics<T>,
+    is_initialized: bool,
+}
+
+impl<T, S> ImplicitEulerIntegrator<T, S>
+where
+    T: AtlasScalar + Float,
+    S: System<T> + ?Sized,
+{
+    pub fn new(
+        system: &Rc<RefCell<S>>,
+        max_step_size: T,
+        context: Option<Rc<RefCell<S::CN>>>,
+    ) -> Self {
+        ImplicitEulerIntegrator {
+            system: system.clone(),
+            context,
+            max_step_size,
+            statistics: IntegratorStatistics::default(),
+            is_initialized: false,
+        }
+    }
+}
+
+impl<T, S> IntegratorBase<T, S> for ImplicitEulerIntegrator<T, S>
+where
+    T: AtlasScalar + Float,
+    S: System<T> + ?Sized,
+{
+    fn system(&self) -> &Rc<RefCell<S>> {
+        &self.system
+    }
+
+    fn context(&self) -> &Option<Rc<RefCell<S::CN>>> {
+        &self.context
+    }
+
+    fn context_mut(&mut self) -> &mut Option<Rc<RefCell<S::CN>>> {
+        &mut self.context
+    }
+
+    fn maximum_step_size(&self) -> T {
+        self.max_step_size
+    }
+
+    fn set_maximum_step_size(&mut self, max_step_size: T) {
+        self.max_step_size = max_step_size;
+    }
+
+    fn statistics(&self) -> &IntegratorStatistics<T> {
+        &self.statistics
+    }
+
+    fn statistics_mut(&mut self) -> &mut IntegratorStatistics<T> {
+        &mut self.statistics
+    }
+
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+
+    fn set_is_initialized(&mut self, is_initialized: bool) {
+        self.is_initialized = is_initialized;
+    }
+
+    fn do_step(&mut self, h: T) -> bool {
+        let max_step_halvings = self.max_step_halvings();
+        self.do_step_with_halving(h, max_step_halvings)
+    }
+}
+
+impl<T, S> ImplicitIntegrator<T, S> for ImplicitEulerIntegrator<T, S>
+where
+    T: AtlasScalar + Float,
+    S: System<T> + ?Sized,
+{
+    fn do_implicit_step(&mut self, h: T) -> bool {
+        let t0 = self.time();
+        let x0 = self.continuous_state();
+
+        let c = [T::one()];
+        let a = [vec![T::one()]];
+        match self.solve_stage_derivatives(t0, &x0, h, &c, &a) {
+            Some(xdot1) => {
+                self.set_time_and_continuous_state(t0 + h, &(&x0 + xdot1 * h));
+                true
+            }
+            None => {
+                self.set_time_and_continuous_state(t0, &x0);
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate nalgebra as na;
+
+    use atlas_derives::leaf_system;
+
+    use super::*;
+    use crate::systems::analysis::test_utilities::spring_mass_system::SpringMassSystem;
+    use crate::systems::framework::context::Context;
+    use crate::systems::framework::continuous_state::ContinuousState;
+    use crate::systems::framework::leaf_context::LeafContext;
+    use crate::systems::framework::leaf_continuous_state::LeafContinuousState;
+
+    // xdot = -x³, whose Newton iteration needs small steps far from the origin.
+    #[leaf_system]
+    #[continuous_state(z = 1, derivatives = "calc_derivatives")]
+    struct CubicDecaySystem<T: AtlasScalar> {}
+
+    impl<T: AtlasScalar> CubicDecaySystem<T> {
+        fn calc_derivatives(
+            &self,
+            context: &LeafContext<T>,
+            derivatives: &mut LeafContinuousState<T>,
+        ) {
+            let x = context.continuous_state_vector()[0].clone();
+            derivatives
+                .vector_mut()
+                .set_at_index(0, T::zero() - x.clone() * x.clone() * x);
+        }
+    }
+
+    #[test]
+    fn test_single_step() {
+        let spring_mass_system = SpringMassSystem::<f64>::new(1.0);
+        let context = spring_mass_system.borrow().create_default_context();
+        spring_mass_system
+            .borrow()
+            .set_position_and_velocity(&mut context.borrow_mut(), 1.0, 0.0);
+
+        let mut integrator =
+            ImplicitEulerIntegrator::new(&spring_mass_system, 0.1, Some(context.clone()));
+        integrator.initialize();
+        integrator.integrate_with_single_fixed_step(0.1);
+
+        // x1 = (I - h A)^-1 x0 for the linear dynamics xdot = A x.
+        let state = context.borrow().continuous_state_vector().copy_to_vector();
+        let expected = na::DVector::<f64>::from_vec(vec![1.0 / 1.01, -0.1 / 1.01]);
+        assert!((state - expected).norm() < 1e-9);
+        assert_eq!(integrator.num_jacobian_evaluations(), 1);
+        assert!(integrator.num_newton_raphson_iterations() >= 1);
+    }
+
+    #[test]
+    fn test_stiff_system_with_large_steps() {
+        let stiffness = 1e6;
+        let spring_mass_system = SpringMassSystem::<f64>::new(stiffness);
+        let context = spring_mass_system.borrow().create_default_context();
+        spring_mass_system
+            .borrow()
+            .set_position_and_velocity(&mut context.borrow_mut(), 1.0, 0.0);
+
+        let mut integrator =
+            ImplicitEulerIntegrator::new(&spring_mass_system, 0.1, Some(context.clone()));
+        integrator.initialize();
+        while integrator.integrate_no_further_than(1.0) < 1.0 {}
+
+        // Implicit Euler dissipates energy instead of blowing up.
+        let state = context.borrow().continuous_state_vector().copy_to_vector();
+        let energy = 0.5 * (stiffness * state[0] * state[0] + state[1] * state[1]);
+        assert!(energy < 0.5 * stiffness);
+    }
+
+    #[test]
+    fn test_step_halving_on_newton_failure() {
+        let cubic_decay_system = CubicDecaySystem::<f64>::new();
+        let context = cubic_decay_system.borrow().create_default_context();
+        context
+            .borrow_mut()
+            .continuous_state_vector_mut()
+            .set_from_vector(&na::DVector::<f64>::from_vec(vec![10.0]));
+
+        let mut integrator =
+            ImplicitEulerIntegrator::new(&cubic_decay_system, 1.0, Some(context.clone()));
+        integrator.initialize();
+        integrator.integrate_with_single_fixed_step(1.0);
+
+        // The full step fails to converge, so it is taken as smaller substeps instead.
+        assert!(
+            integrator
+                .statistics()
+                .num_step_shrinkages_from_substep_failures
+                > 0
+        );
+        assert_eq!(*context.borrow().time(), 1.0);
+        let x = context.borrow().continuous_state_vector()[0];
+        assert!(x > 0.0 && x < 10.0);
+    }
+}
@@ -0,0 +1,287 @@
+extern crate nalgebra as na;
+
+use num_traits::Float;
+
+use crate::common::atlas_scalar::AtlasScalar;
+use crate::systems::analysis::integrator_base::IntegratorBase;
+use crate::systems::framework::continuous_state::ContinuousState;
+use crate::systems::framework::system::System;
+
+// Shared machinery of implicit Runge-Kutta integrators. The stage derivatives K_i are found by
+// driving the system's implicit residual f(t0 + c_i h, x0 + h sum_j(a_ij K_j), K_i) to zero with
+// a Newton solve on a finite-difference Jacobian.
+pub trait ImplicitIntegrator<T, S>: IntegratorBase<T, S>
+where
+    T: AtlasScalar + Float,
+    S: System<T> + ?Sized,
+{
+    fn max_newton_iterations(&self) -> usize {
+        10
+    }
+
+    fn newton_convergence_tolerance(&self) -> T {
+        T::epsilon().sqrt()
+    }
+
+    fn num_newton_raphson_iterations(&self) -> usize {
+        self.statistics().num_newton_raphson_iterations
+    }
+
+    fn num_jacobian_evaluations(&self) -> usize {
+        self.statistics().num_jacobian_evaluations
+    }
+
+    // The most times a step whose Newton iteration fails is split in half before giving up.
+    fn max_step_halvings(&self) -> usize {
+        10
+    }
+
+    // Takes a single implicit step of size h, returning false with the time and state unchanged
+    // if the Newton iteration fails.
+    fn do_implicit_step(&mut self, h: T) -> bool;
+
+    // Takes a step of size h, retrying a failed step as two halves, each of which forms a fresh
+    // Jacobian at its own start. Returns false with the time and state unchanged once a step
+    // still fails after halvings_left halvings.
+    fn do_step_with_halving(&mut self, h: T, halvings_left: usize) -> bool {
+        if self.do_implicit_step(h) {
+            return true;
+        }
+        if halvings_left == 0 {
+            return false;
+        }
+        self.statistics_mut()
+            .num_step_shrinkages_from_substep_failures += 1;
+
+        let t0 = self.time();
+        let x0 = self.continuous_state();
+        let half_step = h / T::from(2.0).unwrap();
+        if self.do_step_with_halving(half_step, halvings_left - 1)
+            && self.do_step_with_halving(half_step, halvings_left - 1)
+        {
+            return true;
+        }
+        self.set_time_and_continuous_state(t0, &x0);
+        false
+    }
+
+    // Evaluates the system's implicit residual at (t, x) for the proposed derivatives xdot.
+    fn eval_implicit_residual(
+        &mut self,
+        t: T,
+        x: &na::DVector<T>,
+        xdot: &na::DVector<T>,
+    ) -> na::DVector<T> {
+        self.set_time_and_continuous_state(t, x);
+        self.statistics_mut().num_derivative_evaluations += 1;
+
+        let context = self.context_link();
+        let system = self.system().borrow();
+        let context = context.borrow();
+        let mut proposed_derivatives = system.allocate_time_derivatives();
+        proposed_derivatives.set_from_vector(xdot);
+        let mut residual =
+            na::DVector::<T>::zeros(system.implicit_time_derivatives_residual_size());
+        system.calc_implicit_time_derivatives_residual(
+            &context,
+            &proposed_derivatives,
+            &mut residual,
+        );
+        residual
+    }
+
+    // Stacks the residuals of every stage for the stacked stage derivatives.
+    fn eval_stage_residuals(
+        &mut self,
+        t0: T,
+        x0: &na::DVector<T>,
+        h: T,
+        c: &[T],
+        a: &[Vec<T>],
+        stage_derivatives: &na::DVector<T>,
+    ) -> na::DVector<T> {
+        let num_states = x0.len();
+        let num_stages = c.len();
+        let mut residuals = na::DVector::<T>::zeros(num_states * num_stages);
+        for i in 0..num_stages {
+            let mut x = x0.clone();
+            for (j, a_ij) in a[i].iter().enumerate() {
+                x.axpy(
+                    h * *a_ij,
+                    &stage_derivatives.rows(j * num_states, num_states),
+                    T::one(),
+                );
+            }
+            let xdot = stage_derivatives
+                .rows(i * num_states, num_states)
+                .into_owned();
+            let residual = self.eval_implicit_residual(t0 + c[i] * h, &x, &xdot);
+            residuals
+                .rows_mut(i * num_states, num_states)
+                .copy_from(&residual);
+        }
+        residuals
+    }
+
+    // Solves for the stacked stage derivatives of the Butcher tableau (c, a), returning None if
+    // the Newton iteration fails to converge. The context is left in an unspecified state.
+    fn solve_stage_derivatives(
+        &mut self,
+        t0: T,
+        x0: &na::DVector<T>,
+        h: T,
+        c: &[T],
+        a: &[Vec<T>],
+    ) -> Option<na::DVector<T>> {
+        let num_states = x0.len();
+        if num_states == 0 {
+            return Some(na::DVector::<T>::zeros(0));
+        }
+        assert_eq!(
+            self.system()
+                .borrow()
+                .implicit_time_derivatives_residual_size(),
+            num_states,
+            "ImplicitIntegrator: the implicit residual must have one element per continuous state"
+        );
+
+        // Start every stage from the explicit derivatives at the beginning of the step.
+        let xdot0 = self.eval_time_derivatives();
+        let mut stage_derivatives =
+            na::DVector::<T>::from_fn(num_states * c.len(), |i, _| xdot0[i % num_states]);
+
+        // The Jacobian is only formed once per step, i.e. a simplified Newton iteration.
+        let jacobian = self.calc_jacobian(t0, x0, h, c, a, &stage_derivatives);
+        let tolerance = self.newton_convergence_tolerance();
+        for _ in 0..self.max_newton_iterations() {
+            self.statistics_mut().num_newton_raphson_iterations += 1;
+            let residuals = self.eval_stage_residuals(t0, x0, h, c, a, &stage_derivatives);
+            let delta = solve_linear_system(jacobian.clone(), -residuals)?;
+            stage_derivatives += &delta;
+
+            let scale = T::one() + max_abs(&stage_derivatives);
+            if max_abs(&delta) <= tolerance * scale {
+                return Some(stage_derivatives);
+            }
+            if delta.iter().any(|value| !value.is_finite()) {
+                return None;
+            }
+        }
+        None
+    }
+
+    // Forward-difference Jacobian of the stacked stage residuals.
+    fn calc_jacobian(
+        &mut self,
+        t0: T,
+        x0: &na::DVector<T>,
+        h: T,
+        c: &[T],
+        a: &[Vec<T>],
+        stage_derivatives: &na::DVector<T>,
+    ) -> na::DMatrix<T> {
+        self.statistics_mut().num_jacobian_evaluations += 1;
+
+        let size = stage_derivatives.len();
+        let residuals = self.eval_stage_residuals(t0, x0, h, c, a, stage_derivatives);
+        let mut jacobian = na::DMatrix::<T>::zeros(size, size);
+        for j in 0..size {
+            let mut perturbed = stage_derivatives.clone();
+            let delta = T::epsilon().sqrt() * T::one().max(perturbed[j].abs());
+            perturbed[j] += delta;
+            let perturbed_residuals = self.eval_stage_residuals(t0, x0, h, c, a, &perturbed);
+            jacobian.set_column(j, &((perturbed_residuals - &residuals) / delta));
+        }
+        jacobian
+    }
+}
+
+fn max_abs<T: AtlasScalar + Float>(vector: &na::DVector<T>) -> T {
+    vector
+        .iter()
+        .fold(T::zero(), |max, value| max.max(value.abs()))
+}
+
+// Solves a x = b by Gaussian elimination with partial pivoting, returning None if a is singular.
+pub fn solve_linear_system<T>(
+    mut a: na::DMatrix<T>,
+    mut b: na::DVector<T>,
+) -> Option<na::DVector<T>>
+where
+    T: AtlasScalar + Float,
+{
+    let n = b.len();
+    assert_eq!(a.nrows(), n);
+    assert_eq!(a.ncols(), n);
+
+    for k in 0..n {
+        // Checked before the pivot search, whose comparisons are undefined for NaN.
+        if (k..n).any(|i| !a[(i, k)].is_finite()) {
+            return None;
+        }
+        let pivot = (k..n)
+            .max_by(|&i, &j| a[(i, k)].abs().partial_cmp(&a[(j, k)].abs()).unwrap())
+            .unwrap();
+        if a[(pivot, k)] == T::zero() {
+            return None;
+        }
+        a.swap_rows(k, pivot);
+        b.swap_rows(k, pivot);
+
+        for i in (k + 1)..n {
+            let factor = a[(i, k)] / a[(k, k)];
+            for j in k..n {
+                let value = a[(k, j)];
+                a[(i, j)] -= factor * value;
+            }
+            let value = b[k];
+            b[i] -= factor * value;
+        }
+    }
+
+    for k in (0..n).rev() {
+        let mut sum = b[k];
+        for j in (k + 1)..n {
+            sum -= a[(k, j)] * b[j];
+        }
+        b[k] = sum / a[(k, k)];
+    }
+    Some(b)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate nalgebra as na;
+
+    use super::*;
+
+    #[test]
+    fn test_solve_linear_system() {
+        let a = na::DMatrix::<f64>::from_row_slice(
+            3,
+            3,
+            &[0.0, 2.0, 1.0, 1.0, 1.0, 0.0, 3.0, 0.0, 1.0],
+        );
+        let x = na::DVector::<f64>::from_vec(vec![1.0, -2.0, 3.0]);
+        let b = &a * &x;
+
+        let solution = solve_linear_system(a, b).unwrap();
+        assert!((solution - x).norm() < 1e-12);
+    }
+
+    #[test]
+    fn test_solve_singular_linear_system() {
+        let a = na::DMatrix::<f64>::from_row_slice(2, 2, &[1.0, 2.0, 2.0, 4.0]);
+        let b = na::DVector::<f64>::from_vec(vec![1.0, 1.0]);
+
+        assert!(solve_linear_system(a, b).is_none());
+    }
+
+    #[test]
+    fn test_solve_non_finite_linear_system() {
+        let a = na::DMatrix::<f64>::from_row_slice(2, 2, &[1.0, 0.0, f64::NAN, 1.0]);
+        let b = na::DVector::<f64>::from_vec(vec![1.0, 1.0]);
+
+        assert!(solve_linear_system(a, b).is_none());
+    }
+}
@@ -0,0 +1,560 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+extern crate nalgebra as na;
+
+use num_traits::Float;
+
+use crate::common::atlas_scalar::AtlasScalar;
+use crate::systems::framework::context::Context;
+use crate::systems::framework::continuous_state::ContinuousState;
+use crate::systems::framework::system::System;
+
+#[derive(Clone, Debug, Default)]
+pub struct IntegratorStatistics<T: AtlasScalar> {
+    pub num_steps_taken: usize,
+    pub num_derivative_evaluations: usize,
+    pub actual_initial_step_size_taken: Option<T>,
+    pub smallest_step_size_taken: Option<T>,
+    pub largest_step_size_taken: Option<T>,
+    pub num_step_shrinkages_from_error_control: usize,
+    pub num_step_shrinkages_from_substep_failures: usize,
+    pub num_newton_raphson_iterations: usize,
+    pub num_jacobian_evaluations: usize,
+}
+
+// Accuracy settings and step-size state of integrators that support error control.
+#[derive(Clone, Debug)]
+pub struct ErrorControl<T: AtlasScalar> {
+    pub absolute_tolerance: T,
+    pub relative_tolerance: T,
+    pub minimum_step_size: T,
+    pub throw_on_minimum_step_size_violation: bool,
+    pub fixed_step_mode: bool,
+    pub initial_step_size_target: Option<T>,
+    // Per-state weights for the q, v and z partitions of the error estimate; None means all ones.
+    pub generalized_position_weights: Option<na::DVector<T>>,
+    pub generalized_velocity_weights: Option<na::DVector<T>>,
+    pub misc_state_weights: Option<na::DVector<T>>,
+    pub ideal_next_step_size: Option<T>,
+}
+
+impl<T: AtlasScalar + Float> Default for ErrorControl<T> {
+    fn default() -> Self {
+        ErrorControl {
+            absolute_tolerance: T::from(1e-6).unwrap(),
+            relative_tolerance: T::from(1e-3).unwrap(),
+            minimum_step_size: T::zero(),
+            throw_on_minimum_step_size_violation: true,
+            fixed_step_mode: false,
+            initial_step_size_target: None,
+            generalized_position_weights: None,
+            generalized_velocity_weights: None,
+            misc_state_weights: None,
+            ideal_next_step_size: None,
+        }
+    }
+}
+
+pub trait IntegratorBase<T, S>
+where
+    T: AtlasScalar + Float,
+    S: System<T> + ?Sized,
+{
+    // Getters and setters without default implementations
+    fn system(&self) -> &Rc<RefCell<S>>;
+    fn context(&self) -> &Option<Rc<RefCell<S::CN>>>;
+    fn context_mut(&mut self) -> &mut Option<Rc<RefCell<S::CN>>>;
+    fn maximum_step_size(&self) -> T;
+    fn set_maximum_step_size(&mut self, max_step_size: T);
+    fn statistics(&self) -> &IntegratorStatistics<T>;
+    fn statistics_mut(&mut self) -> &mut IntegratorStatistics<T>;
+    fn is_initialized(&self) -> bool;
+    fn set_is_initialized(&mut self, is_initialized: bool);
+
+    // Advances the context by exactly h, returning false if the step could not be taken.
+    fn do_step(&mut self, h: T) -> bool;
+
+    fn supports_error_estimation(&self) -> bool {
+        false
+    }
+
+    fn error_estimate_order(&self) -> usize {
+        0
+    }
+
+    // Integrators supporting error estimation override these to expose their accuracy settings
+    // and the error estimate of the last call to do_step.
+    fn error_control(&self) -> Option<&ErrorControl<T>> {
+        None
+    }
+
+    fn error_control_mut(&mut self) -> Option<&mut ErrorControl<T>> {
+        None
+    }
+
+    fn error_estimate(&self) -> Option<&na::DVector<T>> {
+        None
+    }
+
+    fn is_error_controlled(&self) -> bool {
+        self.supports_error_estimation()
+            && self
+                .error_control()
+                .is_some_and(|error_control| !error_control.fixed_step_mode)
+    }
+
+    fn expect_error_control_mut(&mut self) -> &mut ErrorControl<T> {
+        self.error_control_mut()
+            .expect("IntegratorBase: this integrator does not support error control")
+    }
+
+    fn set_absolute_tolerance(&mut self, absolute_tolerance: T) {
+        assert!(absolute_tolerance >= T::zero());
+        self.expect_error_control_mut().absolute_tolerance = absolute_tolerance;
+    }
+
+    fn set_relative_tolerance(&mut self, relative_tolerance: T) {
+        assert!(relative_tolerance >= T::zero());
+        self.expect_error_control_mut().relative_tolerance = relative_tolerance;
+    }
+
+    fn set_minimum_step_size(&mut self, minimum_step_size: T) {
+        assert!(minimum_step_size >= T::zero());
+        self.expect_error_control_mut().minimum_step_size = minimum_step_size;
+    }
+
+    fn set_throw_on_minimum_step_size_violation(&mut self, throws: bool) {
+        self.expect_error_control_mut()
+            .throw_on_minimum_step_size_violation = throws;
+    }
+
+    fn set_fixed_step_mode(&mut self, fixed_step_mode: bool) {
+        self.expect_error_control_mut().fixed_step_mode = fixed_step_mode;
+    }
+
+    fn request_initial_step_size_target(&mut self, step_size: T) {
+        assert!(step_size > T::zero());
+        self.expect_error_control_mut().initial_step_size_target = Some(step_size);
+    }
+
+    fn set_state_weights(
+        &mut self,
+        generalized_position_weights: na::DVector<T>,
+        generalized_velocity_weights: na::DVector<T>,
+        misc_state_weights: na::DVector<T>,
+    ) {
+        let error_control = self.expect_error_control_mut();
+        error_control.generalized_position_weights = Some(generalized_position_weights);
+        error_control.generalized_velocity_weights = Some(generalized_velocity_weights);
+        error_control.misc_state_weights = Some(misc_state_weights);
+    }
+
+    fn reset_context(&mut self, context: Rc<RefCell<S::CN>>) {
+        *self.context_mut() = Some(context);
+        self.set_is_initialized(false);
+    }
+
+    fn initialize(&mut self) {
+        assert!(
+            self.context().is_some(),
+            "IntegratorBase::initialize: no context has been set"
+        );
+        assert!(self.maximum_step_size() > T::zero());
+
+        if self.error_control().is_some() {
+            let (num_q, num_v, num_z) = self.continuous_state_sizes();
+            let maximum_step_size = self.maximum_step_size();
+            let error_control = self.expect_error_control_mut();
+            assert!(
+                error_control.minimum_step_size <= maximum_step_size,
+                "IntegratorBase::initialize: minimum step size exceeds the maximum step size"
+            );
+            for (weights, size) in [
+                (&error_control.generalized_position_weights, num_q),
+                (&error_control.generalized_velocity_weights, num_v),
+                (&error_control.misc_state_weights, num_z),
+            ] {
+                if let Some(weights) = weights {
+                    assert_eq!(
+                        weights.len(),
+                        size,
+                        "IntegratorBase::initialize: state weights do not match the continuous state"
+                    );
+                }
+            }
+            error_control.ideal_next_step_size = None;
+        }
+
+        self.reset_statistics();
+        self.set_is_initialized(true);
+    }
+
+    fn reset_statistics(&mut self) {
+        *self.statistics_mut() = IntegratorStatistics::default();
+    }
+
+    // Steps toward boundary_time by at most the maximum step size and returns the new time.
+    fn integrate_no_further_than(&mut self, boundary_time: T) -> T {
+        let time = self.time();
+        assert!(boundary_time >= time);
+
+        let max_step_size = self.maximum_step_size();
+        if self.is_error_controlled() {
+            assert!(
+                self.is_initialized(),
+                "IntegratorBase::integrate_no_further_than: integrator has not been initialized"
+            );
+            let max_dt = max_step_size.min(boundary_time - time);
+            let h = self.step_once_error_controlled_at_most(max_dt);
+            if h == boundary_time - time {
+                self.set_time(boundary_time);
+            }
+        } else if boundary_time - time <= max_step_size {
+            self.integrate_with_single_fixed_step(boundary_time - time);
+            // Land exactly on the boundary to avoid accumulating round-off.
+            self.set_time(boundary_time);
+        } else {
+            self.integrate_with_single_fixed_step(max_step_size);
+        }
+
+        self.time()
+    }
+
+    fn integrate_with_single_fixed_step(&mut self, h: T) {
+        assert!(
+            self.is_initialized(),
+            "IntegratorBase::integrate_with_single_fixed_step: integrator has not been initialized"
+        );
+        assert!(h >= T::zero());
+
+        if !self.do_step(h) {
+            panic!(
+                "IntegratorBase::integrate_with_single_fixed_step: failed to take a step of size {:?}",
+                h
+            );
+        }
+        self.update_step_statistics(h);
+    }
+
+    // Takes a single step of at most max_dt, shrinking it until the error estimate satisfies the
+    // accuracy settings, and returns the size of the step taken.
+    fn step_once_error_controlled_at_most(&mut self, max_dt: T) -> T {
+        let t0 = self.time();
+        let x0 = self.continuous_state();
+
+        let error_control = self.error_control().unwrap();
+        let ideal_step_size = error_control
+            .ideal_next_step_size
+            .or(error_control.initial_step_size_target)
+            .unwrap_or(self.maximum_step_size());
+        // Never go below a step that can still advance time at the current precision.
+        let working_minimum_step_size = error_control
+            .minimum_step_size
+            .max(T::epsilon() * T::from(100.0).unwrap() * T::one().max(t0.abs()));
+        let throws = error_control.throw_on_minimum_step_size_violation;
+
+        let mut h = ideal_step_size.min(max_dt);
+        let mut at_minimum_step_size = false;
+        loop {
+            if self.do_step(h) {
+                let error_norm = self.calc_error_norm(&x0);
+                let (accepted, next_step_size) = self.calc_adjusted_step_size(error_norm, h);
+                if accepted || at_minimum_step_size {
+                    // A step truncated by max_dt says nothing about the step the dynamics allow.
+                    let ideal_next_step_size = if h < ideal_step_size {
+                        next_step_size.min(ideal_step_size)
+                    } else {
+                        next_step_size
+                    };
+                    self.expect_error_control_mut().ideal_next_step_size =
+                        Some(ideal_next_step_size.min(self.maximum_step_size()));
+                    self.update_step_statistics(h);
+                    return h;
+                }
+                self.statistics_mut().num_step_shrinkages_from_error_control += 1;
+                h = next_step_size;
+            } else {
+                // Unlike a large error, a failed step cannot be accepted at the minimum step size.
+                if at_minimum_step_size {
+                    panic!(
+                        "IntegratorBase: failed to take a step of the minimum step size {:?} at time {:?}",
+                        h, t0
+                    );
+                }
+                self.statistics_mut()
+                    .num_step_shrinkages_from_substep_failures += 1;
+                h /= T::from(2.0).unwrap();
+            }
+            self.set_time_and_continuous_state(t0, &x0);
+
+            if h < working_minimum_step_size {
+                if throws {
+                    panic!(
+                        "IntegratorBase: error control wants a step of size {:?} at time {:?}, smaller than the minimum step size {:?}",
+                        h, t0, working_minimum_step_size
+                    );
+                }
+                h = working_minimum_step_size.min(max_dt);
+                at_minimum_step_size = true;
+            }
+        }
+    }
+
+    // Weighted infinity norm of the error estimate, scaled so that 1 is the accuracy boundary.
+    fn calc_error_norm(&self, x0: &na::DVector<T>) -> T {
+        let error_control = self.error_control().unwrap();
+        let error_estimate = self
+            .error_estimate()
+            .expect("IntegratorBase::calc_error_norm: no error estimate is available");
+        let x1 = self.continuous_state();
+        let (num_q, num_v, _) = self.continuous_state_sizes();
+
+        let weight = |weights: &Option<na::DVector<T>>, index: usize| {
+            weights.as_ref().map_or(T::one(), |weights| weights[index])
+        };
+
+        let mut error_norm = T::zero();
+        for (i, error) in error_estimate.iter().enumerate() {
+            let state_weight = if i < num_q {
+                weight(&error_control.generalized_position_weights, i)
+            } else if i < num_q + num_v {
+                weight(&error_control.generalized_velocity_weights, i - num_q)
+            } else {
+                weight(&error_control.misc_state_weights, i - num_q - num_v)
+            };
+            let scale = error_control.absolute_tolerance
+                + error_control.relative_tolerance * x0[i].abs().max(x1[i].abs());
+            let scaled_error = state_weight * error.abs() / scale;
+            if scaled_error.is_nan() {
+                return T::infinity();
+            }
+            error_norm = error_norm.max(scaled_error);
+        }
+        error_norm
+    }
+
+    // Returns whether a step of size h with the given error norm is accepted, and the step size
+    // to try next.
+    fn calc_adjusted_step_size(&self, error_norm: T, h: T) -> (bool, T) {
+        let safety = T::from(0.9).unwrap();
+        let min_shrink = T::from(0.1).unwrap();
+        let max_grow = T::from(5.0).unwrap();
+
+        if error_norm == T::zero() {
+            return (true, h * max_grow);
+        }
+
+        let order = T::from(self.error_estimate_order()).unwrap();
+        let factor = (safety * (T::one() / error_norm).powf(T::one() / order))
+            .max(min_shrink)
+            .min(max_grow);
+        if error_norm <= T::one() {
+            (true, h * factor)
+        } else {
+            // Guarantee progress toward an acceptable step.
+            (false, h * factor.min(safety))
+        }
+    }
+
+    fn update_step_statistics(&mut self, h: T) {
+        let statistics = self.statistics_mut();
+        statistics.num_steps_taken += 1;
+        if statistics.actual_initial_step_size_taken.is_none() {
+            statistics.actual_initial_step_size_taken = Some(h);
+        }
+        if statistics
+            .smallest_step_size_taken
+            .is_none_or(|min| h < min)
+        {
+            statistics.smallest_step_size_taken = Some(h);
+        }
+        if statistics.largest_step_size_taken.is_none_or(|max| h > max) {
+            statistics.largest_step_size_taken = Some(h);
+        }
+    }
+
+    fn num_steps_taken(&self) -> usize {
+        self.statistics().num_steps_taken
+    }
+
+    fn num_derivative_evaluations(&self) -> usize {
+        self.statistics().num_derivative_evaluations
+    }
+
+    fn num_step_shrinkages_from_error_control(&self) -> usize {
+        self.statistics().num_step_shrinkages_from_error_control
+    }
+
+    fn num_step_shrinkages_from_substep_failures(&self) -> usize {
+        self.statistics().num_step_shrinkages_from_substep_failures
+    }
+
+    fn smallest_step_size_taken(&self) -> Option<T> {
+        self.statistics().smallest_step_size_taken
+    }
+
+    // Context helpers
+    fn context_link(&self) -> Rc<RefCell<S::CN>> {
+        self.context()
+            .clone()
+            .expect("IntegratorBase: no context has been set")
+    }
+
+    fn num_continuous_states(&self) -> usize {
+        self.system().borrow().num_continuous_states()
+    }
+
+    fn time(&self) -> T {
+        *self.context_link().borrow().time()
+    }
+
+    fn set_time(&mut self, time: T) {
+        self.context_link().borrow_mut().set_time(time);
+    }
+
+    fn continuous_state(&self) -> na::DVector<T> {
+        if self.num_continuous_states() == 0 {
+            return na::DVector::<T>::zeros(0);
+        }
+
+        self.context_link()
+            .borrow()
+            .continuous_state_vector()
+            .copy_to_vector()
+    }
+
+    fn set_continuous_state(&mut self, state: &na::DVector<T>) {
+        if self.num_continuous_states() == 0 {
+            return;
+        }
+
+        self.context_link()
+            .borrow_mut()
+            .continuous_state_vector_mut()
+            .set_from_vector(state);
+    }
+
+    fn set_time_and_continuous_state(&mut self, time: T, state: &na::DVector<T>) {
+        self.set_continuous_state(state);
+        self.set_time(time);
+    }
+
+    // Evaluates the time derivatives of the continuous state stored in the context.
+    fn eval_time_derivatives(&mut self) -> na::DVector<T> {
+        if self.num_continuous_states() == 0 {
+            return na::DVector::<T>::zeros(0);
+        }
+
+        self.statistics_mut().num_derivative_evaluations += 1;
+        let context = self.context_link();
+        let system = self.system().borrow();
+        let context = context.borrow();
+        system
+            .eval_time_derivatives(&context)
+            .vector()
+            .copy_to_vector()
+    }
+
+    // Returns (num_q, num_v, num_z) of the continuous state in the context.
+    fn continuous_state_sizes(&self) -> (usize, usize, usize) {
+        if self.num_continuous_states() == 0 {
+            return (0, 0, 0);
+        }
+
+        let context = self.context_link();
+        let context = context.borrow();
+        let continuous_state = context.continuous_state();
+        (
+            continuous_state.num_q(),
+            continuous_state.num_v(),
+            continuous_state.num_z(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::systems::analysis::test_utilities::spring_mass_system::SpringMassSystem;
+    use crate::systems::framework::leaf_context::LeafContext;
+
+    // An error-controlled integrator whose steps always fail.
+    struct FailingIntegrator {
+        system: Rc<RefCell<SpringMassSystem<f64>>>,
+        context: Option<Rc<RefCell<LeafContext<f64>>>>,
+        statistics: IntegratorStatistics<f64>,
+        is_initialized: bool,
+        error_control: ErrorControl<f64>,
+    }
+
+    impl IntegratorBase<f64, SpringMassSystem<f64>> for FailingIntegrator {
+        fn system(&self) -> &Rc<RefCell<SpringMassSystem<f64>>> {
+            &self.system
+        }
+
+        fn context(&self) -> &Option<Rc<RefCell<LeafContext<f64>>>> {
+            &self.context
+        }
+
+        fn context_mut(&mut self) -> &mut Option<Rc<RefCell<LeafContext<f64>>>> {
+            &mut self.context
+        }
+
+        fn maximum_step_size(&self) -> f64 {
+            1.0
+        }
+
+        fn set_maximum_step_size(&mut self, _max_step_size: f64) {}
+
+        fn statistics(&self) -> &IntegratorStatistics<f64> {
+            &self.statistics
+        }
+
+        fn statistics_mut(&mut self) -> &mut IntegratorStatistics<f64> {
+            &mut self.statistics
+        }
+
+        fn is_initialized(&self) -> bool {
+            self.is_initialized
+        }
+
+        fn set_is_initialized(&mut self, is_initialized: bool) {
+            self.is_initialized = is_initialized;
+        }
+
+        fn do_step(&mut self, _h: f64) -> bool {
+            false
+        }
+
+        fn supports_error_estimation(&self) -> bool {
+            true
+        }
+
+        fn error_control(&self) -> Option<&ErrorControl<f64>> {
+            Some(&self.error_control)
+        }
+
+        fn error_control_mut(&mut self) -> Option<&mut ErrorControl<f64>> {
+            Some(&mut self.error_control)
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "failed to take a step of the minimum step size")]
+    fn test_failed_step_at_minimum_step_size() {
+        let spring_mass_system = SpringMassSystem::<f64>::new(1.0);
+        let context = spring_mass_system.borrow().create_default_context();
+        let mut integrator = FailingIntegrator {
+            system: spring_mass_system,
+            context: Some(context),
+            statistics: IntegratorStatistics::default(),
+            is_initialized: false,
+            error_control: ErrorControl::default(),
+        };
+        integrator.set_minimum_step_size(0.1);
+        integrator.set_throw_on_minimum_step_size_violation(false);
+        integrator.initialize();
+        integrator.integrate_no_further_than(1.0);
+    }
+}
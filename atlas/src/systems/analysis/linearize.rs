@@ -0,0 +1,216 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+extern crate nalgebra as na;
+
+use num_traits::Float;
+
+use crate::common::atlas_scalar::AtlasScalar;
+use crate::systems::framework::basic_vector::BasicVector;
+use crate::systems::framework::context::Context;
+use crate::systems::framework::continuous_state::ContinuousState;
+use crate::systems::framework::framework_common::{InputPortIndex, OutputPortIndex};
+use crate::systems::framework::system::System;
+use crate::systems::primitives::affine_system::AffineSystem;
+
+// The first-order Taylor approximation of system around the operating point given by context: the
+// AffineSystem xdot = A x + B u + f0, y = C x + D u + y0, which agrees with system to first order
+// near the continuous state x0 of context and the value u0 of the input port. The partial
+// derivatives are central differences of the time derivatives and of the output port, with the
+// input fixed to u0 while the state is perturbed and to u0 plus a perturbation while the input is.
+// Without an input or output port, the matrices involving u or y are empty.
+//
+// Only continuous-time systems are supported: panics if context has discrete state. context is
+// perturbed in place, and restored to the operating point afterwards, with the input port left
+// fixed to u0.
+pub fn linearize<T, S>(
+    system: &S,
+    context: &mut S::CN,
+    input_port_index: Option<&InputPortIndex>,
+    output_port_index: Option<&OutputPortIndex>,
+) -> Rc<RefCell<AffineSystem<T>>>
+where
+    T: AtlasScalar + Float,
+    S: System<T>,
+{
+    assert!(
+        context.num_discrete_state_groups() == 0,
+        "linearize only supports continuous-time systems, without discrete state"
+    );
+    let x0 = context.continuous_state_vector().copy_to_vector();
+    let u0 = match input_port_index {
+        Some(index) => system
+            .input_port(index)
+            .eval::<<S::CN as Context<T>>::S, BasicVector<T>>(context)
+            .value()
+            .clone(),
+        None => na::DVector::zeros(0),
+    };
+
+    // The time derivatives and the output at the state x and the input u.
+    let evaluate = |context: &mut S::CN, x: &na::DVector<T>, u: &na::DVector<T>| {
+        context.continuous_state_vector_mut().set_from_vector(x);
+        if let Some(index) = input_port_index {
+            system
+                .input_port(index)
+                .fix_value(&mut *context, BasicVector::new(u.clone()));
+        }
+        let xdot = system
+            .eval_time_derivatives(context)
+            .vector()
+            .copy_to_vector();
+        let y = match output_port_index {
+            Some(index) => system
+                .output_port(index)
+                .eval_abstract(context)
+                .get_value::<BasicVector<T>>()
+                .value()
+                .clone(),
+            None => na::DVector::zeros(0),
+        };
+        (xdot, y)
+    };
+
+    let (xdot0, y0) = evaluate(context, &x0, &u0);
+    let (num_states, num_inputs, num_outputs) = (x0.len(), u0.len(), y0.len());
+    let mut a = na::DMatrix::zeros(num_states, num_states);
+    let mut b = na::DMatrix::zeros(num_states, num_inputs);
+    let mut c = na::DMatrix::zeros(num_outputs, num_states);
+    let mut d = na::DMatrix::zeros(num_outputs, num_inputs);
+
+    // Each column of the Jacobians perturbs one element of (x, u).
+    let two = T::one() + T::one();
+    for j in 0..num_states + num_inputs {
+        let (mut x_plus, mut u_plus) = (x0.clone(), u0.clone());
+        let (mut x_minus, mut u_minus) = (x0.clone(), u0.clone());
+        let (value, value_plus, value_minus) = if j < num_states {
+            (x0[j], &mut x_plus[j], &mut x_minus[j])
+        } else {
+            let k = j - num_states;
+            (u0[k], &mut u_plus[k], &mut u_minus[k])
+        };
+        let h = T::epsilon().cbrt() * T::one().max(value.abs());
+        *value_plus = value + h;
+        *value_minus = value - h;
+
+        let (xdot_plus, y_plus) = evaluate(context, &x_plus, &u_plus);
+        let (xdot_minus, y_minus) = evaluate(context, &x_minus, &u_minus);
+        let dxdot = (xdot_plus - xdot_minus) / (two * h);
+        let dy = (y_plus - y_minus) / (two * h);
+        if j < num_states {
+            a.set_column(j, &dxdot);
+            c.set_column(j, &dy);
+        } else {
+            b.set_column(j - num_states, &dxdot);
+            d.set_column(j - num_states, &dy);
+        }
+    }
+    evaluate(context, &x0, &u0);
+
+    let f0 = xdot0 - &a * &x0 - &b * &u0;
+    let y0 = y0 - &c * &x0 - &d * &u0;
+
+    AffineSystem::new(a, b, f0, c, d, y0, 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::systems::analysis::test_utilities::discrete_counter::DiscreteCounter;
+    use crate::systems::analysis::test_utilities::spring_mass_system::SpringMassSystem;
+    use crate::systems::controllers::pid_controller::PIDController;
+
+    fn assert_near(actual: &na::DMatrix<f64>, expected: &na::DMatrix<f64>) {
+        assert_eq!(actual.shape(), expected.shape());
+        assert!(
+            (actual - expected).abs().max() < 1e-8,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_spring_mass_system() {
+        let spring_mass_system = SpringMassSystem::<f64>::new(4.0);
+        let context = spring_mass_system.borrow().create_default_context();
+        spring_mass_system
+            .borrow()
+            .set_position_and_velocity(&mut context.borrow_mut(), 0.5, -1.0);
+
+        let linearized = linearize(
+            &*spring_mass_system.borrow(),
+            &mut context.borrow_mut(),
+            None,
+            None,
+        );
+        let linearized = linearized.borrow();
+        assert_near(
+            linearized.a(),
+            &na::DMatrix::from_row_slice(2, 2, &[0.0, 1.0, -4.0, 0.0]),
+        );
+        assert_eq!(linearized.b().shape(), (2, 0));
+        assert_eq!(linearized.c().shape(), (0, 2));
+        assert!(linearized.f0().abs().max() < 1e-8);
+
+        // The context is back at the operating point.
+        assert_eq!(
+            context.borrow().continuous_state_vector().copy_to_vector(),
+            na::DVector::from_vec(vec![0.5, -1.0])
+        );
+    }
+
+    #[test]
+    fn test_pid_controller() {
+        let pid_controller = PIDController::<f64>::new(
+            na::DVector::from_vec(vec![2.0]),
+            na::DVector::from_vec(vec![0.5]),
+            na::DVector::from_vec(vec![1.0]),
+        );
+        let pid_controller = pid_controller.borrow();
+        let context = pid_controller.create_default_context();
+        pid_controller.input_port_estimated_state().fix_value(
+            context.borrow_mut(),
+            BasicVector::<f64>::from_vec(vec![1.0, 0.0]),
+        );
+        pid_controller.input_port_desired_state().fix_value(
+            context.borrow_mut(),
+            BasicVector::<f64>::from_vec(vec![3.0, 2.0]),
+        );
+
+        let linearized = linearize(
+            &*pid_controller,
+            &mut context.borrow_mut(),
+            Some(&InputPortIndex::new(0)),
+            Some(&OutputPortIndex::new(0)),
+        );
+        let linearized = linearized.borrow();
+        // The integral of the position error is the state, and the estimated state the input.
+        assert_near(linearized.a(), &na::DMatrix::from_element(1, 1, 0.0));
+        assert_near(
+            linearized.b(),
+            &na::DMatrix::from_row_slice(1, 2, &[-1.0, 0.0]),
+        );
+        assert_near(linearized.c(), &na::DMatrix::from_element(1, 1, 0.5));
+        assert_near(
+            linearized.d(),
+            &na::DMatrix::from_row_slice(1, 2, &[-2.0, -1.0]),
+        );
+        // The offsets come from the desired state, which is held constant.
+        assert!((linearized.f0()[0] - 3.0).abs() < 1e-8);
+        assert!((linearized.y0()[0] - (2.0 * 3.0 + 1.0 * 2.0)).abs() < 1e-8);
+    }
+
+    #[test]
+    #[should_panic(expected = "only supports continuous-time systems")]
+    fn test_discrete_state() {
+        let discrete_counter = DiscreteCounter::<f64>::new(0.1, 0.0);
+        let context = discrete_counter.borrow().create_default_context();
+        linearize(
+            &*discrete_counter.borrow(),
+            &mut context.borrow_mut(),
+            None,
+            None,
+        );
+    }
+}
@@ -0,0 +1,197 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use num_traits::Float;
+
+use crate::common::atlas_scalar::AtlasScalar;
+use crate::systems::analysis::implicit_integrator::ImplicitIntegrator;
+use crate::systems::analysis::integrator_base::{IntegratorBase, IntegratorStatistics};
+use crate::systems::framework::system::System;
+
+// Third-order, two-stage Radau IIA integration with a fixed step size. The method is L-stable,
+// so stiff models can be integrated with steps far beyond their fastest time scale.
+pub struct Radau3Integrator<T, S>
+where
+    T: AtlasScalar + Float,
+    S: System<T> + ?Sized,
+{
+    system: Rc<RefCell<S>>,
+    context: Option<Rc<RefCell<S::CN>>>,
+    max_step_size: T,
+    statistics: IntegratorStatistics<T>,
+    is_initialized: bool,
+}
+
+impl<T, S> Radau3Integrator<T, S>
+where
+    T: AtlasScalar + Float,
+    S: System<T> + ?Sized,
+{
+    pub fn new(
+        system: &Rc<RefCell<S>>,
+        max_step_size: T,
+        context: Option<Rc<RefCell<S::CN>>>,
+    ) -> Self {
+        Radau3Integrator {
+            system: system.clone(),
+            context,
+            max_step_size,
+            statistics: IntegratorStatistics::default(),
+            is_initialized: false,
+        }
+    }
+}
+
+impl<T, S> IntegratorBase<T, S> for Radau3Integrator<T, S>
+where
+    T: AtlasScalar + Float,
+    S: System<T> + ?Sized,
+{
+    fn system(&self) -> &Rc<RefCell<S>> {
+        &self.system
+    }
+
+    fn context(&self) -> &Option<Rc<RefCell<S::CN>>> {
+        &self.context
+    }
+
+    fn context_mut(&mut self) -> &mut Option<Rc<RefCell<S::CN>>> {
+        &mut self.context
+    }
+
+    fn maximum_step_size(&self) -> T {
+        self.max_step_size
+    }
+
+    fn set_maximum_step_size(&mut self, max_step_size: T) {
+        self.max_step_size = max_step_size;
+    }
+
+    fn statistics(&self) -> &IntegratorStatistics<T> {
+        &self.statistics
+    }
+
+    fn statistics_mut(&mut self) -> &mut IntegratorStatistics<T> {
+        &mut self.statistics
+    }
+
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+
+    fn set_is_initialized(&mut self, is_initialized: bool) {
+        self.is_initialized = is_initialized;
+    }
+
+    fn do_step(&mut self, h: T) -> bool {
+        let max_step_halvings = self.max_step_halvings();
+        self.do_step_with_halving(h, max_step_halvings)
+    }
+}
+
+impl<T, S> ImplicitIntegrator<T, S> for Radau3Integrator<T, S>
+where
+    T: AtlasScalar + Float,
+    S: System<T> + ?Sized,
+{
+    fn do_implicit_step(&mut self, h: T) -> bool {
+        let t0 = self.time();
+        let x0 = self.continuous_state();
+        let num_states = x0.len();
+        let c = |value: f64| T::from(value).unwrap();
+
+        // Two-stage Radau IIA tableau.
+        let stages = [c(1.0 / 3.0), T::one()];
+        let a = [
+            vec![c(5.0 / 12.0), c(-1.0 / 12.0)],
+            vec![c(3.0 / 4.0), c(1.0 / 4.0)],
+        ];
+        match self.solve_stage_derivatives(t0, &x0, h, &stages, &a) {
+            Some(stage_derivatives) => {
+                let x1 = &x0
+                    + (stage_derivatives.rows(0, num_states) * c(3.0 / 4.0)
+                        + stage_derivatives.rows(num_states, num_states) * c(1.0 / 4.0))
+                        * h;
+                self.set_time_and_continuous_state(t0 + h, &x1);
+                true
+            }
+            None => {
+                self.set_time_and_continuous_state(t0, &x0);
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate nalgebra as na;
+
+    use super::*;
+    use crate::systems::analysis::implicit_euler_integrator::ImplicitEulerIntegrator;
+    use crate::systems::analysis::test_utilities::spring_mass_system::SpringMassSystem;
+    use crate::systems::framework::context::Context;
+
+    fn final_error(integrator: &mut dyn IntegratorBase<f64, SpringMassSystem<f64>>) -> f64 {
+        integrator.initialize();
+        while integrator.integrate_no_further_than(1.0) < 1.0 {}
+
+        let context = integrator.context_link();
+        let state = context.borrow().continuous_state_vector().copy_to_vector();
+        let expected = na::DVector::<f64>::from_vec(vec![1.0_f64.cos(), -1.0_f64.sin()]);
+        (state - expected).norm()
+    }
+
+    #[test]
+    fn test_third_order_accuracy() {
+        let spring_mass_system = SpringMassSystem::<f64>::new(1.0);
+        let create_context = || {
+            let context = spring_mass_system.borrow().create_default_context();
+            spring_mass_system.borrow().set_position_and_velocity(
+                &mut context.borrow_mut(),
+                1.0,
+                0.0,
+            );
+            context
+        };
+
+        let coarse_error = final_error(&mut Radau3Integrator::new(
+            &spring_mass_system,
+            0.02,
+            Some(create_context()),
+        ));
+        let fine_error = final_error(&mut Radau3Integrator::new(
+            &spring_mass_system,
+            0.01,
+            Some(create_context()),
+        ));
+        let implicit_euler_error = final_error(&mut ImplicitEulerIntegrator::new(
+            &spring_mass_system,
+            0.01,
+            Some(create_context()),
+        ));
+
+        assert!(fine_error < 1e-6);
+        // Halving the step shrinks the error by about 2^3.
+        assert!(coarse_error / fine_error > 6.0);
+        assert!(fine_error < implicit_euler_error);
+    }
+
+    #[test]
+    fn test_stiff_system_with_large_steps() {
+        let stiffness = 1e6;
+        let spring_mass_system = SpringMassSystem::<f64>::new(stiffness);
+        let context = spring_mass_system.borrow().create_default_context();
+        spring_mass_system
+            .borrow()
+            .set_position_and_velocity(&mut context.borrow_mut(), 1.0, 0.0);
+
+        let mut integrator = Radau3Integrator::new(&spring_mass_system, 0.1, Some(context.clone()));
+        integrator.initialize();
+        while integrator.integrate_no_further_than(1.0) < 1.0 {}
+
+        let state = context.borrow().continuous_state_vector().copy_to_vector();
+        let energy = 0.5 * (stiffness * state[0] * state[0] + state[1] * state[1]);
+        assert!(energy < 0.5 * stiffness);
+    }
+}
@@ -0,0 +1,138 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use num_traits::Float;
+
+use crate::common::atlas_scalar::AtlasScalar;
+use crate::systems::analysis::integrator_base::{IntegratorBase, IntegratorStatistics};
+use crate::systems::framework::system::System;
+
+// Classic fourth-order Runge-Kutta integration with a fixed step size.
+pub struct RungeKutta4Integrator<T, S>
+where
+    T: AtlasScalar + Float,
+    S: System<T> + ?Sized,
+{
+    system: Rc<RefCell<S>>,
+    context: Option<Rc<RefCell<S::CN>>>,
+    max_step_size: T,
+    statistics: IntegratorStatistics<T>,
+    is_initialized: bool,
+}
+
+impl<T, S> RungeKutta4Integrator<T, S>
+where
+    T: AtlasScalar + Float,
+    S: System<T> + ?Sized,
+{
+    pub fn new(
+        system: &Rc<RefCell<S>>,
+        max_step_size: T,
+        context: Option<Rc<RefCell<S::CN>>>,
+    ) -> Self {
+        RungeKutta4Integrator {
+            system: system.clone(),
+            context,
+            max_step_size,
+            statistics: IntegratorStatistics::default(),
+            is_initialized: false,
+        }
+    }
+}
+
+impl<T, S> IntegratorBase<T, S> for RungeKutta4Integrator<T, S>
+where
+    T: AtlasScalar + Float,
+    S: System<T> + ?Sized,
+{
+    fn system(&self) -> &Rc<RefCell<S>> {
+        &self.system
+    }
+
+    fn context(&self) -> &Option<Rc<RefCell<S::CN>>> {
+        &self.context
+    }
+
+    fn context_mut(&mut self) -> &mut Option<Rc<RefCell<S::CN>>> {
+        &mut self.context
+    }
+
+    fn maximum_step_size(&self) -> T {
+        self.max_step_size
+    }
+
+    fn set_maximum_step_size(&mut self, max_step_size: T) {
+        self.max_step_size = max_step_size;
+    }
+
+    fn statistics(&self) -> &IntegratorStatistics<T> {
+        &self.statistics
+    }
+
+    fn statistics_mut(&mut self) -> &mut IntegratorStatistics<T> {
+        &mut self.statistics
+    }
+
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+
+    fn set_is_initialized(&mut self, is_initialized: bool) {
+        self.is_initialized = is_initialized;
+    }
+
+    fn do_step(&mut self, h: T) -> bool {
+        let two = T::one() + T::one();
+        let six = two + two + two;
+        let half_h = h / two;
+
+        let t0 = self.time();
+        let x0 = self.continuous_state();
+        let k1 = self.eval_time_derivatives();
+
+        self.set_time_and_continuous_state(t0 + half_h, &(&x0 + &k1 * half_h));
+        let k2 = self.eval_time_derivatives();
+
+        self.set_continuous_state(&(&x0 + &k2 * half_h));
+        let k3 = self.eval_time_derivatives();
+
+        self.set_time_and_continuous_state(t0 + h, &(&x0 + &k3 * h));
+        let k4 = self.eval_time_derivatives();
+
+        let x1 = x0 + (k1 + (k2 + k3) * two + k4) * (h / six);
+        self.set_time_and_continuous_state(t0 + h, &x1);
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate nalgebra as na;
+
+    use super::*;
+    use crate::systems::analysis::test_utilities::spring_mass_system::SpringMassSystem;
+    use crate::systems::framework::context::Context;
+
+    #[test]
+    fn test_spring_mass_accuracy() {
+        let spring_mass_system = SpringMassSystem::<f64>::new(1.0);
+        let context = spring_mass_system.borrow().create_default_context();
+        spring_mass_system
+            .borrow()
+            .set_position_and_velocity(&mut context.borrow_mut(), 1.0, 0.0);
+
+        let mut integrator =
+            RungeKutta4Integrator::new(&spring_mass_system, 0.01, Some(context.clone()));
+        integrator.initialize();
+        while integrator.integrate_no_further_than(1.0) < 1.0 {}
+
+        let context = context.borrow();
+        assert_eq!(*context.time(), 1.0);
+        let state = context.continuous_state_vector().copy_to_vector();
+        let expected = na::DVector::<f64>::from_vec(vec![1.0_f64.cos(), -1.0_f64.sin()]);
+        assert!((state - expected).norm() < 1e-9);
+        assert_eq!(integrator.num_steps_taken(), 100);
+        assert_eq!(integrator.num_derivative_evaluations(), 400);
+    }
+}
@@ -0,0 +1,268 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+extern crate nalgebra as na;
+
+use num_traits::Float;
+
+use crate::common::atlas_scalar::AtlasScalar;
+use crate::systems::analysis::integrator_base::{
+    ErrorControl, IntegratorBase, IntegratorStatistics,
+};
+use crate::systems::framework::system::System;
+
+// Fifth-order Dormand-Prince (RK45) integration with an embedded fourth-order error estimate.
+pub struct RungeKutta5Integrator<T, S>
+where
+    T: AtlasScalar + Float,
+    S: System<T> + ?Sized,
+{
+    system: Rc<RefCell<S>>,
+    context: Option<Rc<RefCell<S::CN>>>,
+    max_step_size: T,
+    statistics: IntegratorStatistics<T>,
+    is_initialized: bool,
+    error_control: ErrorControl<T>,
+    error_estimate: Option<na::DVector<T>>,
+}
+
+impl<T, S> RungeKutta5Integrator<T, S>
+where
+    T: AtlasScalar + Float,
+    S: System<T> + ?Sized,
+{
+    pub fn new(
+        system: &Rc<RefCell<S>>,
+        max_step_size: T,
+        context: Option<Rc<RefCell<S::CN>>>,
+    ) -> Self {
+        RungeKutta5Integrator {
+            system: system.clone(),
+            context,
+            max_step_size,
+            statistics: IntegratorStatistics::default(),
+            is_initialized: false,
+            error_control: ErrorControl::default(),
+            error_estimate: None,
+        }
+    }
+}
+
+impl<T, S> IntegratorBase<T, S> for RungeKutta5Integrator<T, S>
+where
+    T: AtlasScalar + Float,
+    S: System<T> + ?Sized,
+{
+    fn system(&self) -> &Rc<RefCell<S>> {
+        &self.system
+    }
+
+    fn context(&self) -> &Option<Rc<RefCell<S::CN>>> {
+        &self.context
+    }
+
+    fn context_mut(&mut self) -> &mut Option<Rc<RefCell<S::CN>>> {
+        &mut self.context
+    }
+
+    fn maximum_step_size(&self) -> T {
+        self.max_step_size
+    }
+
+    fn set_maximum_step_size(&mut self, max_step_size: T) {
+        self.max_step_size = max_step_size;
+    }
+
+    fn statistics(&self) -> &IntegratorStatistics<T> {
+        &self.statistics
+    }
+
+    fn statistics_mut(&mut self) -> &mut IntegratorStatistics<T> {
+        &mut self.statistics
+    }
+
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+
+    fn set_is_initialized(&mut self, is_initialized: bool) {
+        self.is_initialized = is_initialized;
+    }
+
+    fn supports_error_estimation(&self) -> bool {
+        true
+    }
+
+    fn error_estimate_order(&self) -> usize {
+        5
+    }
+
+    fn error_control(&self) -> Option<&ErrorControl<T>> {
+        Some(&self.error_control)
+    }
+
+    fn error_control_mut(&mut self) -> Option<&mut ErrorControl<T>> {
+        Some(&mut self.error_control)
+    }
+
+    fn error_estimate(&self) -> Option<&na::DVector<T>> {
+        self.error_estimate.as_ref()
+    }
+
+    fn do_step(&mut self, h: T) -> bool {
+        let c = |value: f64| T::from(value).unwrap();
+
+        let t0 = self.time();
+        let x0 = self.continuous_state();
+        let k1 = self.eval_time_derivatives();
+
+        self.set_time_and_continuous_state(
+            t0 + h * c(1.0 / 5.0),
+            &(&x0 + &k1 * (h * c(1.0 / 5.0))),
+        );
+        let k2 = self.eval_time_derivatives();
+
+        self.set_time_and_continuous_state(
+            t0 + h * c(3.0 / 10.0),
+            &(&x0 + (&k1 * c(3.0 / 40.0) + &k2 * c(9.0 / 40.0)) * h),
+        );
+        let k3 = self.eval_time_derivatives();
+
+        self.set_time_and_continuous_state(
+            t0 + h * c(4.0 / 5.0),
+            &(&x0 + (&k1 * c(44.0 / 45.0) + &k2 * c(-56.0 / 15.0) + &k3 * c(32.0 / 9.0)) * h),
+        );
+        let k4 = self.eval_time_derivatives();
+
+        self.set_time_and_continuous_state(
+            t0 + h * c(8.0 / 9.0),
+            &(&x0
+                + (&k1 * c(19372.0 / 6561.0)
+                    + &k2 * c(-25360.0 / 2187.0)
+                    + &k3 * c(64448.0 / 6561.0)
+                    + &k4 * c(-212.0 / 729.0))
+                    * h),
+        );
+        let k5 = self.eval_time_derivatives();
+
+        self.set_time_and_continuous_state(
+            t0 + h,
+            &(&x0
+                + (&k1 * c(9017.0 / 3168.0)
+                    + &k2 * c(-355.0 / 33.0)
+                    + &k3 * c(46732.0 / 5247.0)
+                    + &k4 * c(49.0 / 176.0)
+                    + &k5 * c(-5103.0 / 18656.0))
+                    * h),
+        );
+        let k6 = self.eval_time_derivatives();
+
+        let x1 = &x0
+            + (&k1 * c(35.0 / 384.0)
+                + &k3 * c(500.0 / 1113.0)
+                + &k4 * c(125.0 / 192.0)
+                + &k5 * c(-2187.0 / 6784.0)
+                + &k6 * c(11.0 / 84.0))
+                * h;
+        self.set_time_and_continuous_state(t0 + h, &x1);
+        let k7 = self.eval_time_derivatives();
+
+        // Difference between the fifth-order solution and the embedded fourth-order one.
+        self.error_estimate = Some(
+            (k1 * c(71.0 / 57600.0)
+                + k3 * c(-71.0 / 16695.0)
+                + k4 * c(71.0 / 1920.0)
+                + k5 * c(-17253.0 / 339200.0)
+                + k6 * c(22.0 / 525.0)
+                + k7 * c(-1.0 / 40.0))
+                * h,
+        );
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate nalgebra as na;
+
+    use super::*;
+    use crate::systems::analysis::bogacki_shampine3_integrator::BogackiShampine3Integrator;
+    use crate::systems::analysis::test_utilities::spring_mass_system::SpringMassSystem;
+    use crate::systems::framework::context::Context;
+
+    #[test]
+    fn test_spring_mass_accuracy() {
+        let spring_mass_system = SpringMassSystem::<f64>::new(1.0);
+        let context = spring_mass_system.borrow().create_default_context();
+        spring_mass_system
+            .borrow()
+            .set_position_and_velocity(&mut context.borrow_mut(), 1.0, 0.0);
+
+        let mut integrator =
+            RungeKutta5Integrator::new(&spring_mass_system, 0.5, Some(context.clone()));
+        integrator.set_relative_tolerance(1e-10);
+        integrator.set_absolute_tolerance(1e-10);
+        integrator.initialize();
+        while integrator.integrate_no_further_than(2.0) < 2.0 {}
+
+        let context = context.borrow();
+        assert_eq!(*context.time(), 2.0);
+        let state = context.continuous_state_vector().copy_to_vector();
+        let expected = na::DVector::<f64>::from_vec(vec![2.0_f64.cos(), -2.0_f64.sin()]);
+        assert!((state - expected).norm() < 1e-8);
+    }
+
+    #[test]
+    fn test_fewer_steps_than_third_order() {
+        let run = |integrator: &mut dyn IntegratorBase<f64, SpringMassSystem<f64>>| {
+            integrator.set_relative_tolerance(1e-8);
+            integrator.set_absolute_tolerance(1e-8);
+            integrator.initialize();
+            while integrator.integrate_no_further_than(5.0) < 5.0 {}
+            integrator.num_steps_taken()
+        };
+
+        let spring_mass_system = SpringMassSystem::<f64>::new(1.0);
+        let create_context = || {
+            let context = spring_mass_system.borrow().create_default_context();
+            spring_mass_system.borrow().set_position_and_velocity(
+                &mut context.borrow_mut(),
+                1.0,
+                0.0,
+            );
+            context
+        };
+
+        let num_steps_rk5 = run(&mut RungeKutta5Integrator::new(
+            &spring_mass_system,
+            1.0,
+            Some(create_context()),
+        ));
+        let num_steps_bs3 = run(&mut BogackiShampine3Integrator::new(
+            &spring_mass_system,
+            1.0,
+            Some(create_context()),
+        ));
+        assert!(num_steps_rk5 < num_steps_bs3);
+    }
+
+    #[test]
+    fn test_fixed_step_mode() {
+        let spring_mass_system = SpringMassSystem::<f64>::new(1.0);
+        let context = spring_mass_system.borrow().create_default_context();
+        spring_mass_system
+            .borrow()
+            .set_position_and_velocity(&mut context.borrow_mut(), 1.0, 0.0);
+
+        let mut integrator =
+            RungeKutta5Integrator::new(&spring_mass_system, 0.25, Some(context.clone()));
+        integrator.set_fixed_step_mode(true);
+        integrator.initialize();
+        while integrator.integrate_no_further_than(1.0) < 1.0 {}
+
+        assert_eq!(integrator.num_steps_taken(), 4);
+        assert_eq!(integrator.num_derivative_evaluations(), 28);
+        assert_eq!(integrator.num_step_shrinkages_from_error_control(), 0);
+    }
+}
@@ -0,0 +1,151 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use num_traits::Float;
+
+use crate::common::atlas_scalar::AtlasScalar;
+use crate::systems::analysis::integrator_base::{IntegratorBase, IntegratorStatistics};
+use crate::systems::framework::system::System;
+
+// First-order symplectic Euler integration. The generalized velocities v and misc states z are
+// advanced with the derivatives at the start of the step, and the generalized positions q are
+// advanced with qdot evaluated at the updated velocities.
+pub struct SemiExplicitEulerIntegrator<T, S>
+where
+    T: AtlasScalar + Float,
+    S: System<T> + ?Sized,
+{
+    system: Rc<RefCell<S>>,
+    context: Option<Rc<RefCell<S::CN>>>,
+    max_step_size: T,
+    statistics: IntegratorStatistics<T>,
+    is_initialized: bool,
+}
+
+impl<T, S> SemiExplicitEulerIntegrator<T, S>
+where
+    T: AtlasScalar + Float,
+    S: System<T> + ?Sized,
+{
+    pub fn new(
+        system: &Rc<RefCell<S>>,
+        max_step_size: T,
+        context: Option<Rc<RefCell<S::CN>>>,
+    ) -> Self {
+        SemiExplicitEulerIntegrator {
+            system: system.clone(),
+            context,
+            max_step_size,
+            statistics: IntegratorStatistics::default(),
+            is_initialized: false,
+        }
+    }
+}
+
+impl<T, S> IntegratorBase<T, S> for SemiExplicitEulerIntegrator<T, S>
+where
+    T: AtlasScalar + Float,
+    S: System<T> + ?Sized,
+{
+    fn system(&self) -> &Rc<RefCell<S>> {
+        &self.system
+    }
+
+    fn context(&self) -> &Option<Rc<RefCell<S::CN>>> {
+        &self.context
+    }
+
+    fn context_mut(&mut self) -> &mut Option<Rc<RefCell<S::CN>>> {
+        &mut self.context
+    }
+
+    fn maximum_step_size(&self) -> T {
+        self.max_step_size
+    }
+
+    fn set_maximum_step_size(&mut self, max_step_size: T) {
+        self.max_step_size = max_step_size;
+    }
+
+    fn statistics(&self) -> &IntegratorStatistics<T> {
+        &self.statistics
+    }
+
+    fn statistics_mut(&mut self) -> &mut IntegratorStatistics<T> {
+        &mut self.statistics
+    }
+
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+
+    fn set_is_initialized(&mut self, is_initialized: bool) {
+        self.is_initialized = is_initialized;
+    }
+
+    fn do_step(&mut self, h: T) -> bool {
+        let t0 = self.time();
+        let x0 = self.continuous_state();
+        let (num_q, num_v, num_z) = self.continuous_state_sizes();
+        let xdot0 = self.eval_time_derivatives();
+
+        // Advance the velocities and misc states first, then re-evaluate qdot with the
+        // updated velocities.
+        let mut x1 = x0.clone();
+        let num_vz = num_v + num_z;
+        x1.rows_mut(num_q, num_vz)
+            .axpy(h, &xdot0.rows(num_q, num_vz), T::one());
+        self.set_continuous_state(&x1);
+        let xdot1 = self.eval_time_derivatives();
+
+        x1.rows_mut(0, num_q)
+            .axpy(h, &xdot1.rows(0, num_q), T::one());
+        self.set_time_and_continuous_state(t0 + h, &x1);
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate nalgebra as na;
+
+    use super::*;
+    use crate::systems::analysis::test_utilities::spring_mass_system::SpringMassSystem;
+    use crate::systems::framework::context::Context;
+
+    #[test]
+    fn test_velocity_is_updated_first() {
+        let spring_mass_system = SpringMassSystem::<f64>::new(1.0);
+        let context = spring_mass_system.borrow().create_default_context();
+        spring_mass_system
+            .borrow()
+            .set_position_and_velocity(&mut context.borrow_mut(), 1.0, 0.0);
+
+        let mut integrator =
+            SemiExplicitEulerIntegrator::new(&spring_mass_system, 0.1, Some(context.clone()));
+        integrator.initialize();
+        integrator.integrate_with_single_fixed_step(0.1);
+
+        let state = context.borrow().continuous_state_vector().copy_to_vector();
+        assert!((state - na::DVector::<f64>::from_vec(vec![0.99, -0.1])).norm() < 1e-12);
+    }
+
+    #[test]
+    fn test_energy_stays_bounded() {
+        let spring_mass_system = SpringMassSystem::<f64>::new(1.0);
+        let context = spring_mass_system.borrow().create_default_context();
+        spring_mass_system
+            .borrow()
+            .set_position_and_velocity(&mut context.borrow_mut(), 1.0, 0.0);
+
+        let mut integrator =
+            SemiExplicitEulerIntegrator::new(&spring_mass_system, 0.05, Some(context.clone()));
+        integrator.initialize();
+        while integrator.integrate_no_further_than(100.0) < 100.0 {}
+
+        let state = context.borrow().continuous_state_vector().copy_to_vector();
+        let energy = 0.5 * (state[0] * state[0] + state[1] * state[1]);
+        assert!((energy - 0.5).abs() < 0.05);
+    }
+}
@@ -0,0 +1,522 @@
+use std::cell::{Ref, RefCell, RefMut};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use num_traits::Float;
+
+use crate::common::atlas_scalar::AtlasScalar;
+use crate::systems::analysis::integrator_base::IntegratorBase;
+use crate::systems::analysis::runge_kutta4_integrator::RungeKutta4Integrator;
+use crate::systems::framework::context::Context;
+use crate::systems::framework::event::PeriodicEventData;
+use crate::systems::framework::state::State;
+use crate::systems::framework::system::System;
+
+// Advances the Context of a System through time, in the manner of Drake's Simulator.
+pub struct Simulator<T, S>
+where
+    T: AtlasScalar + Float,
+    S: System<T> + ?Sized,
+{
+    system: Rc<RefCell<S>>,
+    context: Rc<RefCell<S::CN>>,
+    integrator: Box<dyn IntegratorBase<T, S>>,
+    target_realtime_rate: f64,
+    initialization_done: bool,
+    num_steps_taken: usize,
+    num_discrete_updates: usize,
+    num_unrestricted_updates: usize,
+    discrete_update_timings: Vec<PeriodicEventData>,
+    unrestricted_update_timings: Vec<PeriodicEventData>,
+    next_update_time: f64,
+    initial_simtime: T,
+    initial_realtime: Instant,
+}
+
+impl<T, S> Simulator<T, S>
+where
+    T: AtlasScalar + Float,
+    S: System<T> + ?Sized,
+{
+    pub fn new(system: &Rc<RefCell<S>>) -> Self {
+        let context = system.borrow().create_default_context();
+        let integrator = Box::new(RungeKutta4Integrator::new(
+            system,
+            T::from(1e-3).unwrap(),
+            Some(context.clone()),
+        ));
+
+        Simulator::<T, S> {
+            system: system.clone(),
+            context,
+            integrator,
+            target_realtime_rate: 0.0,
+            initialization_done: false,
+            num_steps_taken: 0,
+            num_discrete_updates: 0,
+            num_unrestricted_updates: 0,
+            discrete_update_timings: vec![],
+            unrestricted_update_timings: vec![],
+            next_update_time: f64::INFINITY,
+            initial_simtime: T::zero(),
+            initial_realtime: Instant::now(),
+        }
+    }
+
+    pub fn initialize(&mut self) {
+        self.system
+            .borrow()
+            .validate_context(self.context.borrow().as_base())
+            .unwrap_or_else(|error| panic!("Simulator::initialize: {}", error));
+
+        if !self.integrator.is_initialized() {
+            self.integrator.initialize();
+        }
+
+        // Periodic events due at the initial time are handled at the start of the first step.
+        self.discrete_update_timings = self.system.borrow().periodic_discrete_update_timings();
+        self.unrestricted_update_timings =
+            self.system.borrow().periodic_unrestricted_update_timings();
+        let time = self.context.borrow().time().to_f64().unwrap();
+        self.next_update_time = self.calc_next_update_time(time, true);
+
+        self.num_steps_taken = 0;
+        self.num_discrete_updates = 0;
+        self.num_unrestricted_updates = 0;
+        self.reset_statistics();
+        self.initialization_done = true;
+    }
+
+    pub fn advance_to(&mut self, boundary_time: T) {
+        if !self.initialization_done {
+            self.initialize();
+        }
+
+        let mut time = *self.context.borrow().time();
+        assert!(
+            boundary_time >= time,
+            "Simulator::advance_to: boundary time {:?} is before the current time {:?}",
+            boundary_time,
+            time
+        );
+
+        // Events due exactly at the boundary are left for the next call.
+        while time < boundary_time {
+            // The event time is compared in T, in which the integrator lands on it. In f64 the
+            // time of an f32 context would never equal it.
+            let next_update_time = T::from(self.next_update_time).unwrap();
+            if time >= next_update_time {
+                self.handle_updates();
+            }
+
+            let next_update_time = T::from(self.next_update_time).unwrap();
+            time = self
+                .integrator
+                .integrate_no_further_than(boundary_time.min(next_update_time));
+            self.num_steps_taken += 1;
+
+            self.pause_if_too_fast();
+        }
+    }
+
+    fn calc_next_update_time(&self, time: f64, inclusive: bool) -> f64 {
+        self.discrete_update_timings
+            .iter()
+            .chain(self.unrestricted_update_timings.iter())
+            .map(|timing| timing.next_event_time(time, inclusive))
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    fn due_timings(&self, timings: &[PeriodicEventData]) -> Vec<PeriodicEventData> {
+        timings
+            .iter()
+            .filter(|timing| {
+                timing.next_event_time(self.next_update_time, true) == self.next_update_time
+            })
+            .cloned()
+            .collect()
+    }
+
+    // Applies the updates of every periodic event due now: first the unrestricted updates, then
+    // the discrete updates. All updates of one kind are computed from the same state.
+    fn handle_updates(&mut self) {
+        let due_timings = self.due_timings(&self.unrestricted_update_timings);
+        if !due_timings.is_empty() {
+            let mut state = self.context.borrow().state().clone();
+            self.system.borrow().calc_unrestricted_updates(
+                &self.context.borrow(),
+                &due_timings,
+                &mut state,
+            );
+            self.context.borrow_mut().state_mut().set_from(&state);
+            self.num_unrestricted_updates += 1;
+        }
+
+        let due_timings = self.due_timings(&self.discrete_update_timings);
+        if !due_timings.is_empty() {
+            let mut discrete_state = self.context.borrow().discrete_state().clone();
+            self.system.borrow().calc_discrete_variable_updates(
+                &self.context.borrow(),
+                &due_timings,
+                &mut discrete_state,
+            );
+            self.context
+                .borrow_mut()
+                .discrete_state_mut()
+                .set_from(&discrete_state);
+            self.num_discrete_updates += 1;
+        }
+
+        self.next_update_time = self.calc_next_update_time(self.next_update_time, false);
+    }
+
+    fn pause_if_too_fast(&self) {
+        if self.target_realtime_rate <= 0.0 {
+            return;
+        }
+
+        let simtime_advanced = (*self.context.borrow().time() - self.initial_simtime)
+            .to_f64()
+            .unwrap();
+        let desired_realtime = simtime_advanced / self.target_realtime_rate;
+        let realtime_passed = self.initial_realtime.elapsed().as_secs_f64();
+        if desired_realtime > realtime_passed {
+            std::thread::sleep(Duration::from_secs_f64(desired_realtime - realtime_passed));
+        }
+    }
+
+    pub fn reset_statistics(&mut self) {
+        self.initial_simtime = *self.context.borrow().time();
+        self.initial_realtime = Instant::now();
+    }
+
+    pub fn get_context(&self) -> Ref<'_, S::CN> {
+        self.context.borrow()
+    }
+
+    pub fn get_mutable_context(&mut self) -> RefMut<'_, S::CN> {
+        self.context.borrow_mut()
+    }
+
+    pub fn get_system(&self) -> Ref<'_, S> {
+        self.system.borrow()
+    }
+
+    pub fn get_integrator(&self) -> &dyn IntegratorBase<T, S> {
+        self.integrator.as_ref()
+    }
+
+    pub fn get_mutable_integrator(&mut self) -> &mut dyn IntegratorBase<T, S> {
+        self.integrator.as_mut()
+    }
+
+    pub fn reset_integrator(&mut self, mut integrator: Box<dyn IntegratorBase<T, S>>) {
+        integrator.reset_context(self.context.clone());
+        self.integrator = integrator;
+        self.initialization_done = false;
+    }
+
+    pub fn get_target_realtime_rate(&self) -> f64 {
+        self.target_realtime_rate
+    }
+
+    pub fn set_target_realtime_rate(&mut self, realtime_rate: f64) {
+        assert!(realtime_rate >= 0.0);
+        self.target_realtime_rate = realtime_rate;
+    }
+
+    pub fn get_actual_realtime_rate(&self) -> f64 {
+        let simtime_advanced = (*self.context.borrow().time() - self.initial_simtime)
+            .to_f64()
+            .unwrap();
+        let realtime_passed = self.initial_realtime.elapsed().as_secs_f64();
+        if realtime_passed > 0.0 {
+            simtime_advanced / realtime_passed
+        } else {
+            0.0
+        }
+    }
+
+    pub fn get_num_steps_taken(&self) -> usize {
+        self.num_steps_taken
+    }
+
+    pub fn get_num_discrete_updates(&self) -> usize {
+        self.num_discrete_updates
+    }
+
+    pub fn get_num_unrestricted_updates(&self) -> usize {
+        self.num_unrestricted_updates
+    }
+
+    pub fn has_been_initialized(&self) -> bool {
+        self.initialization_done
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate nalgebra as na;
+
+    use super::*;
+    use crate::systems::analysis::explicit_euler_integrator::ExplicitEulerIntegrator;
+    use crate::systems::analysis::test_utilities::discrete_counter::DiscreteCounter;
+    use crate::systems::analysis::test_utilities::spring_mass_system::SpringMassSystem;
+    use crate::systems::analysis::test_utilities::time_recorder::TimeRecorder;
+    use crate::systems::controllers::pid_controller::PIDController;
+    use crate::systems::framework::basic_vector::BasicVector;
+    use crate::systems::framework::diagram::{DiagramExt, SystemLinkExt};
+    use crate::systems::framework::diagram_builder::DiagramBuilder;
+    use crate::systems::framework::framework_common::{
+        AbstractStateIndex, DiscreteStateIndex, InputPortIndex,
+    };
+    use crate::systems::primitives::adder::Adder;
+    use crate::systems::primitives::linear_system::LinearSystem;
+
+    #[test]
+    fn test_advance_stateless_diagram() {
+        let mut diagram_builder = DiagramBuilder::<f64>::new();
+        let adder = Adder::<f64>::new(2, 3);
+        diagram_builder.add_leaf_system(&adder);
+        let diagram = diagram_builder.build();
+
+        let mut simulator = Simulator::new(&diagram);
+        simulator.advance_to(0.25);
+        assert_eq!(*simulator.get_context().time(), 0.25);
+
+        simulator.advance_to(0.5);
+        assert_eq!(*simulator.get_context().time(), 0.5);
+        assert_eq!(simulator.get_num_steps_taken(), 500);
+    }
+
+    #[test]
+    fn test_advance_pid_controller() {
+        let kp = na::DVector::<f64>::from_vec(vec![1.0, 2.0]);
+        let ki = na::DVector::<f64>::from_vec(vec![0.5, 0.5]);
+        let kd = na::DVector::<f64>::from_vec(vec![0.1, 0.1]);
+        let pid_controller = PIDController::<f64>::new(kp, ki, kd);
+
+        let mut simulator = Simulator::new(&pid_controller);
+        pid_controller
+            .borrow()
+            .input_port(&InputPortIndex::new(0))
+            .fix_value(
+                simulator.get_mutable_context(),
+                BasicVector::<f64>::from_vec(vec![0.0, 0.0, 0.0, 0.0]),
+            );
+        pid_controller
+            .borrow()
+            .input_port(&InputPortIndex::new(1))
+            .fix_value(
+                simulator.get_mutable_context(),
+                BasicVector::<f64>::from_vec(vec![1.0, -2.0, 0.0, 0.0]),
+            );
+
+        simulator.initialize();
+        simulator.advance_to(1.5);
+
+        let context = simulator.get_context();
+        assert_eq!(*context.time(), 1.5);
+        let integral = context.continuous_state_vector().copy_to_vector();
+        assert!((integral - na::DVector::<f64>::from_vec(vec![1.5, -3.0])).norm() < 1e-9);
+    }
+
+    #[test]
+    fn test_periodic_discrete_update() {
+        let discrete_counter = DiscreteCounter::<f64>::new(0.1, 0.0);
+        let mut simulator = Simulator::new(&discrete_counter);
+
+        // The update due at the boundary is left for the next call to advance_to.
+        simulator.advance_to(1.0);
+        assert_eq!(*simulator.get_context().time(), 1.0);
+        assert_eq!(simulator.get_num_discrete_updates(), 10);
+        assert_eq!(
+            discrete_counter.borrow().count(&simulator.get_context()),
+            10.0
+        );
+
+        simulator.advance_to(1.05);
+        assert_eq!(
+            discrete_counter.borrow().count(&simulator.get_context()),
+            11.0
+        );
+    }
+
+    #[test]
+    fn test_periodic_discrete_update_f32() {
+        // 0.1f32 is not 0.1, so the events must be matched in f32.
+        let discrete_counter = DiscreteCounter::<f32>::new(0.1, 0.0);
+        let mut simulator = Simulator::new(&discrete_counter);
+
+        simulator.advance_to(0.35);
+        assert_eq!(*simulator.get_context().time(), 0.35f32);
+        assert_eq!(simulator.get_num_discrete_updates(), 4);
+        assert_eq!(
+            discrete_counter.borrow().count(&simulator.get_context()),
+            4.0
+        );
+    }
+
+    #[test]
+    fn test_advance_diagram_converted_to_f32() {
+        // A discrete double integrator driven by a unit input, converted from f64.
+        let mut diagram_builder = DiagramBuilder::<f64>::new();
+        let linear_system = LinearSystem::<f64>::new_linear(
+            na::DMatrix::from_row_slice(2, 2, &[1.0, 1.0, 0.0, 1.0]),
+            na::DMatrix::from_row_slice(2, 1, &[0.0, 1.0]),
+            na::DMatrix::zeros(0, 0),
+            na::DMatrix::zeros(0, 0),
+            0.1,
+        );
+        diagram_builder.add_leaf_system(&linear_system);
+        diagram_builder.export_input_port(linear_system.input_port(InputPortIndex::new(0)));
+        let diagram = diagram_builder.build().borrow().to_scalar_type::<f32>();
+
+        let mut simulator = Simulator::new(&diagram);
+        diagram.input_port_mut(&InputPortIndex::new(0)).fix_value(
+            simulator.get_mutable_context(),
+            BasicVector::<f32>::from_vec(vec![1.0]),
+        );
+        simulator.advance_to(0.35);
+
+        // Updates at 0, 0.1, 0.2 and 0.3.
+        assert_eq!(simulator.get_num_discrete_updates(), 4);
+        assert_eq!(
+            simulator
+                .get_context()
+                .discrete_state()
+                .value(&DiscreteStateIndex::new(0)),
+            na::DVector::from_vec(vec![6.0f32, 4.0])
+        );
+    }
+
+    #[test]
+    fn test_periodic_discrete_update_in_diagram() {
+        let mut diagram_builder = DiagramBuilder::<f64>::new();
+        let discrete_counter1 = DiscreteCounter::<f64>::new(0.1, 0.0);
+        let discrete_counter2 = DiscreteCounter::<f64>::new(0.25, 0.05);
+        diagram_builder.add_leaf_system(&discrete_counter1);
+        diagram_builder.add_leaf_system(&discrete_counter2);
+        let diagram = diagram_builder.build();
+
+        let mut simulator = Simulator::new(&diagram);
+        simulator.advance_to(1.0);
+
+        // The second counter updates at 0.05, 0.3, 0.55 and 0.8, the last together with the first
+        // counter.
+        let counts = simulator.get_context().discrete_state().clone();
+        assert_eq!(counts.value(&DiscreteStateIndex::new(0))[0], 10.0);
+        assert_eq!(counts.value(&DiscreteStateIndex::new(1))[0], 4.0);
+        assert_eq!(simulator.get_num_discrete_updates(), 13);
+    }
+
+    #[test]
+    fn test_periodic_unrestricted_update() {
+        let time_recorder = TimeRecorder::<f64>::new(0.25, 0.0);
+        let mut simulator = Simulator::new(&time_recorder);
+        simulator.advance_to(1.0);
+
+        assert_eq!(simulator.get_num_unrestricted_updates(), 4);
+        assert_eq!(
+            time_recorder.borrow().times(&simulator.get_context()),
+            vec![0.0, 0.25, 0.5, 0.75]
+        );
+    }
+
+    #[test]
+    fn test_updates_preserve_diagram_state() {
+        let mut diagram_builder = DiagramBuilder::<f64>::new();
+        let spring_mass_system = SpringMassSystem::<f64>::new(1.0);
+        let discrete_counter = DiscreteCounter::<f64>::new(0.1, 0.0);
+        let time_recorder = TimeRecorder::<f64>::new(0.25, 0.0);
+        diagram_builder.add_leaf_system(&spring_mass_system);
+        diagram_builder.add_leaf_system(&discrete_counter);
+        diagram_builder.add_leaf_system(&time_recorder);
+        let diagram = diagram_builder.build();
+
+        let mut simulator = Simulator::new(&diagram);
+        simulator
+            .get_mutable_context()
+            .continuous_state_vector_mut()
+            .set_from_vector(&na::DVector::<f64>::from_vec(vec![1.0, 0.0]));
+        simulator.advance_to(1.0);
+
+        // Updating the whole state leaves the parts that were not updated untouched.
+        let context = simulator.get_context();
+        let state = context.continuous_state_vector().copy_to_vector();
+        let expected = na::DVector::<f64>::from_vec(vec![1.0_f64.cos(), -1.0_f64.sin()]);
+        assert!((state - expected).norm() < 1e-9);
+        assert_eq!(
+            context.discrete_state().value(&DiscreteStateIndex::new(0))[0],
+            10.0
+        );
+        assert_eq!(
+            *context
+                .abstract_state()
+                .get_value::<Vec<f64>>(&AbstractStateIndex::new(0)),
+            vec![0.0, 0.25, 0.5, 0.75]
+        );
+    }
+
+    #[test]
+    fn test_reset_integrator() {
+        let spring_mass_system = SpringMassSystem::<f64>::new(1.0);
+        let mut simulator = Simulator::new(&spring_mass_system);
+        spring_mass_system.borrow().set_position_and_velocity(
+            &mut simulator.get_mutable_context(),
+            1.0,
+            0.0,
+        );
+        simulator.reset_integrator(Box::new(ExplicitEulerIntegrator::new(
+            &spring_mass_system,
+            0.5,
+            None,
+        )));
+
+        simulator.advance_to(1.0);
+        assert_eq!(simulator.get_integrator().num_steps_taken(), 2);
+
+        let state = simulator
+            .get_context()
+            .continuous_state_vector()
+            .copy_to_vector();
+        assert!((state - na::DVector::<f64>::from_vec(vec![0.75, -1.0])).norm() < 1e-12);
+    }
+
+    #[test]
+    fn test_advance_diagram_with_continuous_state() {
+        let mut diagram_builder = DiagramBuilder::<f64>::new();
+        let spring_mass_system = SpringMassSystem::<f64>::new(1.0);
+        diagram_builder.add_leaf_system(&spring_mass_system);
+        let diagram = diagram_builder.build();
+
+        let mut simulator = Simulator::new(&diagram);
+        simulator
+            .get_mutable_context()
+            .continuous_state_vector_mut()
+            .set_from_vector(&na::DVector::<f64>::from_vec(vec![1.0, 0.0]));
+        simulator.advance_to(1.0);
+
+        let state = simulator
+            .get_context()
+            .continuous_state_vector()
+            .copy_to_vector();
+        let expected = na::DVector::<f64>::from_vec(vec![1.0_f64.cos(), -1.0_f64.sin()]);
+        assert!((state - expected).norm() < 1e-9);
+    }
+
+    #[test]
+    fn test_target_realtime_rate() {
+        let adder = Adder::<f64>::new(2, 1);
+        let mut simulator = Simulator::new(&adder);
+        simulator
+            .get_mutable_integrator()
+            .set_maximum_step_size(0.01);
+        simulator.set_target_realtime_rate(10.0);
+        assert_eq!(simulator.get_target_realtime_rate(), 10.0);
+
+        let start = Instant::now();
+        simulator.advance_to(0.2);
+        assert!(start.elapsed().as_secs_f64() >= 0.02);
+    }
+}
@@ -0,0 +1,3 @@
+pub mod discrete_counter;
+pub mod spring_mass_system;
+pub mod time_recorder;
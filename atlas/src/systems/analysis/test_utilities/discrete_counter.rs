@@ -0,0 +1,44 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use atlas_derives::leaf_system;
+
+extern crate nalgebra as na;
+
+use crate::common::atlas_scalar::AtlasScalar;
+use crate::systems::framework::context::Context;
+use crate::systems::framework::discrete_values::DiscreteValues;
+use crate::systems::framework::framework_common::DiscreteStateIndex;
+use crate::systems::framework::leaf_context::LeafContext;
+use crate::systems::framework::leaf_system::LeafSystem;
+
+// A System with a single discrete state variable that is incremented by one at every periodic
+// update.
+#[leaf_system(constructor = "allocate")]
+pub struct DiscreteCounter<T: AtlasScalar> {}
+
+impl<T: AtlasScalar> DiscreteCounter<T> {
+    pub fn new(period_sec: f64, offset_sec: f64) -> Rc<RefCell<Self>> {
+        let discrete_counter = Self::allocate();
+
+        let index = discrete_counter.borrow_mut().declare_discrete_state(1);
+        discrete_counter
+            .borrow_mut()
+            .declare_periodic_discrete_update_event(
+                period_sec,
+                offset_sec,
+                Box::new(
+                    move |context: &LeafContext<T>, discrete_state: &mut DiscreteValues<T>| {
+                        let count = context.discrete_state().vector(&index)[0].clone();
+                        discrete_state.vector_mut(&index)[0] = count + T::one();
+                    },
+                ),
+            );
+
+        discrete_counter
+    }
+
+    pub fn count(&self, context: &LeafContext<T>) -> T {
+        context.discrete_state().vector(&DiscreteStateIndex::new(0))[0].clone()
+    }
+}
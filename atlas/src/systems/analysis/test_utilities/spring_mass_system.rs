@@ -0,0 +1,34 @@
+use atlas_derives::leaf_system;
+
+extern crate nalgebra as na;
+
+use crate::common::atlas_scalar::AtlasScalar;
+use crate::systems::framework::context::Context;
+use crate::systems::framework::continuous_state::ContinuousState;
+use crate::systems::framework::leaf_context::LeafContext;
+use crate::systems::framework::leaf_continuous_state::LeafContinuousState;
+
+// A unit mass attached to a spring of the given stiffness: qdot = v, vdot = -k q.
+#[leaf_system]
+#[continuous_state(q = 1, v = 1, derivatives = "calc_derivatives")]
+pub struct SpringMassSystem<T: AtlasScalar> {
+    stiffness: T,
+}
+
+impl<T: AtlasScalar> SpringMassSystem<T> {
+    pub fn set_position_and_velocity(&self, context: &mut LeafContext<T>, q: T, v: T) {
+        context
+            .continuous_state_vector_mut()
+            .set_from_vector(&na::DVector::<T>::from_vec(vec![q, v]));
+    }
+
+    fn calc_derivatives(&self, context: &LeafContext<T>, derivatives: &mut LeafContinuousState<T>) {
+        let state = context.continuous_state_vector();
+        let q = state[0].clone();
+        let v = state[1].clone();
+
+        let derivatives_vector = derivatives.vector_mut();
+        derivatives_vector.set_at_index(0, v);
+        derivatives_vector.set_at_index(1, T::zero() - self.stiffness.clone() * q);
+    }
+}
@@ -0,0 +1,50 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use atlas_derives::leaf_system;
+
+extern crate nalgebra as na;
+
+use crate::common::atlas_scalar::AtlasScalar;
+use crate::common::value::Value;
+use crate::systems::framework::context::Context;
+use crate::systems::framework::framework_common::AbstractStateIndex;
+use crate::systems::framework::leaf_context::LeafContext;
+use crate::systems::framework::leaf_state::LeafState;
+use crate::systems::framework::leaf_system::LeafSystem;
+use crate::systems::framework::state::State;
+
+// A System whose abstract state is the list of times at which its periodic unrestricted update
+// was handled.
+#[leaf_system(constructor = "allocate")]
+pub struct TimeRecorder<T: AtlasScalar> {}
+
+impl<T: AtlasScalar> TimeRecorder<T> {
+    pub fn new(period_sec: f64, offset_sec: f64) -> Rc<RefCell<Self>> {
+        let time_recorder = Self::allocate();
+
+        let index = time_recorder
+            .borrow_mut()
+            .declare_abstract_state(&Value::<Vec<T>>::new(vec![]));
+        time_recorder
+            .borrow_mut()
+            .declare_periodic_unrestricted_update_event(
+                period_sec,
+                offset_sec,
+                Box::new(move |context: &LeafContext<T>, state: &mut LeafState<T>| {
+                    state
+                        .abstract_state_mut()
+                        .get_value_mut::<Vec<T>>(&index)
+                        .push(context.time().clone());
+                }),
+            );
+
+        time_recorder
+    }
+
+    pub fn times(&self, context: &LeafContext<T>) -> Vec<T> {
+        context
+            .get_abstract_state::<Vec<T>>(&AbstractStateIndex::new(0))
+            .clone()
+    }
+}
@@ -1,136 +1,90 @@
-use std::any::Any;
 use std::cell::RefCell;
-use std::rc::{Rc, Weak};
+use std::rc::Rc;
 
-use atlas_derives::{AbstractSystem, LeafSystem, SystemBase};
+use atlas_derives::leaf_system;
 
 extern crate nalgebra as na;
 
+use crate::common::atlas_error::AtlasError;
 use crate::common::atlas_scalar::AtlasScalar;
-use crate::common::value::AbstractValue;
 use crate::systems::framework::basic_vector::BasicVector;
-use crate::systems::framework::cache_entry::CacheEntry;
 use crate::systems::framework::context::Context;
 use crate::systems::framework::continuous_state::ContinuousState;
-use crate::systems::framework::diagram::SystemWeakLink;
+use crate::systems::framework::diagram::SystemLink;
 use crate::systems::framework::framework_common::InputPortIndex;
+use crate::systems::framework::framework_common::NumericParameterIndex;
 use crate::systems::framework::framework_common::OutputPortIndex;
-use crate::systems::framework::framework_common::{
-    CacheIndex, SystemId, SystemParentServiceInterface,
-};
 use crate::systems::framework::input_port::InputPort;
-use crate::systems::framework::input_port_base::InputPortBase;
 use crate::systems::framework::leaf_context::LeafContext;
 use crate::systems::framework::leaf_continuous_state::LeafContinuousState;
 use crate::systems::framework::leaf_output_port::LeafOutputPort;
 use crate::systems::framework::leaf_state::LeafState;
 use crate::systems::framework::leaf_system::LeafSystem;
-use crate::systems::framework::model_values::ModelValues;
-use crate::systems::framework::output_port::OutputPort;
 use crate::systems::framework::output_port_base::OutputPortBase;
-use crate::systems::framework::state::State;
-use crate::systems::framework::system::{AbstractSystem, System};
-use crate::systems::framework::system_base::ContextSizes;
-use crate::systems::framework::system_base::SystemBase;
-
-#[derive(SystemBase, AbstractSystem, LeafSystem)]
+use crate::systems::framework::system_scalar_converter::ScalarConvertible;
+
+#[leaf_system(constructor = "allocate")]
+#[system(
+    time_derivatives = "do_calc_time_derivatives",
+    direct_feedthroughs = "direct_feedthroughs",
+    scalar_convertible
+)]
 pub struct PIDController<T: AtlasScalar> {
-    kp: na::DVector<T>,
-    ki: na::DVector<T>,
-    kd: na::DVector<T>,
+    #[default]
+    kp_index: NumericParameterIndex,
+    #[default]
+    ki_index: NumericParameterIndex,
+    #[default]
+    kd_index: NumericParameterIndex,
+    #[default]
     input_port_index_state: InputPortIndex,
+    #[default]
     input_port_index_desired_state: InputPortIndex,
+    #[default]
     output_port_index_control: OutputPortIndex,
     num_controlled_q: usize,
-    #[allow(clippy::box_collection)]
-    name: String,
-    system_weak_link: Option<SystemWeakLink<T>>,
-    input_ports: Vec<InputPort<T>>,
-    output_ports: Vec<LeafOutputPort<T>>,
-    cache_entries: Vec<CacheEntry>,
-    context_sizes: ContextSizes,
-    system_id: SystemId,
-    parent_service: Option<Weak<RefCell<dyn SystemParentServiceInterface>>>,
-    time_derivatives_cache_index: CacheIndex,
-    model_input_values: ModelValues,
-    model_continuous_state_vector: BasicVector<T>,
-    implicit_time_derivatives_residual_size: Option<usize>,
 }
 
 impl<T: AtlasScalar> PIDController<T> {
     pub fn new(kp: na::DVector<T>, ki: na::DVector<T>, kd: na::DVector<T>) -> Rc<RefCell<Self>> {
         let num_controlled_q = kp.len();
 
-        let pid_controller = Rc::new(RefCell::new(Self {
-            name: "pid_controller".to_string(),
-            kp,
-            ki,
-            kd,
-            input_port_index_state: InputPortIndex::default(),
-            input_port_index_desired_state: InputPortIndex::default(),
-            output_port_index_control: OutputPortIndex::default(),
-            num_controlled_q,
-            system_weak_link: None,
-            input_ports: vec![],
-            output_ports: vec![],
-            cache_entries: vec![],
-            context_sizes: ContextSizes::default(),
-            system_id: SystemId::new(0),
-            parent_service: None,
-            time_derivatives_cache_index: CacheIndex::new(0),
-            model_input_values: ModelValues::default(),
-            model_continuous_state_vector: BasicVector::<T>::zeros(0),
-            implicit_time_derivatives_residual_size: None,
-        }));
-
-        unsafe {
-            let pid_controller_weak = Rc::downgrade(&pid_controller);
-            let pid_controller_weak_ptr = Weak::into_raw(pid_controller_weak);
-            let system_weak = Weak::<RefCell<dyn System<T, CN = LeafContext<T>>>>::from_raw(
-                pid_controller_weak_ptr,
-            );
-            pid_controller.borrow_mut().system_weak_link =
-                Some(SystemWeakLink::LeafSystemWeakLink(system_weak));
+        let pid_controller = Self::allocate(num_controlled_q);
+
+        {
+            let mut pid_controller = pid_controller.borrow_mut();
+            pid_controller.declare_continuous_state(num_controlled_q, 0, 0);
+            pid_controller.kp_index =
+                pid_controller.declare_numeric_parameter(BasicVector::<T>::new(kp));
+            pid_controller.ki_index =
+                pid_controller.declare_numeric_parameter(BasicVector::<T>::new(ki));
+            pid_controller.kd_index =
+                pid_controller.declare_numeric_parameter(BasicVector::<T>::new(kd));
         }
 
-        pid_controller
-            .borrow_mut()
-            .declare_continuous_state(num_controlled_q, 0, 0);
-
-        let calc = {
-            let pid_controller_weak = Rc::downgrade(&pid_controller);
-            Box::new(
-                move |context: &LeafContext<T>, control: &mut BasicVector<T>| {
-                    let pid_controller = pid_controller_weak.upgrade().unwrap();
-                    pid_controller.borrow().calc_control(context, control);
-                },
-            )
-        };
-
-        pid_controller.borrow_mut().output_port_index_control = pid_controller
-            .borrow_mut()
-            .declare_vector_output_port("control".to_string(), num_controlled_q, calc)
-            .index()
-            .clone();
-
-        pid_controller.borrow_mut().input_port_index_state = pid_controller
-            .borrow_mut()
-            .declare_vector_input_port("estimated_state".to_string(), num_controlled_q * 2)
-            .index()
-            .clone();
-
-        pid_controller.borrow_mut().input_port_index_desired_state = pid_controller
-            .borrow_mut()
-            .declare_vector_input_port("desired_state".to_string(), num_controlled_q * 2)
-            .index()
-            .clone();
+        {
+            let mut pid_controller = pid_controller.borrow_mut();
+            let calc = pid_controller.make_vector_output_calc(Self::calc_control);
+            pid_controller.output_port_index_control = pid_controller
+                .declare_vector_output_port("control".to_string(), num_controlled_q, calc)
+                .index()
+                .clone();
+            pid_controller.input_port_index_state = pid_controller
+                .declare_vector_input_port("estimated_state".to_string(), num_controlled_q * 2)
+                .index()
+                .clone();
+            pid_controller.input_port_index_desired_state = pid_controller
+                .declare_vector_input_port("desired_state".to_string(), num_controlled_q * 2)
+                .index()
+                .clone();
+        }
 
         pid_controller
     }
 
     pub fn do_calc_time_derivatives(
-        &mut self,
-        context: &mut LeafContext<T>,
+        &self,
+        context: &LeafContext<T>,
         derivatives: &mut LeafContinuousState<T>,
     ) {
         let state = self
@@ -142,7 +96,14 @@ impl<T: AtlasScalar> PIDController<T> {
 
         let derivatives_vector = derivatives.vector_mut();
         let controlled_state_diff = &desired_state - &state;
-        derivatives_vector.set_from_vector(controlled_state_diff.value());
+        // The state declared in new holds num_controlled_q integrals, which calc_control reads as
+        // the generalized position, so only the position half of the error is integrated.
+        derivatives_vector.set_from_vector(
+            &controlled_state_diff
+                .value()
+                .rows(0, self.num_controlled_q)
+                .into_owned(),
+        );
     }
 
     pub fn calc_control(&self, context: &LeafContext<T>, control: &mut BasicVector<T>) {
@@ -158,12 +119,12 @@ impl<T: AtlasScalar> PIDController<T> {
         let integrated_controlled_state_diff = context.continuous_state().generalized_position();
 
         let control_vector = self
-            .kp
+            .kp(context)
             .component_mul(&controlled_state_diff.value().rows(0, self.num_controlled_q))
             + self
-                .ki
+                .ki(context)
                 .component_mul(&integrated_controlled_state_diff.value())
-            + self.kd.component_mul(
+            + self.kd(context).component_mul(
                 &controlled_state_diff
                     .value()
                     .rows(self.num_controlled_q, self.num_controlled_q),
@@ -176,97 +137,282 @@ impl<T: AtlasScalar> PIDController<T> {
         integrated_controlled_state_diff.set_from_vector(value)
     }
 
-    fn input_port_estimated_state(&self) -> &InputPort<T> {
-        &self.input_ports[&self.input_port_index_state]
-    }
-
-    fn input_port_desired_state(&self) -> &InputPort<T> {
-        &self.input_ports[&self.input_port_index_desired_state]
-    }
-
-    #[allow(dead_code)]
-    fn output_port_control(&self) -> &LeafOutputPort<T> {
-        &self.output_ports[&self.output_port_index_control]
-    }
-}
-
-impl<T: AtlasScalar> System<T> for PIDController<T> {
-    type CN = LeafContext<T>;
-
-    fn input_ports(&self) -> Vec<&InputPort<T>> {
-        self.input_ports.iter().collect()
-    }
-
-    fn input_ports_mut(&mut self) -> Vec<&mut InputPort<T>> {
-        self.input_ports.iter_mut().collect()
+    pub fn kp(&self, context: &LeafContext<T>) -> na::DVector<T> {
+        context
+            .get_numeric_parameter(&self.kp_index)
+            .copy_to_vector()
     }
 
-    fn input_port(&self, index: &InputPortIndex) -> &InputPort<T> {
-        &self.input_ports[index]
+    pub fn ki(&self, context: &LeafContext<T>) -> na::DVector<T> {
+        context
+            .get_numeric_parameter(&self.ki_index)
+            .copy_to_vector()
     }
 
-    fn input_port_mut(&mut self, index: &InputPortIndex) -> &mut InputPort<T> {
-        &mut self.input_ports[index]
+    pub fn kd(&self, context: &LeafContext<T>) -> na::DVector<T> {
+        context
+            .get_numeric_parameter(&self.kd_index)
+            .copy_to_vector()
     }
 
-    fn add_input_port(&mut self, input_port: InputPort<T>) {
-        self.input_ports.push(input_port);
+    pub fn set_gains(
+        &self,
+        context: &mut LeafContext<T>,
+        kp: &na::DVector<T>,
+        ki: &na::DVector<T>,
+        kd: &na::DVector<T>,
+    ) {
+        context
+            .get_mutable_numeric_parameter(&self.kp_index)
+            .set_from_vector(kp);
+        context
+            .get_mutable_numeric_parameter(&self.ki_index)
+            .set_from_vector(ki);
+        context
+            .get_mutable_numeric_parameter(&self.kd_index)
+            .set_from_vector(kd);
     }
 
-    fn output_ports(&self) -> Vec<&dyn OutputPort<T, CN = Self::CN>> {
-        self.output_ports
-            .iter()
-            .map(|p| p as &dyn OutputPort<T, CN = Self::CN>)
-            .collect()
+    pub fn input_port_estimated_state(&self) -> &InputPort<T> {
+        &self.input_ports[&self.input_port_index_state]
     }
 
-    fn output_ports_mut(&mut self) -> Vec<&mut dyn OutputPort<T, CN = Self::CN>> {
-        self.output_ports
-            .iter_mut()
-            .map(|p| p as &mut dyn OutputPort<T, CN = Self::CN>)
-            .collect()
+    pub fn input_port_desired_state(&self) -> &InputPort<T> {
+        &self.input_ports[&self.input_port_index_desired_state]
     }
 
-    fn output_port(&self, index: &OutputPortIndex) -> &dyn OutputPort<T, CN = Self::CN> {
-        &self.output_ports[index]
+    pub fn output_port_control(&self) -> &LeafOutputPort<T> {
+        &self.output_ports[&self.output_port_index_control]
     }
 
-    fn output_port_mut(
-        &mut self,
-        index: &OutputPortIndex,
-    ) -> &mut dyn OutputPort<T, CN = Self::CN> {
-        &mut self.output_ports[index]
+    // Both state inputs reach the control output directly through the kp and kd terms. The gains
+    // live in the context, so this holds even where they are zero.
+    fn direct_feedthroughs(&self) -> Vec<(InputPortIndex, OutputPortIndex)> {
+        vec![
+            (
+                self.input_port_index_state.clone(),
+                self.output_port_index_control.clone(),
+            ),
+            (
+                self.input_port_index_desired_state.clone(),
+                self.output_port_index_control.clone(),
+            ),
+        ]
     }
+}
 
-    fn system_weak_link(&self) -> SystemWeakLink<T> {
-        self.system_weak_link.clone().unwrap()
-    }
+impl<T: AtlasScalar> ScalarConvertible for PIDController<T> {
+    fn convert_scalar_type<U: AtlasScalar>(&self) -> Result<SystemLink<U>, AtlasError> {
+        let gains = |index: &NumericParameterIndex| {
+            self.model_parameters
+                .numeric_parameter(index)
+                .copy_to_vector()
+                .map(|gain| gain.cast_scalar::<U>())
+        };
+        let pid_controller = PIDController::<U>::new(
+            gains(&self.kp_index),
+            gains(&self.ki_index),
+            gains(&self.kd_index),
+        );
 
-    fn time_derivatives_cache_index(&self) -> &CacheIndex {
-        &self.time_derivatives_cache_index
+        Ok(pid_controller.into())
     }
+}
 
-    fn allocate_context(&self) -> Rc<RefCell<Self::CN>> {
-        LeafSystem::<T>::allocate_context(self)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::autodiff::AutoDiffXd;
+    use crate::systems::framework::system::System;
+    use crate::systems::framework::system_base::SystemBase;
+
+    #[test]
+    fn test_direct_feedthroughs() {
+        let gain = na::DVector::<f64>::from_vec(vec![0.0]);
+        let pid_controller = PIDController::<f64>::new(gain.clone(), gain.clone(), gain);
+        let pid_controller = pid_controller.borrow();
+        assert_eq!(
+            pid_controller.get_direct_feedthroughs(),
+            vec![
+                (InputPortIndex::new(0), OutputPortIndex::new(0)),
+                (InputPortIndex::new(1), OutputPortIndex::new(0)),
+            ]
+        );
+        assert!(System::has_direct_feedthrough(
+            &*pid_controller,
+            &InputPortIndex::new(1),
+            &OutputPortIndex::new(0)
+        ));
     }
 
-    fn do_allocate_input(&self, input_port: &InputPort<T>) -> Box<dyn AbstractValue> {
-        LeafSystem::<T>::do_allocate_input(self, input_port)
+    #[test]
+    fn test_time_derivatives() {
+        let gain = na::DVector::<f64>::from_vec(vec![1.0]);
+        let pid_controller = PIDController::<f64>::new(gain.clone(), gain.clone(), gain);
+        let pid_controller = pid_controller.borrow();
+        let context = pid_controller.create_default_context();
+        pid_controller.input_port_estimated_state().fix_value(
+            context.borrow_mut(),
+            BasicVector::<f64>::from_vec(vec![1.0, 2.0]),
+        );
+        pid_controller.input_port_desired_state().fix_value(
+            context.borrow_mut(),
+            BasicVector::<f64>::from_vec(vec![4.0, 7.0]),
+        );
+
+        // One integral per controlled q, of the position error.
+        let derivatives = pid_controller.eval_time_derivatives(&context.borrow());
+        assert_eq!(
+            derivatives.vector().copy_to_vector(),
+            na::DVector::<f64>::from_vec(vec![3.0])
+        );
     }
 
-    fn allocate_time_derivatives(&mut self) -> Box<<<Self::CN as Context<T>>::S as State<T>>::CS> {
-        LeafSystem::<T>::allocate_time_derivatives(self)
+    #[test]
+    fn test_gains_per_context() {
+        let kp = na::DVector::<f64>::from_vec(vec![1.0]);
+        let ki = na::DVector::<f64>::from_vec(vec![0.0]);
+        let kd = na::DVector::<f64>::from_vec(vec![0.0]);
+        let pid_controller = PIDController::<f64>::new(kp, ki.clone(), kd.clone());
+
+        let context1 = pid_controller.borrow().create_default_context();
+        let context2 = pid_controller.borrow().create_default_context();
+        pid_controller.borrow().set_gains(
+            &mut context2.borrow_mut(),
+            &na::DVector::<f64>::from_vec(vec![3.0]),
+            &ki,
+            &kd,
+        );
+        assert_eq!(pid_controller.borrow().kp(&context1.borrow())[0], 1.0);
+        assert_eq!(pid_controller.borrow().kp(&context2.borrow())[0], 3.0);
+
+        let mut controls = vec![];
+        for context in [&context1, &context2] {
+            let pid_controller = pid_controller.borrow();
+            pid_controller.input_port_estimated_state().fix_value(
+                context.borrow_mut(),
+                BasicVector::<f64>::from_vec(vec![0.0, 0.0]),
+            );
+            pid_controller.input_port_desired_state().fix_value(
+                context.borrow_mut(),
+                BasicVector::<f64>::from_vec(vec![2.0, 0.0]),
+            );
+            controls.push(
+                pid_controller
+                    .output_port_control()
+                    .eval::<BasicVector<f64>>(&mut context.borrow_mut()),
+            );
+        }
+        assert_eq!(controls[0], BasicVector::<f64>::from_vec(vec![2.0]));
+        assert_eq!(controls[1], BasicVector::<f64>::from_vec(vec![6.0]));
+
+        // Changing the gains or the integral invalidates the cached control.
+        pid_controller.borrow().set_gains(
+            &mut context1.borrow_mut(),
+            &na::DVector::<f64>::from_vec(vec![5.0]),
+            &na::DVector::<f64>::from_vec(vec![1.0]),
+            &kd,
+        );
+        let eval_control = |context: &Rc<RefCell<LeafContext<f64>>>| {
+            pid_controller
+                .borrow()
+                .output_port_control()
+                .eval::<BasicVector<f64>>(&mut context.borrow_mut())
+        };
+        assert_eq!(
+            eval_control(&context1),
+            BasicVector::<f64>::from_vec(vec![10.0])
+        );
+        pid_controller.borrow().set_integral_value(
+            &mut context1.borrow_mut(),
+            &na::DVector::from_vec(vec![1.0]),
+        );
+        assert_eq!(
+            eval_control(&context1),
+            BasicVector::<f64>::from_vec(vec![11.0])
+        );
+
+        // Restoring the defaults undoes the change.
+        pid_controller
+            .borrow()
+            .set_default_context(&mut context2.borrow_mut());
+        assert_eq!(pid_controller.borrow().kp(&context2.borrow())[0], 1.0);
     }
 
-    fn set_default_state(&self, context: &mut Self::CN) {
-        LeafSystem::<T>::set_default_state(self, context)
+    #[test]
+    fn test_to_scalar_type() {
+        let pid_controller = PIDController::<f64>::new(
+            na::DVector::from_vec(vec![2.0]),
+            na::DVector::from_vec(vec![0.5]),
+            na::DVector::from_vec(vec![1.0]),
+        );
+        pid_controller
+            .borrow_mut()
+            .set_name("controller".to_string());
+        let system_link = SystemLink::from(pid_controller)
+            .try_to_scalar_type::<f32>()
+            .unwrap();
+        assert_eq!(*system_link.name(), "controller");
+
+        let SystemLink::LeafSystemLink(system) = system_link else {
+            panic!("a PIDController converts to a leaf system");
+        };
+        let system = system.borrow();
+        let pid_controller = system
+            .as_any()
+            .downcast_ref::<PIDController<f32>>()
+            .unwrap();
+        let context = pid_controller.create_default_context();
+        assert_eq!(pid_controller.kp(&context.borrow())[0], 2.0f32);
+        assert_eq!(pid_controller.ki(&context.borrow())[0], 0.5f32);
+        pid_controller.input_port_estimated_state().fix_value(
+            context.borrow_mut(),
+            BasicVector::<f32>::from_vec(vec![1.0, 0.0]),
+        );
+        pid_controller.input_port_desired_state().fix_value(
+            context.borrow_mut(),
+            BasicVector::<f32>::from_vec(vec![2.0, 1.0]),
+        );
+        let control = pid_controller
+            .output_port_control()
+            .eval::<BasicVector<f32>>(&mut context.borrow_mut());
+        assert_eq!(control, BasicVector::<f32>::from_vec(vec![3.0]));
     }
 
-    fn do_calc_time_derivatives(
-        &mut self,
-        context: &mut Self::CN,
-        derivatives: &mut <<Self::CN as Context<T>>::S as State<T>>::CS,
-    ) {
-        PIDController::<T>::do_calc_time_derivatives(self, context, derivatives)
+    #[test]
+    fn test_to_autodiff() {
+        let pid_controller = PIDController::<f64>::new(
+            na::DVector::from_vec(vec![2.0]),
+            na::DVector::from_vec(vec![0.5]),
+            na::DVector::from_vec(vec![1.0]),
+        );
+        let SystemLink::LeafSystemLink(system) = pid_controller.borrow().to_autodiff().unwrap()
+        else {
+            panic!("a PIDController converts to a leaf system");
+        };
+        let system = system.borrow();
+        let pid_controller = system
+            .as_any()
+            .downcast_ref::<PIDController<AutoDiffXd>>()
+            .unwrap();
+
+        // Differentiate the control with respect to the estimated state.
+        let context = pid_controller.create_default_context();
+        let estimated_state = AutoDiffXd::variables(&na::DVector::from_vec(vec![1.0, 0.0]));
+        pid_controller
+            .input_port_estimated_state()
+            .fix_value(context.borrow_mut(), BasicVector::new(estimated_state));
+        pid_controller.input_port_desired_state().fix_value(
+            context.borrow_mut(),
+            BasicVector::from_vec(vec![AutoDiffXd::constant(2.0), AutoDiffXd::constant(1.0)]),
+        );
+        let control = pid_controller
+            .output_port_control()
+            .eval::<BasicVector<AutoDiffXd>>(&mut context.borrow_mut());
+        assert_eq!(control[0].value(), 3.0);
+        assert_eq!(
+            *control[0].derivatives(),
+            na::DVector::from_vec(vec![-2.0, -1.0])
+        );
     }
 }
@@ -1,15 +1,19 @@
+pub mod abstract_values;
 pub mod basic_vector;
 pub mod cache;
 pub mod cache_entry;
 pub mod context;
 pub mod context_base;
 pub mod continuous_state;
+pub mod dependency_tracker;
 pub mod diagram;
 pub mod diagram_builder;
 pub mod diagram_context;
 pub mod diagram_continuous_state;
 pub mod diagram_output_port;
 pub mod diagram_state;
+pub mod discrete_values;
+pub mod event;
 pub mod fixed_input_port_value;
 pub mod framework_common;
 pub mod input_port;
@@ -22,10 +26,13 @@ pub mod leaf_system;
 pub mod model_values;
 pub mod output_port;
 pub mod output_port_base;
+pub mod parameters;
 pub mod port_base;
 pub mod state;
 pub mod subvector;
+pub mod supervector;
 pub mod system;
 pub mod system_base;
+pub mod system_scalar_converter;
 pub mod value_producer;
 pub mod vector_base;
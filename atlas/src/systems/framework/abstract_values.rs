@@ -0,0 +1,131 @@
+use std::fmt::Debug;
+
+use crate::common::value::AbstractValue;
+use crate::systems::framework::framework_common::AbstractStateIndex;
+
+// The abstract state of a System, made of values of arbitrary types. The values are either owned
+// or, for a Diagram, pointers to the values of its subsystems, in subsystem order.
+#[derive(Default)]
+pub struct AbstractValues {
+    data: Vec<*mut dyn AbstractValue>,
+    owned_data: Vec<Box<dyn AbstractValue>>,
+}
+
+impl AbstractValues {
+    pub fn new(mut owned_data: Vec<Box<dyn AbstractValue>>) -> Self {
+        let data = owned_data
+            .iter_mut()
+            .map(|value| value.as_mut() as *mut dyn AbstractValue)
+            .collect();
+        AbstractValues { data, owned_data }
+    }
+
+    // The values must outlive the AbstractValues.
+    pub fn from_views(data: Vec<*mut dyn AbstractValue>) -> Self {
+        AbstractValues {
+            data,
+            owned_data: vec![],
+        }
+    }
+
+    pub fn add_value(&mut self, mut value: Box<dyn AbstractValue>) -> AbstractStateIndex {
+        assert_eq!(
+            self.data.len(),
+            self.owned_data.len(),
+            "AbstractValues::add_value: cannot add a value to a view"
+        );
+        self.data.push(value.as_mut() as *mut dyn AbstractValue);
+        self.owned_data.push(value);
+        AbstractStateIndex::new(self.data.len() - 1)
+    }
+
+    // Appends pointers to values that must outlive the AbstractValues.
+    pub fn append_views(&mut self, data: Vec<*mut dyn AbstractValue>) {
+        self.data.extend(data);
+    }
+
+    pub fn size(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn value(&self, index: &AbstractStateIndex) -> &(dyn AbstractValue + 'static) {
+        unsafe { &*self.data[index.value()] }
+    }
+
+    pub fn value_mut(&mut self, index: &AbstractStateIndex) -> &mut (dyn AbstractValue + 'static) {
+        unsafe { &mut *self.data[index.value()] }
+    }
+
+    // Panics if the value at index is not a ValueType.
+    pub fn get_value<ValueType: 'static + Clone + Debug>(
+        &self,
+        index: &AbstractStateIndex,
+    ) -> &ValueType {
+        self.value(index).get_value::<ValueType>()
+    }
+
+    pub fn get_value_mut<ValueType: 'static + Clone + Debug>(
+        &mut self,
+        index: &AbstractStateIndex,
+    ) -> &mut ValueType {
+        self.value_mut(index).get_value_mut::<ValueType>()
+    }
+
+    // Copies the values of other, which must hold values of the same types.
+    pub fn set_from(&mut self, other: &AbstractValues) {
+        assert_eq!(self.size(), other.size());
+        for i in 0..self.size() {
+            let index = AbstractStateIndex::new(i);
+            self.value_mut(&index).set_from(other.value(&index));
+        }
+    }
+
+    // Pointers to every value, for a Diagram to aggregate the abstract state of its subsystems.
+    pub fn value_ptrs(&mut self) -> Vec<*mut dyn AbstractValue> {
+        self.data.clone()
+    }
+}
+
+// Cloning copies the values into newly owned ones, even when self views other values.
+impl Clone for AbstractValues {
+    fn clone(&self) -> Self {
+        AbstractValues::new(
+            (0..self.size())
+                .map(|i| self.value(&AbstractStateIndex::new(i)).clone_box())
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::value::Value;
+
+    #[test]
+    fn test_values() {
+        let mut abstract_values = AbstractValues::default();
+        let index0 = abstract_values.add_value(Box::new(Value::<i64>::new(1)));
+        let index1 = abstract_values.add_value(Box::new(Value::<String>::new("a".to_string())));
+        assert_eq!(abstract_values.size(), 2);
+
+        *abstract_values.get_value_mut::<i64>(&index0) += 1;
+        assert_eq!(*abstract_values.get_value::<i64>(&index0), 2);
+        assert_eq!(abstract_values.get_value::<String>(&index1), "a");
+    }
+
+    #[test]
+    fn test_views_and_clone() {
+        let mut abstract_values = AbstractValues::default();
+        let index = abstract_values.add_value(Box::new(Value::<i64>::new(1)));
+
+        let mut view = AbstractValues::from_views(abstract_values.value_ptrs());
+        let mut copy = view.clone();
+        view.value_mut(&index).set_value(2_i64);
+        assert_eq!(*abstract_values.get_value::<i64>(&index), 2);
+
+        copy.value_mut(&index).set_value(3_i64);
+        view.set_from(&copy);
+        assert_eq!(*abstract_values.get_value::<i64>(&index), 3);
+    }
+}
@@ -34,6 +34,10 @@ impl<T: AtlasScalar> BasicVector<T> {
         &mut self.values
     }
 
+    pub fn cast_scalar<U: AtlasScalar>(&self) -> BasicVector<U> {
+        BasicVector::<U>::new(self.values.map(|value| value.cast_scalar::<U>()))
+    }
+
     pub fn set_value(&mut self, value: &na::DVector<T>) {
         self.values = (*value).clone();
     }
@@ -330,4 +334,22 @@ mod tests {
             BasicVector::<f64>::new(na::DVector::<f64>::from_vec(vec![-2.0, -2.0]))
         );
     }
+
+    #[test]
+    fn test_f32() {
+        let mut a = BasicVector::<f32>::from_vec(vec![1.0, 2.0]);
+        let b = BasicVector::<f32>::from_vec(vec![3.0, 4.0]);
+
+        assert_eq!(&a + &b, BasicVector::<f32>::from_vec(vec![4.0, 6.0]));
+        a -= &b;
+        assert_eq!(a, BasicVector::<f32>::from_vec(vec![-2.0, -2.0]));
+    }
+
+    #[test]
+    fn test_cast_scalar() {
+        let a = BasicVector::<f64>::from_vec(vec![0.5, -2.0]);
+        let b = a.cast_scalar::<f32>();
+        assert_eq!(b, BasicVector::<f32>::from_vec(vec![0.5, -2.0]));
+        assert_eq!(b.cast_scalar::<f64>(), a);
+    }
 }
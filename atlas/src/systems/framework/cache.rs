@@ -73,6 +73,12 @@ impl Cache {
         self.cache_mut_entry_value(&cache_index)
     }
 
+    pub fn mark_all_out_of_date(&mut self) {
+        for cache_entry_value in self.store.iter_mut() {
+            cache_entry_value.mark_out_of_date();
+        }
+    }
+
     pub fn cache_entry_value(&self, cache_index: &CacheIndex) -> &CacheEntryValue {
         &self.store[cache_index]
     }
@@ -1,18 +1,33 @@
 use crate::common::value::AbstractValue;
 use crate::systems::framework::context_base::ContextBase;
-use crate::systems::framework::framework_common::CacheIndex;
+use crate::systems::framework::framework_common::{CacheIndex, DependencyTicket};
 use crate::systems::framework::value_producer::ValueProducer;
 
+// The cache entries of a System. They are boxed so that they keep their addresses, which the
+// LeafOutputPorts hold, as more are declared.
+#[allow(clippy::vec_box)]
+pub type CacheEntries = Vec<Box<CacheEntry>>;
+
 pub struct CacheEntry {
     cache_index: CacheIndex,
     value_producer: ValueProducer,
+    prerequisites_of_calc: Vec<DependencyTicket>,
 }
 
 impl CacheEntry {
-    pub fn new(cache_index: CacheIndex, value_producer: ValueProducer) -> Self {
+    pub fn new(
+        cache_index: CacheIndex,
+        value_producer: ValueProducer,
+        prerequisites_of_calc: Vec<DependencyTicket>,
+    ) -> Self {
+        assert!(
+            !prerequisites_of_calc.is_empty(),
+            "CacheEntry: a cache entry must have at least one prerequisite"
+        );
         CacheEntry {
             cache_index,
             value_producer,
+            prerequisites_of_calc,
         }
     }
 
@@ -69,10 +84,21 @@ impl CacheEntry {
                 .cache_mut_entry_value(&self.cache_index)
                 .abstract_value_mut()
                 .set_from(value.as_ref());
+            cache
+                .cache_mut_entry_value(&self.cache_index)
+                .mark_up_to_date();
         }
     }
 
     pub fn cache_index(&self) -> &CacheIndex {
         &self.cache_index
     }
+
+    pub fn ticket(&self) -> DependencyTicket {
+        DependencyTicket::CacheEntry(self.cache_index.clone())
+    }
+
+    pub fn prerequisites(&self) -> &Vec<DependencyTicket> {
+        &self.prerequisites_of_calc
+    }
 }
@@ -1,5 +1,13 @@
+use std::fmt::Debug;
+
 use crate::common::atlas_scalar::AtlasScalar;
+use crate::systems::framework::abstract_values::AbstractValues;
 use crate::systems::framework::context_base::ContextBase;
+use crate::systems::framework::discrete_values::DiscreteValues;
+use crate::systems::framework::framework_common::{
+    AbstractParameterIndex, AbstractStateIndex, NumericParameterIndex,
+};
+use crate::systems::framework::parameters::Parameters;
 use crate::systems::framework::state::State;
 use crate::systems::framework::vector_base::VectorBase;
 
@@ -7,8 +15,49 @@ pub trait Context<T: AtlasScalar>: ContextBase {
     type S: State<T>;
 
     fn time(&self) -> &T;
+    fn set_time(&mut self, time: T);
     fn state(&self) -> &Self::S;
     fn state_mut(&mut self) -> &mut Self::S;
+    fn parameters(&self) -> &Parameters<T>;
+    fn parameters_mut(&mut self) -> &mut Parameters<T>;
+    fn num_numeric_parameter_groups(&self) -> usize {
+        self.parameters().num_numeric_parameter_groups()
+    }
+    fn num_abstract_parameters(&self) -> usize {
+        self.parameters().num_abstract_parameters()
+    }
+    fn get_numeric_parameter(
+        &self,
+        index: &NumericParameterIndex,
+    ) -> &dyn VectorBase<T, Output = T> {
+        self.parameters().numeric_parameter(index)
+    }
+    fn get_mutable_numeric_parameter(
+        &mut self,
+        index: &NumericParameterIndex,
+    ) -> &mut dyn VectorBase<T, Output = T> {
+        self.parameters_mut().numeric_parameter_mut(index)
+    }
+    // Panics if the parameter at index is not a ValueType.
+    fn get_abstract_parameter<ValueType: 'static + Clone + Debug>(
+        &self,
+        index: &AbstractParameterIndex,
+    ) -> &ValueType
+    where
+        Self: Sized,
+    {
+        self.parameters().abstract_parameter::<ValueType>(index)
+    }
+    fn get_mutable_abstract_parameter<ValueType: 'static + Clone + Debug>(
+        &mut self,
+        index: &AbstractParameterIndex,
+    ) -> &mut ValueType
+    where
+        Self: Sized,
+    {
+        self.parameters_mut()
+            .abstract_parameter_mut::<ValueType>(index)
+    }
     fn init_continuous_state(&mut self, continuous_state: Box<<Self::S as State<T>>::CS>);
     fn num_continuous_states(&self) -> usize;
     fn continuous_state(&self) -> &<Self::S as State<T>>::CS;
@@ -19,6 +68,43 @@ pub trait Context<T: AtlasScalar>: ContextBase {
     }
     fn continuous_state_vector(&self) -> &dyn VectorBase<T, Output = T>;
     fn continuous_state_vector_mut(&mut self) -> &mut dyn VectorBase<T, Output = T>;
+    fn discrete_state(&self) -> &DiscreteValues<T> {
+        self.state().discrete_state()
+    }
+    fn discrete_state_mut(&mut self) -> &mut DiscreteValues<T> {
+        self.state_mut().discrete_state_mut()
+    }
+    fn num_discrete_state_groups(&self) -> usize {
+        self.discrete_state().num_groups()
+    }
+    fn abstract_state(&self) -> &AbstractValues {
+        self.state().abstract_state()
+    }
+    fn abstract_state_mut(&mut self) -> &mut AbstractValues {
+        self.state_mut().abstract_state_mut()
+    }
+    fn num_abstract_states(&self) -> usize {
+        self.abstract_state().size()
+    }
+    // Panics if the abstract state at index is not a ValueType.
+    fn get_abstract_state<ValueType: 'static + Clone + Debug>(
+        &self,
+        index: &AbstractStateIndex,
+    ) -> &ValueType
+    where
+        Self: Sized,
+    {
+        self.abstract_state().get_value::<ValueType>(index)
+    }
+    fn get_mutable_abstract_state<ValueType: 'static + Clone + Debug>(
+        &mut self,
+        index: &AbstractStateIndex,
+    ) -> &mut ValueType
+    where
+        Self: Sized,
+    {
+        self.abstract_state_mut().get_value_mut::<ValueType>(index)
+    }
     fn as_base(&self) -> &dyn ContextBase;
     fn as_mutable_base(&mut self) -> &mut dyn ContextBase;
 }
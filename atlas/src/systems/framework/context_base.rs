@@ -4,20 +4,40 @@ use std::rc::Rc;
 
 use crate::common::value::AbstractValue;
 use crate::systems::framework::cache::Cache;
+use crate::systems::framework::dependency_tracker::DependencyGraph;
 use crate::systems::framework::fixed_input_port_value::FixedInputPortValue;
-use crate::systems::framework::framework_common::{InputPortIndex, SystemId};
+use crate::systems::framework::framework_common::{DependencyTicket, InputPortIndex, SystemId};
 
 pub trait ContextBase: Any {
     fn as_any(&self) -> &dyn Any;
     fn as_any_mut(&mut self) -> &mut dyn Any;
     fn set_system_id(&mut self, system_id: SystemId);
     fn system_id(&self) -> &SystemId;
+    // The name of the System that created this context, for error messages.
+    fn set_system_name(&mut self, system_name: String);
+    fn system_name(&self) -> &str;
     fn set_parent(&mut self, parent: Rc<RefCell<dyn ContextBase>>) {
         *self.parent_base_mut() = Some(parent);
     }
     fn parent_base(&self) -> &Option<Rc<RefCell<dyn ContextBase>>>;
     fn parent_base_mut(&mut self) -> &mut Option<Rc<RefCell<dyn ContextBase>>>;
-    fn cache(&self) -> &RefCell<Cache>;
+    fn graph(&self) -> &Rc<DependencyGraph>;
+    fn cache(&self) -> &RefCell<Cache> {
+        self.graph().cache()
+    }
+    fn mark_all_cache_entries_out_of_date(&self) {
+        self.cache().borrow_mut().mark_all_out_of_date();
+    }
+    // Marks out of date every cache entry, in this context or another, that depends on the value
+    // for ticket.
+    fn note_value_change(&self, ticket: &DependencyTicket) {
+        self.graph().note_value_change(ticket);
+    }
+    fn note_all_state_changed(&self) {
+        self.note_value_change(&DependencyTicket::ContinuousState);
+        self.note_value_change(&DependencyTicket::DiscreteState);
+        self.note_value_change(&DependencyTicket::AbstractState);
+    }
 
     fn input_port_values(&mut self) -> &mut Vec<Option<FixedInputPortValue>>;
     fn num_input_ports(&self) -> usize;
@@ -26,6 +46,11 @@ pub trait ContextBase: Any {
     fn add_input_port(&mut self, input_port_index: &InputPortIndex) {
         assert!(*input_port_index == self.num_input_ports());
         self.input_port_values().push(None);
+
+        let graph = self.graph().clone();
+        let ticket = DependencyTicket::InputPort(input_port_index.clone());
+        graph.add_tracker(ticket.clone());
+        graph.subscribe_to_prerequisite(&DependencyTicket::AllInputPorts, &graph, &ticket);
     }
     fn fix_input_port(
         &mut self,
@@ -0,0 +1,185 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::{Rc, Weak};
+
+use crate::systems::framework::cache::Cache;
+use crate::systems::framework::framework_common::{CacheIndex, DependencyTicket};
+
+// A tracker in the graph of another context, or of the same one.
+#[derive(Clone)]
+struct Subscriber {
+    graph: Weak<DependencyGraph>,
+    ticket: DependencyTicket,
+}
+
+// Tracks one value of a context and the trackers that depend on it. A tracker for a cache entry
+// marks the entry's value out of date when notified of a change.
+#[derive(Default)]
+pub struct DependencyTracker {
+    cache_index: Option<CacheIndex>,
+    subscribers: Vec<Subscriber>,
+}
+
+impl DependencyTracker {
+    pub fn num_subscribers(&self) -> usize {
+        self.subscribers.len()
+    }
+}
+
+// The trackers of a context, together with the cache whose entries they invalidate. Subscriptions
+// may cross contexts, e.g. from an input port of a subsystem to the output port it is connected
+// to, so the graph is shared through an Rc.
+#[derive(Default)]
+pub struct DependencyGraph {
+    trackers: RefCell<HashMap<DependencyTicket, DependencyTracker>>,
+    cache: RefCell<Cache>,
+}
+
+impl DependencyGraph {
+    pub fn cache(&self) -> &RefCell<Cache> {
+        &self.cache
+    }
+
+    pub fn add_tracker(&self, ticket: DependencyTicket) {
+        let previous = self
+            .trackers
+            .borrow_mut()
+            .insert(ticket.clone(), DependencyTracker::default());
+        assert!(
+            previous.is_none(),
+            "DependencyGraph: a tracker for {:?} already exists",
+            ticket
+        );
+    }
+
+    pub fn add_cache_entry_tracker(&self, cache_index: CacheIndex) {
+        let ticket = DependencyTicket::CacheEntry(cache_index.clone());
+        self.add_tracker(ticket.clone());
+        self.trackers
+            .borrow_mut()
+            .get_mut(&ticket)
+            .unwrap()
+            .cache_index = Some(cache_index);
+    }
+
+    pub fn has_tracker(&self, ticket: &DependencyTicket) -> bool {
+        self.trackers.borrow().contains_key(ticket)
+    }
+
+    pub fn num_subscribers(&self, ticket: &DependencyTicket) -> usize {
+        self.trackers.borrow()[ticket].num_subscribers()
+    }
+
+    // Makes the tracker for ticket depend on the tracker for prerequisite in prerequisite_graph,
+    // which may be this graph.
+    pub fn subscribe_to_prerequisite(
+        self: &Rc<Self>,
+        ticket: &DependencyTicket,
+        prerequisite_graph: &DependencyGraph,
+        prerequisite: &DependencyTicket,
+    ) {
+        assert!(
+            self.has_tracker(ticket),
+            "DependencyGraph: there is no tracker for {:?}",
+            ticket
+        );
+        let mut trackers = prerequisite_graph.trackers.borrow_mut();
+        let tracker = trackers.get_mut(prerequisite).unwrap_or_else(|| {
+            panic!(
+                "DependencyGraph: there is no tracker for the prerequisite {:?}",
+                prerequisite
+            )
+        });
+        tracker.subscribers.push(Subscriber {
+            graph: Rc::downgrade(self),
+            ticket: ticket.clone(),
+        });
+    }
+
+    // Marks out of date the cache entries that depend, directly or not, on the value for ticket.
+    pub fn note_value_change(&self, ticket: &DependencyTicket) {
+        self.notify(ticket, &mut HashSet::new());
+    }
+
+    fn notify(
+        &self,
+        ticket: &DependencyTicket,
+        notified: &mut HashSet<(*const DependencyGraph, DependencyTicket)>,
+    ) {
+        if !notified.insert((self as *const DependencyGraph, ticket.clone())) {
+            return;
+        }
+
+        let subscribers = {
+            let trackers = self.trackers.borrow();
+            let Some(tracker) = trackers.get(ticket) else {
+                return;
+            };
+            if let Some(cache_index) = &tracker.cache_index {
+                self.cache
+                    .borrow_mut()
+                    .cache_mut_entry_value(cache_index)
+                    .mark_out_of_date();
+            }
+            tracker.subscribers.clone()
+        };
+
+        // Subscribers whose context has been dropped are skipped.
+        for subscriber in subscribers {
+            if let Some(graph) = subscriber.graph.upgrade() {
+                graph.notify(&subscriber.ticket, notified);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_note_value_change() {
+        let graph = Rc::new(DependencyGraph::default());
+        let other_graph = Rc::new(DependencyGraph::default());
+        for index in 0..2 {
+            graph
+                .cache()
+                .borrow_mut()
+                .create_new_cache_entry_value(CacheIndex::new(index));
+            graph.add_cache_entry_tracker(CacheIndex::new(index));
+        }
+        other_graph
+            .cache()
+            .borrow_mut()
+            .create_new_cache_entry_value(CacheIndex::new(0));
+        other_graph.add_cache_entry_tracker(CacheIndex::new(0));
+        graph.add_tracker(DependencyTicket::Time);
+
+        // time -> cache entry 0 -> cache entry 0 of the other graph, and cache entry 1 alone.
+        let entry0 = DependencyTicket::CacheEntry(CacheIndex::new(0));
+        graph.subscribe_to_prerequisite(&entry0, &graph, &DependencyTicket::Time);
+        other_graph.subscribe_to_prerequisite(&entry0, &graph, &entry0);
+        assert_eq!(graph.num_subscribers(&DependencyTicket::Time), 1);
+
+        for cache in [graph.cache(), other_graph.cache()] {
+            let mut cache = cache.borrow_mut();
+            for index in 0..cache.cache_size() {
+                cache
+                    .cache_mut_entry_value(&CacheIndex::new(index))
+                    .mark_up_to_date();
+            }
+        }
+
+        graph.note_value_change(&DependencyTicket::Time);
+        let needs_recomputation = |graph: &DependencyGraph, index: usize| {
+            graph
+                .cache()
+                .borrow()
+                .cache_entry_value(&CacheIndex::new(index))
+                .needs_recomputation()
+        };
+        assert!(needs_recomputation(&graph, 0));
+        assert!(!needs_recomputation(&graph, 1));
+        assert!(needs_recomputation(&other_graph, 0));
+    }
+}
@@ -1,4 +1,4 @@
-use std::any::Any;
+use std::any::{Any, TypeId};
 use std::cell::{Ref, RefCell, RefMut};
 use std::cmp::{Eq, PartialEq};
 use std::collections::HashMap;
@@ -8,27 +8,37 @@ use std::rc::{Rc, Weak};
 
 use atlas_derives::{AbstractSystem, SystemBase};
 
+use crate::common::atlas_error::AtlasError;
 use crate::common::atlas_scalar::AtlasScalar;
 use crate::common::value::AbstractValue;
-use crate::systems::framework::cache_entry::CacheEntry;
+use crate::systems::framework::cache_entry::CacheEntries;
 use crate::systems::framework::context::Context;
 use crate::systems::framework::context_base::ContextBase;
+use crate::systems::framework::continuous_state::ContinuousState;
 use crate::systems::framework::diagram_context::{ContextLink, DiagramContext, DiagramContextExt};
+use crate::systems::framework::diagram_continuous_state::{
+    DiagramContinuousState, OwnedContinuousState,
+};
 use crate::systems::framework::diagram_output_port::DiagramOutputPort;
+use crate::systems::framework::diagram_state::StatePtr;
+use crate::systems::framework::discrete_values::DiscreteValues;
+use crate::systems::framework::event::PeriodicEventData;
 use crate::systems::framework::framework_common::{
-    CacheIndex, InputPortIndex, OutputPortIndex, SubsystemIndex, SystemId,
+    DependencyTicket, InputPortIndex, OutputPortIndex, SubsystemIndex, SystemId,
     SystemParentServiceInterface,
 };
 use crate::systems::framework::input_port::InputPort;
 use crate::systems::framework::input_port_base::InputPortBase;
 use crate::systems::framework::leaf_context::LeafContext;
 use crate::systems::framework::output_port::OutputPort;
-use crate::systems::framework::output_port_base::OutputPortBase;
 use crate::systems::framework::port_base::PortBase;
 use crate::systems::framework::state::State;
-use crate::systems::framework::system::{AbstractSystem, System};
+use crate::systems::framework::system::System;
 use crate::systems::framework::system_base::ContextSizes;
 use crate::systems::framework::system_base::SystemBase;
+use crate::systems::framework::system_scalar_converter::{
+    convert_to_scalar_type, unbox_converted_system, ScalarConvertible,
+};
 
 #[derive(Clone)]
 pub enum SystemLink<T: AtlasScalar> {
@@ -76,7 +86,7 @@ impl<T: AtlasScalar> Hash for SystemLink<T> {
 }
 
 impl<T: AtlasScalar> SystemLink<T> {
-    pub fn input_port(&self, input_port_index: InputPortIndex) -> Ref<InputPort<T>> {
+    pub fn input_port(&self, input_port_index: InputPortIndex) -> Ref<'_, InputPort<T>> {
         match self {
             SystemLink::LeafSystemLink(system) => {
                 Ref::map(system.borrow(), |s| s.input_port(&input_port_index))
@@ -87,7 +97,7 @@ impl<T: AtlasScalar> SystemLink<T> {
         }
     }
 
-    pub fn input_port_mut(&mut self, input_port_index: InputPortIndex) -> RefMut<InputPort<T>> {
+    pub fn input_port_mut(&mut self, input_port_index: InputPortIndex) -> RefMut<'_, InputPort<T>> {
         match self {
             SystemLink::LeafSystemLink(system) => {
                 RefMut::map(system.borrow_mut(), |s| s.input_port_mut(&input_port_index))
@@ -122,7 +132,7 @@ impl<T: AtlasScalar> SystemLink<T> {
         }
     }
 
-    pub fn context_sizes(&self) -> Ref<ContextSizes> {
+    pub fn context_sizes(&self) -> Ref<'_, ContextSizes> {
         match self {
             SystemLink::LeafSystemLink(system) => Ref::map(system.borrow(), |s| s.context_sizes()),
             SystemLink::DiagramLink(system) => Ref::map(system.borrow(), |s| s.context_sizes()),
@@ -142,7 +152,7 @@ impl<T: AtlasScalar> SystemLink<T> {
 }
 
 impl<T: AtlasScalar> SystemLink<T> {
-    pub fn name(&self) -> Ref<String> {
+    pub fn name(&self) -> Ref<'_, String> {
         match self {
             SystemLink::LeafSystemLink(system) => Ref::map(system.borrow(), |s| s.name()),
             SystemLink::DiagramLink(system) => Ref::map(system.borrow(), |s| s.name()),
@@ -156,10 +166,27 @@ impl<T: AtlasScalar> SystemLink<T> {
         }
     }
 
+    pub fn system_weak_link(&self) -> SystemWeakLink<T> {
+        match self {
+            SystemLink::LeafSystemLink(system) => system.borrow().system_weak_link(),
+            SystemLink::DiagramLink(system) => system.borrow().system_weak_link(),
+        }
+    }
+
+    // Rebuilds the System with the scalar type U, under the same name.
+    pub fn try_to_scalar_type<U: AtlasScalar>(&self) -> Result<SystemLink<U>, AtlasError> {
+        let scalar_type = TypeId::of::<U>();
+        let converted = match self {
+            SystemLink::LeafSystemLink(system) => system.borrow().do_to_scalar_type(scalar_type),
+            SystemLink::DiagramLink(system) => system.borrow().do_to_scalar_type(scalar_type),
+        };
+        unbox_converted_system(converted, &self.name())
+    }
+
     pub fn output_port(
         &self,
         output_port_index: OutputPortIndex,
-    ) -> Ref<dyn OutputPort<T, CN = LeafContext<T>>> {
+    ) -> Ref<'_, dyn OutputPort<T, CN = LeafContext<T>>> {
         match self {
             SystemLink::LeafSystemLink(system) => {
                 Ref::map(system.borrow(), |s| s.output_port(&output_port_index))
@@ -174,7 +201,7 @@ impl<T: AtlasScalar> SystemLink<T> {
     pub fn output_port_mut(
         &mut self,
         output_port_index: OutputPortIndex,
-    ) -> RefMut<dyn OutputPort<T, CN = LeafContext<T>>> {
+    ) -> RefMut<'_, dyn OutputPort<T, CN = LeafContext<T>>> {
         match self {
             SystemLink::LeafSystemLink(system) => RefMut::map(system.borrow_mut(), |s| {
                 s.output_port_mut(&output_port_index)
@@ -190,19 +217,19 @@ impl<T: AtlasScalar> SystemLink<T> {
 pub trait SystemLinkExt<T: AtlasScalar> {
     type CN: Context<T>;
 
-    fn input_port(&self, input_port_index: InputPortIndex) -> Ref<InputPort<T>>;
+    fn input_port(&self, input_port_index: InputPortIndex) -> Ref<'_, InputPort<T>>;
 
-    fn input_port_mut(&self, input_port_index: InputPortIndex) -> RefMut<InputPort<T>>;
+    fn input_port_mut(&self, input_port_index: InputPortIndex) -> RefMut<'_, InputPort<T>>;
 
     fn output_port(
         &self,
         output_port_index: OutputPortIndex,
-    ) -> Ref<dyn OutputPort<T, CN = Self::CN>>;
+    ) -> Ref<'_, dyn OutputPort<T, CN = Self::CN>>;
 
     fn output_port_mut(
         &self,
         output_port_index: OutputPortIndex,
-    ) -> RefMut<dyn OutputPort<T, CN = Self::CN>>;
+    ) -> RefMut<'_, dyn OutputPort<T, CN = Self::CN>>;
 }
 
 impl<T: AtlasScalar, S> SystemLinkExt<T> for Rc<RefCell<S>>
@@ -211,25 +238,25 @@ where
 {
     type CN = LeafContext<T>;
 
-    fn input_port(&self, input_port_index: InputPortIndex) -> Ref<InputPort<T>> {
+    fn input_port(&self, input_port_index: InputPortIndex) -> Ref<'_, InputPort<T>> {
         Ref::map(self.borrow(), |s| s.input_port(&input_port_index))
     }
 
-    fn input_port_mut(&self, input_port_index: InputPortIndex) -> RefMut<InputPort<T>> {
+    fn input_port_mut(&self, input_port_index: InputPortIndex) -> RefMut<'_, InputPort<T>> {
         RefMut::map(self.borrow_mut(), |s| s.input_port_mut(&input_port_index))
     }
 
     fn output_port(
         &self,
         output_port_index: OutputPortIndex,
-    ) -> Ref<dyn OutputPort<T, CN = Self::CN>> {
+    ) -> Ref<'_, dyn OutputPort<T, CN = Self::CN>> {
         Ref::map(self.borrow(), |s| s.output_port(&output_port_index))
     }
 
     fn output_port_mut(
         &self,
         output_port_index: OutputPortIndex,
-    ) -> RefMut<dyn OutputPort<T, CN = Self::CN>> {
+    ) -> RefMut<'_, dyn OutputPort<T, CN = Self::CN>> {
         RefMut::map(self.borrow_mut(), |s| s.output_port_mut(&output_port_index))
     }
 }
@@ -237,25 +264,25 @@ where
 impl<T: AtlasScalar> SystemLinkExt<T> for DiagramLink<T> {
     type CN = DiagramContext<T>;
 
-    fn input_port(&self, input_port_index: InputPortIndex) -> Ref<InputPort<T>> {
+    fn input_port(&self, input_port_index: InputPortIndex) -> Ref<'_, InputPort<T>> {
         Ref::map(self.borrow(), |s| s.input_port(&input_port_index))
     }
 
-    fn input_port_mut(&self, input_port_index: InputPortIndex) -> RefMut<InputPort<T>> {
+    fn input_port_mut(&self, input_port_index: InputPortIndex) -> RefMut<'_, InputPort<T>> {
         RefMut::map(self.borrow_mut(), |s| s.input_port_mut(&input_port_index))
     }
 
     fn output_port(
         &self,
         output_port_index: OutputPortIndex,
-    ) -> Ref<dyn OutputPort<T, CN = Self::CN>> {
+    ) -> Ref<'_, dyn OutputPort<T, CN = Self::CN>> {
         Ref::map(self.borrow(), |s| s.output_port(&output_port_index))
     }
 
     fn output_port_mut(
         &self,
         output_port_index: OutputPortIndex,
-    ) -> RefMut<dyn OutputPort<T, CN = Self::CN>> {
+    ) -> RefMut<'_, dyn OutputPort<T, CN = Self::CN>> {
         RefMut::map(self.borrow_mut(), |s| s.output_port_mut(&output_port_index))
     }
 }
@@ -348,6 +375,77 @@ impl<T: AtlasScalar> SystemWeakLink<T> {
         }
     }
 
+    pub fn num_input_ports(&self) -> usize {
+        match self {
+            SystemWeakLink::LeafSystemWeakLink(system) => {
+                system.upgrade().unwrap().borrow().num_input_ports()
+            }
+            SystemWeakLink::DiagramWeakLink(system) => {
+                system.upgrade().unwrap().borrow().num_input_ports()
+            }
+        }
+    }
+
+    pub fn num_output_ports(&self) -> usize {
+        match self {
+            SystemWeakLink::LeafSystemWeakLink(system) => {
+                system.upgrade().unwrap().borrow().num_output_ports()
+            }
+            SystemWeakLink::DiagramWeakLink(system) => {
+                system.upgrade().unwrap().borrow().num_output_ports()
+            }
+        }
+    }
+
+    pub fn input_port_name(&self, input_port_index: &InputPortIndex) -> String {
+        match self {
+            SystemWeakLink::LeafSystemWeakLink(system) => system
+                .upgrade()
+                .unwrap()
+                .borrow()
+                .input_port_base(input_port_index)
+                .name()
+                .to_string(),
+            SystemWeakLink::DiagramWeakLink(system) => system
+                .upgrade()
+                .unwrap()
+                .borrow()
+                .input_port_base(input_port_index)
+                .name()
+                .to_string(),
+        }
+    }
+
+    pub fn output_port_name(&self, output_port_index: &OutputPortIndex) -> String {
+        match self {
+            SystemWeakLink::LeafSystemWeakLink(system) => system
+                .upgrade()
+                .unwrap()
+                .borrow()
+                .output_port_base(output_port_index)
+                .name()
+                .to_string(),
+            SystemWeakLink::DiagramWeakLink(system) => system
+                .upgrade()
+                .unwrap()
+                .borrow()
+                .output_port_base(output_port_index)
+                .name()
+                .to_string(),
+        }
+    }
+
+    pub fn get_direct_feedthroughs(&self) -> Vec<(InputPortIndex, OutputPortIndex)> {
+        match self {
+            SystemWeakLink::LeafSystemWeakLink(system) => {
+                system.upgrade().unwrap().borrow().get_direct_feedthroughs()
+            }
+            SystemWeakLink::DiagramWeakLink(system) => {
+                system.upgrade().unwrap().borrow().get_direct_feedthroughs()
+            }
+        }
+    }
+
     pub fn allocate_input_abstract(&self, input_port: &InputPort<T>) -> Box<dyn AbstractValue> {
         match self {
             SystemWeakLink::LeafSystemWeakLink(system) => system
@@ -407,12 +505,28 @@ impl<T: AtlasScalar> Hash for InputPortLocator<T> {
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug)]
 pub struct OutputPortLocator<T: AtlasScalar> {
     pub system_weak_link: SystemWeakLink<T>,
     pub output_port_index: OutputPortIndex,
 }
 
+impl<T: AtlasScalar> PartialEq for OutputPortLocator<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.system_weak_link == other.system_weak_link
+            && self.output_port_index == other.output_port_index
+    }
+}
+
+impl<T: AtlasScalar> Eq for OutputPortLocator<T> {}
+
+impl<T: AtlasScalar> Hash for OutputPortLocator<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.system_weak_link.hash(state);
+        self.output_port_index.hash(state);
+    }
+}
+
 #[derive(Default)]
 pub struct OwnedSystems<T: AtlasScalar> {
     pub systems: Vec<SystemLink<T>>,
@@ -446,7 +560,7 @@ pub struct Diagram<T: AtlasScalar> {
     name: String,
     input_ports: Vec<InputPort<T>>,
     output_ports: Vec<DiagramOutputPort<T>>,
-    cache_entries: Vec<CacheEntry>,
+    cache_entries: CacheEntries,
     context_sizes: ContextSizes,
     system_id: SystemId,
     parent_service: Option<Weak<RefCell<dyn SystemParentServiceInterface>>>,
@@ -515,10 +629,6 @@ impl<T: AtlasScalar> System<T> for Diagram<T> {
         self.system_weak_link.clone().unwrap()
     }
 
-    fn time_derivatives_cache_index(&self) -> &CacheIndex {
-        todo!()
-    }
-
     fn allocate_context(&self) -> Rc<RefCell<Self::CN>> {
         self.do_allocate_context()
     }
@@ -527,12 +637,28 @@ impl<T: AtlasScalar> System<T> for Diagram<T> {
         todo!()
     }
 
-    fn allocate_time_derivatives(&mut self) -> Box<<<Self::CN as Context<T>>::S as State<T>>::CS> {
-        todo!()
+    fn allocate_time_derivatives(&self) -> Box<<<Self::CN as Context<T>>::S as State<T>>::CS> {
+        let substates = self
+            .registered_systems
+            .systems
+            .iter()
+            .map(|system_link| match system_link {
+                SystemLink::LeafSystemLink(system) => OwnedContinuousState::LeafContinuousState(
+                    system.borrow().allocate_time_derivatives(),
+                ),
+                SystemLink::DiagramLink(system) => OwnedContinuousState::DiagramContinuousState(
+                    system.borrow().allocate_time_derivatives(),
+                ),
+            })
+            .collect();
+        let mut derivatives = Box::new(DiagramContinuousState::<T>::from_owned(substates));
+        derivatives.set_system_id(self.system_id().clone());
+        derivatives
     }
 
     fn set_default_state(&self, context: &mut Self::CN) {
-        self.validate_context(context);
+        self.validate_context(context)
+            .unwrap_or_else(|error| panic!("{}", error));
 
         for i in 0..self.num_subsystems() {
             let subcontext = context.get_context(&SubsystemIndex::new(i));
@@ -554,12 +680,239 @@ impl<T: AtlasScalar> System<T> for Diagram<T> {
         }
     }
 
+    fn set_default_parameters(&self, context: &mut Self::CN) {
+        self.validate_context(context)
+            .unwrap_or_else(|error| panic!("{}", error));
+
+        for i in 0..self.num_subsystems() {
+            let subcontext = context.get_context(&SubsystemIndex::new(i));
+            match &self.registered_systems.systems[i] {
+                SystemLink::LeafSystemLink(system) => {
+                    let leaf_context = subcontext.as_leaf_context().unwrap();
+                    system
+                        .borrow()
+                        .set_default_parameters(&mut leaf_context.borrow_mut());
+                }
+                SystemLink::DiagramLink(system) => {
+                    let diagram_context = subcontext.as_diagram_context().unwrap();
+                    system
+                        .borrow()
+                        .set_default_parameters(&mut diagram_context.borrow_mut());
+                }
+            };
+        }
+    }
+
+    fn has_direct_feedthrough(
+        &self,
+        input_port_index: &InputPortIndex,
+        output_port_index: &OutputPortIndex,
+    ) -> bool {
+        self.get_direct_feedthroughs()
+            .contains(&(input_port_index.clone(), output_port_index.clone()))
+    }
+
+    // Composes the feedthroughs of the subsystems: walking upstream from the subsystem output
+    // port behind each exported output, through the subsystem inputs that feed it directly and
+    // the output ports they are connected to, every exported input reached feeds that output.
+    fn get_direct_feedthroughs(&self) -> Vec<(InputPortIndex, OutputPortIndex)> {
+        let subsystem_feedthroughs: HashMap<_, _> = self
+            .system_index_map
+            .keys()
+            .map(|system_weak_link| {
+                (
+                    system_weak_link.clone(),
+                    system_weak_link.get_direct_feedthroughs(),
+                )
+            })
+            .collect();
+
+        let mut feedthroughs = vec![];
+        for (index, exported_locator) in self.output_port_ids.iter().enumerate() {
+            let output_port_index = OutputPortIndex::new(index);
+            let mut pending = vec![exported_locator.clone()];
+            let mut visited: Vec<OutputPortLocator<T>> = vec![];
+            while let Some(output_port_locator) = pending.pop() {
+                if visited.contains(&output_port_locator) {
+                    continue;
+                }
+                for (subsystem_input_port_index, subsystem_output_port_index) in
+                    subsystem_feedthroughs[&output_port_locator.system_weak_link].iter()
+                {
+                    if *subsystem_output_port_index != output_port_locator.output_port_index {
+                        continue;
+                    }
+                    let input_port_locator = InputPortLocator {
+                        system_weak_link: output_port_locator.system_weak_link.clone(),
+                        input_port_index: subsystem_input_port_index.clone(),
+                    };
+                    if let Some(input_port_index) = self.input_port_map.get(&input_port_locator) {
+                        let feedthrough = (input_port_index.clone(), output_port_index.clone());
+                        if !feedthroughs.contains(&feedthrough) {
+                            feedthroughs.push(feedthrough);
+                        }
+                    }
+                    if let Some(upstream_locator) = self.connection_map.get(&input_port_locator) {
+                        pending.push(upstream_locator.clone());
+                    }
+                }
+                visited.push(output_port_locator);
+            }
+        }
+        feedthroughs.sort_by_key(|(input_port_index, output_port_index)| {
+            (input_port_index.value(), output_port_index.value())
+        });
+        feedthroughs
+    }
+
+    fn periodic_discrete_update_timings(&self) -> Vec<PeriodicEventData> {
+        self.collect_subsystem_timings(|system_link| match system_link {
+            SystemLink::LeafSystemLink(system) => {
+                system.borrow().periodic_discrete_update_timings()
+            }
+            SystemLink::DiagramLink(system) => system.borrow().periodic_discrete_update_timings(),
+        })
+    }
+
+    fn periodic_unrestricted_update_timings(&self) -> Vec<PeriodicEventData> {
+        self.collect_subsystem_timings(|system_link| match system_link {
+            SystemLink::LeafSystemLink(system) => {
+                system.borrow().periodic_unrestricted_update_timings()
+            }
+            SystemLink::DiagramLink(system) => {
+                system.borrow().periodic_unrestricted_update_timings()
+            }
+        })
+    }
+
+    fn calc_discrete_variable_updates(
+        &self,
+        context: &Self::CN,
+        timings: &[PeriodicEventData],
+        discrete_state: &mut DiscreteValues<T>,
+    ) {
+        self.validate_context(context)
+            .unwrap_or_else(|error| panic!("{}", error));
+        assert_eq!(
+            discrete_state.num_groups(),
+            context.num_discrete_state_groups()
+        );
+
+        // Each subsystem updates the groups of discrete_state that belong to it, in place.
+        let mut first_group = 0;
+        for i in 0..self.num_subsystems() {
+            let subcontext = context.get_context(&SubsystemIndex::new(i));
+            match &self.registered_systems.systems[i] {
+                SystemLink::LeafSystemLink(system) => {
+                    let leaf_context = subcontext.as_leaf_context().unwrap();
+                    let leaf_context = leaf_context.borrow();
+                    let num_groups = leaf_context.num_discrete_state_groups();
+                    let mut subdiscrete_state =
+                        discrete_state.subset_views(first_group, num_groups);
+                    system.borrow().calc_discrete_variable_updates(
+                        &leaf_context,
+                        timings,
+                        &mut subdiscrete_state,
+                    );
+                    first_group += num_groups;
+                }
+                SystemLink::DiagramLink(system) => {
+                    let diagram_context = subcontext.as_diagram_context().unwrap();
+                    let diagram_context = diagram_context.borrow();
+                    let num_groups = diagram_context.num_discrete_state_groups();
+                    let mut subdiscrete_state =
+                        discrete_state.subset_views(first_group, num_groups);
+                    system.borrow().calc_discrete_variable_updates(
+                        &diagram_context,
+                        timings,
+                        &mut subdiscrete_state,
+                    );
+                    first_group += num_groups;
+                }
+            }
+        }
+    }
+
+    fn calc_unrestricted_updates(
+        &self,
+        context: &Self::CN,
+        timings: &[PeriodicEventData],
+        state: &mut <Self::CN as Context<T>>::S,
+    ) {
+        self.validate_context(context)
+            .unwrap_or_else(|error| panic!("{}", error));
+        assert_eq!(state.num_substates(), self.num_subsystems());
+
+        // Each subsystem updates its own substate of state, in place.
+        for i in 0..self.num_subsystems() {
+            let subcontext = context.get_context(&SubsystemIndex::new(i));
+            match (
+                &self.registered_systems.systems[i],
+                state.substate_ptr_mut(i),
+            ) {
+                (SystemLink::LeafSystemLink(system), StatePtr::LeafStatePtr(substate)) => {
+                    let leaf_context = subcontext.as_leaf_context().unwrap();
+                    system.borrow().calc_unrestricted_updates(
+                        &leaf_context.borrow(),
+                        timings,
+                        unsafe { &mut **substate },
+                    );
+                }
+                (SystemLink::DiagramLink(system), StatePtr::LeafDiagramPtr(substate)) => {
+                    let diagram_context = subcontext.as_diagram_context().unwrap();
+                    system.borrow().calc_unrestricted_updates(
+                        &diagram_context.borrow(),
+                        timings,
+                        unsafe { &mut **substate },
+                    );
+                }
+                _ => panic!(
+                    "Diagram::calc_unrestricted_updates: substate {} has the wrong type",
+                    i
+                ),
+            }
+        }
+    }
+
     fn do_calc_time_derivatives(
-        &mut self,
-        _context: &mut Self::CN,
-        _derivatives: &mut <<Self::CN as Context<T>>::S as State<T>>::CS,
+        &self,
+        context: &Self::CN,
+        derivatives: &mut <<Self::CN as Context<T>>::S as State<T>>::CS,
     ) {
-        todo!()
+        assert_eq!(derivatives.num_substates(), self.num_subsystems());
+
+        // Each subsystem writes its derivatives in place into its portion of derivatives.
+        for i in 0..self.num_subsystems() {
+            let subcontext = context.get_context(&SubsystemIndex::new(i));
+            let subderivatives = derivatives.substate_mut(i);
+            match &self.registered_systems.systems[i] {
+                SystemLink::LeafSystemLink(system) => {
+                    let leaf_context = subcontext.as_leaf_context().unwrap();
+                    system.borrow().calc_time_derivatives(
+                        &leaf_context.borrow(),
+                        subderivatives.as_leaf_continuous_state_mut(),
+                    );
+                }
+                SystemLink::DiagramLink(system) => {
+                    let diagram_context = subcontext.as_diagram_context().unwrap();
+                    system.borrow().calc_time_derivatives(
+                        &diagram_context.borrow(),
+                        subderivatives.as_diagram_continuous_state_mut(),
+                    );
+                }
+            }
+        }
+    }
+
+    fn do_to_scalar_type(&self, scalar_type: TypeId) -> Option<Box<dyn Any>> {
+        convert_to_scalar_type(self, scalar_type)
+    }
+}
+
+impl<T: AtlasScalar> ScalarConvertible for Diagram<T> {
+    fn convert_scalar_type<U: AtlasScalar>(&self) -> Result<SystemLink<U>, AtlasError> {
+        let diagram: DiagramLink<U> = self.try_to_scalar_type::<U>()?;
+        Ok(SystemLink::DiagramLink(diagram))
     }
 }
 
@@ -573,7 +926,8 @@ impl<T: AtlasScalar> SystemParentServiceInterface for Diagram<T> {
         context: &dyn ContextBase,
         input_port: &dyn InputPortBase,
     ) -> Option<Box<dyn AbstractValue>> {
-        self.validate_context(context);
+        self.validate_context(context)
+            .unwrap_or_else(|error| panic!("{}", error));
 
         let diagram_context = context
             .as_any()
@@ -608,7 +962,26 @@ impl<T: AtlasScalar> SystemParentServiceInterface for Diagram<T> {
 
 impl<T: AtlasScalar> Diagram<T> {
     pub fn new() -> Self {
-        Self::default()
+        Self {
+            system_id: SystemId::get_new_id(),
+            ..Self::default()
+        }
+    }
+
+    // The union of the periodic event timings of every subsystem, in subsystem order.
+    fn collect_subsystem_timings(
+        &self,
+        subsystem_timings: impl Fn(&SystemLink<T>) -> Vec<PeriodicEventData>,
+    ) -> Vec<PeriodicEventData> {
+        let mut timings: Vec<PeriodicEventData> = vec![];
+        for system_link in self.registered_systems.systems.iter() {
+            for timing in subsystem_timings(system_link) {
+                if !timings.contains(&timing) {
+                    timings.push(timing);
+                }
+            }
+        }
+        timings
     }
 
     pub fn subsystem_index(&self, system_weak_link: &SystemWeakLink<T>) -> SubsystemIndex {
@@ -636,15 +1009,74 @@ impl<T: AtlasScalar> Diagram<T> {
             context.add_system(SubsystemIndex::new(i), subcontext);
         }
 
-        // TODO: Add MakeState()
-
-        // TODO: Add SubscribeDiagramCompositeTrackersToChildrens()
+        context.borrow_mut().make_state();
+        context.borrow_mut().make_parameters();
 
-        // TODO: Register Subscribers and Trackers
+        self.subscribe_subcontext_trackers(&context.borrow());
 
         context
     }
 
+    // The subcontexts view the state and parameters of the DiagramContext, so their trackers
+    // depend on the Diagram's. Each input port of a subsystem depends on the output port or the
+    // exported input port it is connected to, and each exported output port on its source.
+    fn subscribe_subcontext_trackers(&self, context: &DiagramContext<T>) {
+        let graph = context.graph().clone();
+        let subgraphs: Vec<_> = (0..self.num_subsystems())
+            .map(|i| {
+                context
+                    .get_context(&SubsystemIndex::new(i))
+                    .as_context_base()
+                    .borrow()
+                    .graph()
+                    .clone()
+            })
+            .collect();
+
+        for subgraph in subgraphs.iter() {
+            for ticket in [
+                DependencyTicket::ContinuousState,
+                DependencyTicket::DiscreteState,
+                DependencyTicket::AbstractState,
+                DependencyTicket::Parameters,
+            ] {
+                subgraph.subscribe_to_prerequisite(&ticket, &graph, &ticket);
+            }
+        }
+
+        for (input_port_locator, output_port_locator) in self.connection_map.iter() {
+            let input_subgraph =
+                &subgraphs[self.subsystem_index(&input_port_locator.system_weak_link)];
+            let output_subgraph =
+                &subgraphs[self.subsystem_index(&output_port_locator.system_weak_link)];
+            input_subgraph.subscribe_to_prerequisite(
+                &DependencyTicket::InputPort(input_port_locator.input_port_index.clone()),
+                output_subgraph,
+                &DependencyTicket::OutputPort(output_port_locator.output_port_index.clone()),
+            );
+        }
+
+        for (input_port_locator, input_port_index) in self.input_port_map.iter() {
+            let input_subgraph =
+                &subgraphs[self.subsystem_index(&input_port_locator.system_weak_link)];
+            input_subgraph.subscribe_to_prerequisite(
+                &DependencyTicket::InputPort(input_port_locator.input_port_index.clone()),
+                &graph,
+                &DependencyTicket::InputPort(input_port_index.clone()),
+            );
+        }
+
+        for (index, output_port_locator) in self.output_port_ids.iter().enumerate() {
+            let output_subgraph =
+                &subgraphs[self.subsystem_index(&output_port_locator.system_weak_link)];
+            graph.subscribe_to_prerequisite(
+                &DependencyTicket::OutputPort(OutputPortIndex::new(index)),
+                output_subgraph,
+                &DependencyTicket::OutputPort(output_port_locator.output_port_index.clone()),
+            );
+        }
+    }
+
     pub fn diagram_output_port(&self, index: &OutputPortIndex) -> &DiagramOutputPort<T> {
         &self.output_ports[index]
     }
@@ -670,22 +1102,94 @@ impl<T: AtlasScalar> Diagram<T> {
 
     pub fn create_default_context(&self) -> Rc<RefCell<DiagramContext<T>>> {
         let context = self.do_allocate_context();
-        self.set_default_state(&mut context.borrow_mut());
+        self.set_default_context(&mut context.borrow_mut());
         context
     }
 
-    pub fn from_blueprint(blueprint: DiagramBlueprint<T>) -> Rc<RefCell<Self>> {
-        let mut diagram = Rc::new(RefCell::new(Self::new()));
+    pub fn to_scalar_type<U: AtlasScalar>(&self) -> Rc<RefCell<Diagram<U>>> {
+        self.try_to_scalar_type()
+            .unwrap_or_else(|error| panic!("Diagram::to_scalar_type: {}", error))
+    }
+
+    // Rebuilds this Diagram with the scalar type U. Every subsystem is converted, and the
+    // converted subsystems are connected and exported like the original ones.
+    pub fn try_to_scalar_type<U: AtlasScalar>(
+        &self,
+    ) -> Result<Rc<RefCell<Diagram<U>>>, AtlasError> {
+        let mut blueprint = DiagramBlueprint::<U>::new();
+        for system_link in self.registered_systems.systems.iter() {
+            let converted = system_link.try_to_scalar_type::<U>()?;
+            blueprint
+                .system_weak_links
+                .push(converted.system_weak_link());
+            blueprint.registered_systems.push(converted);
+        }
 
-        unsafe {
-            let diagram_weak = Rc::downgrade(&diagram);
-            let diagram_weak_ptr = Weak::into_raw(diagram_weak);
-            let system_weak =
-                Weak::<RefCell<dyn System<T, CN = DiagramContext<T>>>>::from_raw(diagram_weak_ptr);
-            diagram.borrow_mut().system_weak_link =
-                Some(SystemWeakLink::DiagramWeakLink(system_weak));
+        let system_weak_links = blueprint.system_weak_links.clone();
+        let convert_system_weak_link = |system_weak_link: &SystemWeakLink<T>| {
+            system_weak_links[self.subsystem_index(system_weak_link).value()].clone()
+        };
+        let convert_input_port_locator = |locator: &InputPortLocator<T>| InputPortLocator {
+            system_weak_link: convert_system_weak_link(&locator.system_weak_link),
+            input_port_index: locator.input_port_index.clone(),
+        };
+        let convert_output_port_locator = |locator: &OutputPortLocator<T>| OutputPortLocator {
+            system_weak_link: convert_system_weak_link(&locator.system_weak_link),
+            output_port_index: locator.output_port_index.clone(),
+        };
+
+        blueprint.connection_map = self
+            .connection_map
+            .iter()
+            .map(|(input_port_locator, output_port_locator)| {
+                (
+                    convert_input_port_locator(input_port_locator),
+                    convert_output_port_locator(output_port_locator),
+                )
+            })
+            .collect();
+
+        // Exported input ports are declared in the order of their indices.
+        let mut input_ports: Vec<_> = self.input_port_map.iter().collect();
+        input_ports.sort_by_key(|(locator, index)| {
+            (
+                index.value(),
+                self.subsystem_index(&locator.system_weak_link).value(),
+                locator.input_port_index.value(),
+            )
+        });
+        for (locator, index) in input_ports {
+            blueprint
+                .input_port_ids
+                .push(convert_input_port_locator(locator));
+            blueprint
+                .input_port_names
+                .push(self.input_ports[index].name().to_string());
+        }
+        for (locator, output_port) in self.output_port_ids.iter().zip(self.output_ports.iter()) {
+            blueprint
+                .output_port_ids
+                .push(convert_output_port_locator(locator));
+            blueprint
+                .output_port_names
+                .push(output_port.name().to_string());
         }
 
+        let diagram = Diagram::from_blueprint(blueprint);
+        diagram.borrow_mut().set_name(self.name.clone());
+
+        Ok(diagram)
+    }
+
+    pub fn from_blueprint(blueprint: DiagramBlueprint<T>) -> Rc<RefCell<Self>> {
+        let mut diagram = Rc::new_cyclic(|diagram_weak: &Weak<RefCell<Self>>| {
+            let system_weak: DiagramWeakLink<T> = diagram_weak.clone();
+            RefCell::new(Self {
+                system_weak_link: Some(SystemWeakLink::DiagramWeakLink(system_weak)),
+                ..Self::new()
+            })
+        });
+
         diagram.initialize(blueprint);
 
         diagram
@@ -745,7 +1249,7 @@ impl<T: AtlasScalar> Diagram<T> {
                 let result = subsystem
                     .borrow()
                     .output_port(&output_port_index)
-                    .eval_abstract(&*leaf_context.borrow_mut());
+                    .eval_abstract(&*leaf_context.borrow());
                 result
             }
             SystemWeakLink::DiagramWeakLink(system) => {
@@ -754,7 +1258,7 @@ impl<T: AtlasScalar> Diagram<T> {
                 let result = subsystem
                     .borrow()
                     .output_port(&output_port_index)
-                    .eval_abstract(&*diagram_context.borrow_mut());
+                    .eval_abstract(&*diagram_context.borrow());
                 result
             }
         }
@@ -762,13 +1266,13 @@ impl<T: AtlasScalar> Diagram<T> {
 }
 
 pub trait DiagramExt<T: AtlasScalar> {
-    fn input_port(&self, index: &InputPortIndex) -> Ref<InputPort<T>>;
+    fn input_port(&self, index: &InputPortIndex) -> Ref<'_, InputPort<T>>;
 
-    fn input_port_mut(&self, index: &InputPortIndex) -> RefMut<InputPort<T>>;
+    fn input_port_mut(&self, index: &InputPortIndex) -> RefMut<'_, InputPort<T>>;
 
-    fn diagram_output_port(&self, index: &OutputPortIndex) -> Ref<DiagramOutputPort<T>>;
+    fn diagram_output_port(&self, index: &OutputPortIndex) -> Ref<'_, DiagramOutputPort<T>>;
 
-    fn diagram_output_port_mut(&self, index: &OutputPortIndex) -> RefMut<DiagramOutputPort<T>>;
+    fn diagram_output_port_mut(&self, index: &OutputPortIndex) -> RefMut<'_, DiagramOutputPort<T>>;
 
     fn initialize(&mut self, blueprint: DiagramBlueprint<T>);
 
@@ -776,19 +1280,19 @@ pub trait DiagramExt<T: AtlasScalar> {
 }
 
 impl<T: AtlasScalar> DiagramExt<T> for Rc<RefCell<Diagram<T>>> {
-    fn input_port(&self, index: &InputPortIndex) -> Ref<InputPort<T>> {
+    fn input_port(&self, index: &InputPortIndex) -> Ref<'_, InputPort<T>> {
         Ref::map(self.borrow(), |diagram| diagram.input_port(index))
     }
 
-    fn input_port_mut(&self, index: &InputPortIndex) -> RefMut<InputPort<T>> {
+    fn input_port_mut(&self, index: &InputPortIndex) -> RefMut<'_, InputPort<T>> {
         RefMut::map(self.borrow_mut(), |diagram| diagram.input_port_mut(index))
     }
 
-    fn diagram_output_port(&self, index: &OutputPortIndex) -> Ref<DiagramOutputPort<T>> {
+    fn diagram_output_port(&self, index: &OutputPortIndex) -> Ref<'_, DiagramOutputPort<T>> {
         Ref::map(self.borrow(), |diagram| diagram.diagram_output_port(index))
     }
 
-    fn diagram_output_port_mut(&self, index: &OutputPortIndex) -> RefMut<DiagramOutputPort<T>> {
+    fn diagram_output_port_mut(&self, index: &OutputPortIndex) -> RefMut<'_, DiagramOutputPort<T>> {
         RefMut::map(self.borrow_mut(), |diagram| {
             diagram.diagram_output_port_mut(index)
         })
@@ -809,12 +1313,8 @@ impl<T: AtlasScalar> DiagramExt<T> for Rc<RefCell<Diagram<T>>> {
         }
 
         // Set parent service for all subsystems
-        let diagram_weak_link = Rc::downgrade(self);
-        let parent_service = unsafe {
-            let raw_ptr = Weak::into_raw(diagram_weak_link);
-            let trait_ptr = raw_ptr as *const RefCell<dyn SystemParentServiceInterface>;
-            Weak::<RefCell<dyn SystemParentServiceInterface>>::from_raw(trait_ptr)
-        };
+        let diagram_weak = Rc::downgrade(self);
+        let parent_service: Weak<RefCell<dyn SystemParentServiceInterface>> = diagram_weak;
 
         // Set parent service for each subsystem
         for system_link in &mut self.borrow_mut().registered_systems.systems {
@@ -876,8 +1376,17 @@ impl<T: AtlasScalar> DiagramExt<T> for Rc<RefCell<Diagram<T>>> {
 
 #[cfg(test)]
 mod tests {
+    extern crate nalgebra as na;
+
+    use crate::systems::analysis::test_utilities::discrete_counter::DiscreteCounter;
+    use crate::systems::analysis::test_utilities::spring_mass_system::SpringMassSystem;
+    use crate::systems::analysis::test_utilities::time_recorder::TimeRecorder;
+    use crate::systems::controllers::pid_controller::PIDController;
     use crate::systems::framework::basic_vector::BasicVector;
     use crate::systems::framework::diagram_builder::DiagramBuilder;
+    use crate::systems::framework::framework_common::{
+        AbstractStateIndex, CacheIndex, DiscreteStateIndex, NumericParameterIndex,
+    };
     use crate::systems::primitives::adder::Adder;
 
     use super::*;
@@ -910,6 +1419,54 @@ mod tests {
         assert_eq!(System::<f64>::output_ports(&*diagram.borrow()).len(), 2);
     }
 
+    #[test]
+    fn test_direct_feedthrough() {
+        let mut diagram_builder = DiagramBuilder::<f64>::new();
+        let adder1 = Adder::new(1, 1);
+        let adder2 = Adder::new(1, 1);
+        diagram_builder.add_leaf_system(&adder1);
+        diagram_builder.add_leaf_system(&adder2);
+
+        diagram_builder.export_input_port(adder1.input_port(InputPortIndex::new(0)));
+        diagram_builder.export_input_port(adder2.input_port(InputPortIndex::new(0)));
+        diagram_builder.export_output_port(adder1.output_port(OutputPortIndex::new(0)));
+        let diagram = diagram_builder.build();
+
+        assert!(diagram
+            .borrow()
+            .has_direct_feedthrough(&InputPortIndex::new(0), &OutputPortIndex::new(0)));
+        assert!(!diagram
+            .borrow()
+            .has_direct_feedthrough(&InputPortIndex::new(1), &OutputPortIndex::new(0)));
+
+        // The feedthroughs compose through connections.
+        let mut diagram_builder = DiagramBuilder::<f64>::new();
+        let adder = Adder::new(1, 2);
+        let gain = na::DVector::<f64>::from_vec(vec![1.0]);
+        let pid_controller = PIDController::new(gain.clone(), gain.clone(), gain);
+        diagram_builder.add_leaf_system(&adder);
+        diagram_builder.add_leaf_system(&pid_controller);
+
+        diagram_builder.export_input_port(adder.input_port(InputPortIndex::new(0)));
+        diagram_builder.export_input_port(pid_controller.input_port(InputPortIndex::new(1)));
+        diagram_builder.connect(
+            adder.output_port_mut(OutputPortIndex::new(0)),
+            pid_controller.input_port(InputPortIndex::new(0)),
+        );
+        diagram_builder.export_output_port(adder.output_port(OutputPortIndex::new(0)));
+        diagram_builder.export_output_port(pid_controller.output_port(OutputPortIndex::new(0)));
+        let diagram = diagram_builder.build();
+
+        assert_eq!(
+            diagram.borrow().get_direct_feedthroughs(),
+            vec![
+                (InputPortIndex::new(0), OutputPortIndex::new(0)),
+                (InputPortIndex::new(0), OutputPortIndex::new(1)),
+                (InputPortIndex::new(1), OutputPortIndex::new(1)),
+            ]
+        );
+    }
+
     #[test]
     fn test_connection_map() {
         let mut diagram_builder = DiagramBuilder::<f64>::new();
@@ -942,6 +1499,11 @@ mod tests {
 
         let diagram = diagram_builder.build();
         assert_eq!(diagram.borrow().num_subsystems(), 3);
+        for i in 0..4 {
+            assert!(diagram
+                .borrow()
+                .has_direct_feedthrough(&InputPortIndex::new(i), &OutputPortIndex::new(0)));
+        }
 
         let diagram_context = diagram.create_default_context();
 
@@ -964,4 +1526,405 @@ mod tests {
         let sum_expected = inputs[0].clone() + &inputs[1] + &inputs[2] + &inputs[3];
         assert_eq!(sum, sum_expected);
     }
+
+    #[test]
+    fn test_to_scalar_type() {
+        let mut diagram_builder = DiagramBuilder::<f64>::new();
+        let adder1 = Adder::new(2, 2);
+        let adder2 = Adder::new(1, 2);
+        diagram_builder.add_leaf_system(&adder1);
+        diagram_builder.add_leaf_system(&adder2);
+
+        diagram_builder.export_input_port(adder1.input_port(InputPortIndex::new(0)));
+        diagram_builder.export_input_port(adder1.input_port(InputPortIndex::new(1)));
+        diagram_builder.connect(
+            adder1.output_port_mut(OutputPortIndex::new(0)),
+            adder2.input_port(InputPortIndex::new(0)),
+        );
+        diagram_builder.export_output_port(adder2.output_port(OutputPortIndex::new(0)));
+        let diagram = diagram_builder.build();
+        diagram.borrow_mut().set_name("sum".to_string());
+
+        let converted = diagram.borrow().to_scalar_type::<f32>();
+        assert_eq!(converted.borrow().name(), "sum");
+        assert_eq!(converted.borrow().num_subsystems(), 2);
+        assert_eq!(
+            converted.borrow().get_direct_feedthroughs(),
+            diagram.borrow().get_direct_feedthroughs()
+        );
+        for i in 0..2 {
+            let input_port_index = InputPortIndex::new(i);
+            assert_eq!(
+                converted.input_port(&input_port_index).name(),
+                diagram.input_port(&input_port_index).name()
+            );
+        }
+
+        let context = converted.create_default_context();
+        converted.input_port_mut(&InputPortIndex::new(0)).fix_value(
+            context.borrow_mut(),
+            BasicVector::<f32>::from_vec(vec![1.0, 2.0]),
+        );
+        converted.input_port_mut(&InputPortIndex::new(1)).fix_value(
+            context.borrow_mut(),
+            BasicVector::<f32>::from_vec(vec![0.5, 0.25]),
+        );
+        let sum = converted
+            .diagram_output_port(&OutputPortIndex::new(0))
+            .eval::<BasicVector<f32>>(context.borrow());
+        assert_eq!(sum, BasicVector::<f32>::from_vec(vec![1.5, 2.25]));
+
+        // A Diagram converts back to f64 through the same conversions.
+        let round_trip = converted.borrow().to_scalar_type::<f64>();
+        assert_eq!(round_trip.borrow().num_subsystems(), 2);
+    }
+
+    #[test]
+    fn test_to_scalar_type_unsupported() {
+        let mut diagram_builder = DiagramBuilder::<f64>::new();
+        let spring_mass_system = SpringMassSystem::new(1.0);
+        diagram_builder.add_leaf_system(&spring_mass_system);
+        let diagram = diagram_builder.build();
+
+        let error = diagram.borrow().try_to_scalar_type::<f32>().err().unwrap();
+        assert_eq!(
+            error,
+            AtlasError::UnsupportedScalarType {
+                system: "spring_mass_system".to_string(),
+                scalar_type: "f32".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_output_port_cache_invalidation() {
+        let mut diagram_builder = DiagramBuilder::<f64>::new();
+        let adder1 = Adder::new(2, 1);
+        let adder2 = Adder::new(2, 1);
+        let adder3 = Adder::new(2, 1);
+        diagram_builder.add_leaf_system(&adder1);
+        diagram_builder.add_leaf_system(&adder2);
+        diagram_builder.add_leaf_system(&adder3);
+        for adder in [&adder1, &adder2] {
+            diagram_builder.export_input_port(adder.input_port(InputPortIndex::new(0)));
+            diagram_builder.export_input_port(adder.input_port(InputPortIndex::new(1)));
+        }
+        diagram_builder.connect(
+            adder1.output_port_mut(OutputPortIndex::new(0)),
+            adder3.input_port(InputPortIndex::new(0)),
+        );
+        diagram_builder.connect(
+            adder2.output_port_mut(OutputPortIndex::new(0)),
+            adder3.input_port(InputPortIndex::new(1)),
+        );
+        diagram_builder.export_output_port(adder3.output_port(OutputPortIndex::new(0)));
+        let diagram = diagram_builder.build();
+
+        let diagram_context = diagram.create_default_context();
+        for i in 0..4 {
+            diagram.input_port_mut(&InputPortIndex::new(i)).fix_value(
+                diagram_context.borrow_mut(),
+                BasicVector::<f64>::from_vec(vec![i as f64]),
+            );
+        }
+        let needs_recomputation = |index: usize| {
+            diagram_context
+                .borrow()
+                .get_context(&SubsystemIndex::new(index))
+                .as_context_base()
+                .borrow()
+                .cache()
+                .borrow()
+                .cache_entry_value(&CacheIndex::new(0))
+                .needs_recomputation()
+        };
+        let eval_sum = || {
+            diagram
+                .diagram_output_port(&OutputPortIndex::new(0))
+                .eval::<BasicVector<f64>>(diagram_context.borrow())
+        };
+
+        assert!((0..3).all(needs_recomputation));
+        assert_eq!(eval_sum(), BasicVector::<f64>::from_vec(vec![6.0]));
+        assert!(!(0..3).any(needs_recomputation));
+
+        // Only the sums downstream of the changed input are recomputed.
+        diagram.input_port_mut(&InputPortIndex::new(2)).fix_value(
+            diagram_context.borrow_mut(),
+            BasicVector::<f64>::from_vec(vec![10.0]),
+        );
+        assert!(!needs_recomputation(0));
+        assert!(needs_recomputation(1));
+        assert!(needs_recomputation(2));
+        assert_eq!(eval_sum(), BasicVector::<f64>::from_vec(vec![14.0]));
+
+        // The sums do not depend on time.
+        diagram_context.borrow_mut().set_time(1.0);
+        assert!(!(0..3).any(needs_recomputation));
+    }
+
+    fn set_spring_mass_state(
+        spring_mass_system: &Rc<RefCell<SpringMassSystem<f64>>>,
+        context: &DiagramContext<f64>,
+        index: usize,
+        q: f64,
+        v: f64,
+    ) {
+        let leaf_context = context
+            .get_context(&SubsystemIndex::new(index))
+            .as_leaf_context()
+            .unwrap();
+        spring_mass_system
+            .borrow()
+            .set_position_and_velocity(&mut leaf_context.borrow_mut(), q, v);
+    }
+
+    #[test]
+    fn test_continuous_state() {
+        let mut diagram_builder = DiagramBuilder::<f64>::new();
+        let spring_mass_system1 = SpringMassSystem::<f64>::new(1.0);
+        let spring_mass_system2 = SpringMassSystem::<f64>::new(4.0);
+        diagram_builder.add_leaf_system(&spring_mass_system1);
+        diagram_builder.add_leaf_system(&spring_mass_system2);
+        let diagram = diagram_builder.build();
+
+        let diagram_context = diagram.create_default_context();
+        set_spring_mass_state(&spring_mass_system1, &diagram_context.borrow(), 0, 1.0, 2.0);
+        set_spring_mass_state(&spring_mass_system2, &diagram_context.borrow(), 1, 3.0, 4.0);
+
+        // The state is ordered [q_all, v_all, z_all].
+        {
+            let context = diagram_context.borrow();
+            let continuous_state = context.continuous_state();
+            assert_eq!(continuous_state.num_q(), 2);
+            assert_eq!(continuous_state.num_v(), 2);
+            assert_eq!(continuous_state.num_z(), 0);
+            assert_eq!(
+                continuous_state.vector().copy_to_vector(),
+                na::DVector::<f64>::from_vec(vec![1.0, 3.0, 2.0, 4.0])
+            );
+            assert_eq!(
+                continuous_state.generalized_velocity().value().into_owned(),
+                na::DVector::<f64>::from_vec(vec![2.0, 4.0])
+            );
+        }
+
+        // Writes through the Diagram are seen by the subsystems, and vice versa.
+        diagram_context
+            .borrow_mut()
+            .continuous_state_vector_mut()
+            .set_from_vector(&na::DVector::<f64>::from_vec(vec![5.0, 6.0, 7.0, 8.0]));
+        let leaf_context = diagram_context
+            .borrow()
+            .get_context(&SubsystemIndex::new(1))
+            .as_leaf_context()
+            .unwrap();
+        assert_eq!(
+            leaf_context
+                .borrow()
+                .continuous_state_vector()
+                .copy_to_vector(),
+            na::DVector::<f64>::from_vec(vec![6.0, 8.0])
+        );
+
+        let derivatives = diagram
+            .borrow()
+            .eval_time_derivatives(&diagram_context.borrow());
+        assert_eq!(
+            derivatives.vector().copy_to_vector(),
+            na::DVector::<f64>::from_vec(vec![7.0, 8.0, -5.0, -24.0])
+        );
+    }
+
+    #[test]
+    fn test_nested_diagram_continuous_state() {
+        let mut inner_builder = DiagramBuilder::<f64>::new();
+        let inner_spring_mass_system = SpringMassSystem::<f64>::new(2.0);
+        inner_builder.add_leaf_system(&inner_spring_mass_system);
+        let inner_diagram = inner_builder.build();
+
+        let mut outer_builder = DiagramBuilder::<f64>::new();
+        let outer_spring_mass_system = SpringMassSystem::<f64>::new(3.0);
+        outer_builder.add_leaf_system(&outer_spring_mass_system);
+        outer_builder.add_diagram(&inner_diagram);
+        let outer_diagram = outer_builder.build();
+
+        let outer_context = outer_diagram.create_default_context();
+        assert_eq!(outer_context.borrow().num_continuous_states(), 4);
+
+        outer_context
+            .borrow_mut()
+            .continuous_state_vector_mut()
+            .set_from_vector(&na::DVector::<f64>::from_vec(vec![1.0, 2.0, 3.0, 4.0]));
+        let inner_context = outer_context
+            .borrow()
+            .get_context(&SubsystemIndex::new(1))
+            .as_diagram_context()
+            .unwrap();
+        assert_eq!(
+            inner_context
+                .borrow()
+                .continuous_state_vector()
+                .copy_to_vector(),
+            na::DVector::<f64>::from_vec(vec![2.0, 4.0])
+        );
+
+        let derivatives = outer_diagram
+            .borrow()
+            .eval_time_derivatives(&outer_context.borrow());
+        assert_eq!(
+            derivatives.vector().copy_to_vector(),
+            na::DVector::<f64>::from_vec(vec![3.0, 4.0, -3.0, -4.0])
+        );
+    }
+
+    #[test]
+    fn test_nested_diagram_discrete_state() {
+        let mut inner_builder = DiagramBuilder::<f64>::new();
+        let inner_discrete_counter = DiscreteCounter::<f64>::new(0.25, 0.0);
+        inner_builder.add_leaf_system(&inner_discrete_counter);
+        let inner_diagram = inner_builder.build();
+
+        let mut outer_builder = DiagramBuilder::<f64>::new();
+        let outer_discrete_counter = DiscreteCounter::<f64>::new(0.1, 0.0);
+        outer_builder.add_leaf_system(&outer_discrete_counter);
+        outer_builder.add_diagram(&inner_diagram);
+        let outer_diagram = outer_builder.build();
+
+        let timings = outer_diagram.borrow().periodic_discrete_update_timings();
+        assert_eq!(
+            timings,
+            vec![
+                PeriodicEventData::new(0.1, 0.0),
+                PeriodicEventData::new(0.25, 0.0)
+            ]
+        );
+
+        // The discrete state holds the groups of every leaf system, in subsystem order.
+        let outer_context = outer_diagram.create_default_context();
+        assert_eq!(outer_context.borrow().num_discrete_state_groups(), 2);
+        outer_context.borrow_mut().discrete_state_mut().set_value(
+            &DiscreteStateIndex::new(1),
+            &na::DVector::<f64>::from_vec(vec![5.0]),
+        );
+        let inner_context = outer_context
+            .borrow()
+            .get_context(&SubsystemIndex::new(1))
+            .as_diagram_context()
+            .unwrap();
+        assert_eq!(
+            inner_context
+                .borrow()
+                .discrete_state()
+                .value(&DiscreteStateIndex::new(0)),
+            na::DVector::<f64>::from_vec(vec![5.0])
+        );
+
+        // Only the events with the given timings update their groups.
+        let mut discrete_state = outer_context.borrow().discrete_state().clone();
+        outer_diagram.borrow().calc_discrete_variable_updates(
+            &outer_context.borrow(),
+            &timings[1..],
+            &mut discrete_state,
+        );
+        assert_eq!(
+            discrete_state.value(&DiscreteStateIndex::new(0)),
+            na::DVector::<f64>::from_vec(vec![0.0])
+        );
+        assert_eq!(
+            discrete_state.value(&DiscreteStateIndex::new(1)),
+            na::DVector::<f64>::from_vec(vec![6.0])
+        );
+    }
+
+    #[test]
+    fn test_nested_diagram_abstract_state() {
+        let mut inner_builder = DiagramBuilder::<f64>::new();
+        let inner_time_recorder = TimeRecorder::<f64>::new(0.5, 0.0);
+        inner_builder.add_leaf_system(&inner_time_recorder);
+        let inner_diagram = inner_builder.build();
+
+        let mut outer_builder = DiagramBuilder::<f64>::new();
+        let outer_time_recorder = TimeRecorder::<f64>::new(0.5, 0.0);
+        outer_builder.add_leaf_system(&outer_time_recorder);
+        outer_builder.add_diagram(&inner_diagram);
+        let outer_diagram = outer_builder.build();
+
+        let outer_context = outer_diagram.create_default_context();
+        assert_eq!(outer_context.borrow().num_abstract_states(), 2);
+
+        // A copy of the state is updated independently of the context.
+        let timings = outer_diagram
+            .borrow()
+            .periodic_unrestricted_update_timings();
+        assert_eq!(timings, vec![PeriodicEventData::new(0.5, 0.0)]);
+        outer_context.borrow_mut().set_time(0.5);
+        let mut state = outer_context.borrow().state().clone();
+        outer_diagram.borrow().calc_unrestricted_updates(
+            &outer_context.borrow(),
+            &timings,
+            &mut state,
+        );
+        assert!(outer_context
+            .borrow()
+            .get_abstract_state::<Vec<f64>>(&AbstractStateIndex::new(1))
+            .is_empty());
+
+        outer_context.borrow_mut().state_mut().set_from(&state);
+        let inner_context = outer_context
+            .borrow()
+            .get_context(&SubsystemIndex::new(1))
+            .as_diagram_context()
+            .unwrap();
+        assert_eq!(
+            *inner_context
+                .borrow()
+                .get_abstract_state::<Vec<f64>>(&AbstractStateIndex::new(0)),
+            vec![0.5]
+        );
+    }
+
+    #[test]
+    fn test_diagram_parameters() {
+        let gains = |value: f64| na::DVector::<f64>::from_vec(vec![value]);
+        let mut diagram_builder = DiagramBuilder::<f64>::new();
+        let pid_controller1 = PIDController::<f64>::new(gains(1.0), gains(2.0), gains(3.0));
+        let pid_controller2 = PIDController::<f64>::new(gains(4.0), gains(5.0), gains(6.0));
+        diagram_builder.add_leaf_system(&pid_controller1);
+        diagram_builder.add_leaf_system(&pid_controller2);
+        let diagram = diagram_builder.build();
+
+        // The Diagram's parameters view those of every leaf system, in subsystem order.
+        let diagram_context = diagram.create_default_context();
+        assert_eq!(diagram_context.borrow().num_numeric_parameter_groups(), 6);
+        assert_eq!(
+            diagram_context
+                .borrow()
+                .get_numeric_parameter(&NumericParameterIndex::new(3))
+                .copy_to_vector(),
+            gains(4.0)
+        );
+
+        diagram_context
+            .borrow_mut()
+            .get_mutable_numeric_parameter(&NumericParameterIndex::new(3))
+            .set_from_vector(&gains(7.0));
+        let leaf_context = diagram_context
+            .borrow()
+            .get_context(&SubsystemIndex::new(1))
+            .as_leaf_context()
+            .unwrap();
+        assert_eq!(
+            pid_controller2.borrow().kp(&leaf_context.borrow()),
+            gains(7.0)
+        );
+
+        diagram
+            .borrow()
+            .set_default_context(&mut diagram_context.borrow_mut());
+        assert_eq!(
+            pid_controller2.borrow().kp(&leaf_context.borrow()),
+            gains(4.0)
+        );
+    }
 }
@@ -3,6 +3,7 @@ use std::collections::HashMap;
 use std::ops::{Deref, DerefMut};
 use std::rc::Rc;
 
+use crate::common::atlas_error::AtlasError;
 use crate::common::atlas_scalar::AtlasScalar;
 use crate::systems::framework::context::Context;
 use crate::systems::framework::diagram::{
@@ -18,7 +19,6 @@ use crate::systems::framework::port_base::PortBase;
 use crate::systems::framework::system::System;
 
 struct ExportedInputPortData<T: AtlasScalar> {
-    #[allow(dead_code)]
     pub input_port_locator: InputPortLocator<T>,
 }
 
@@ -32,7 +32,6 @@ pub struct DiagramBuilder<T: AtlasScalar> {
     connection_map: HashMap<InputPortLocator<T>, OutputPortLocator<T>>,
     system_weak_links: Vec<SystemWeakLink<T>>,
     registered_systems: OwnedSystems<T>,
-    already_built: bool,
 }
 
 impl<T: AtlasScalar> DiagramBuilder<T> {
@@ -86,55 +85,110 @@ impl<T: AtlasScalar> DiagramBuilder<T> {
         system_link
     }
 
-    pub fn connect<CN, I, O>(&mut self, mut output_port: O, input_port: I)
+    pub fn connect<CN, I, O>(&mut self, output_port: O, input_port: I)
     where
         CN: Context<T>,
         I: Deref<Target = InputPort<T>>,
         O: DerefMut<Target = dyn OutputPort<T, CN = CN>>,
     {
-        let input_port = &*input_port;
-        let output_port = &mut *output_port;
-        self.assert_if_already_built();
+        self.try_connect(output_port, input_port)
+            .unwrap_or_else(|error| panic!("DiagramBuilder::connect: {}", error))
+    }
 
-        let input_port_locator = InputPortLocator::<T> {
-            system_weak_link: input_port.system_weak_link().clone(),
-            input_port_index: input_port.index().clone(),
-        };
+    pub fn try_connect<CN, I, O>(
+        &mut self,
+        mut output_port: O,
+        input_port: I,
+    ) -> Result<(), AtlasError>
+    where
+        CN: Context<T>,
+        I: Deref<Target = InputPort<T>>,
+        O: DerefMut<Target = dyn OutputPort<T, CN = CN>>,
+    {
+        let input_port = &*input_port;
+        // Take what is needed from the output port up front: it mutably borrows its System,
+        // whose name can only be read once the borrow is released.
         let output_port_locator = OutputPortLocator::<T> {
             system_weak_link: output_port.system_weak_link().clone(),
             output_port_index: output_port.index().clone(),
         };
+        let output_port_name = output_port.name().to_string();
+        let output_data_type = output_port.data_type().clone();
+        let output_size = output_port.size();
+        let model_output = match output_data_type {
+            PortDataType::AbstractValued => Some(output_port.allocate()),
+            PortDataType::VectorValued => None,
+        };
+        drop(output_port);
 
-        if *output_port.data_type() == PortDataType::AbstractValued {
-            let model_output = output_port.allocate();
+        let input_port_locator = InputPortLocator::<T> {
+            system_weak_link: input_port.system_weak_link().clone(),
+            input_port_index: input_port.index().clone(),
+        };
+        self.check_system_registered(input_port.system_weak_link())?;
+        self.check_system_registered(&output_port_locator.system_weak_link)?;
+        self.check_input_not_connected(&input_port_locator, input_port)?;
+
+        Self::check_vector_compatible(
+            output_port_locator.system_weak_link.name(),
+            &output_port_name,
+            &output_data_type,
+            output_size,
+            input_port,
+        )?;
+        if let Some(model_output) = model_output {
             let model_input = input_port
                 .system_weak_link()
                 .allocate_input_abstract(input_port);
             if model_output.type_id() != model_input.type_id() {
-                panic!(
-                    "Mismatched value types while connecting output port (type {:?}) to input port (type {:?})",
-                    model_output.type_id(),
-                    model_input.type_id()
-                );
+                return Err(AtlasError::AbstractTypeMismatch {
+                    source_system: output_port_locator.system_weak_link.name(),
+                    source_port: output_port_name,
+                    destination_system: input_port.system_weak_link().name(),
+                    destination_port: input_port.name().to_string(),
+                });
             }
         }
         self.connection_map
             .insert(input_port_locator, output_port_locator);
+
+        Ok(())
     }
 
     pub fn export_input_port<I>(&mut self, input_port: I) -> InputPortIndex
+    where
+        I: Deref<Target = InputPort<T>>,
+    {
+        self.try_export_input_port(input_port)
+            .unwrap_or_else(|error| panic!("DiagramBuilder::export_input_port: {}", error))
+    }
+
+    pub fn try_export_input_port<I>(&mut self, input_port: I) -> Result<InputPortIndex, AtlasError>
     where
         I: Deref<Target = InputPort<T>>,
     {
         let input_port = &*input_port;
-        self.assert_if_already_built();
-        let diagram_port_index = self.declare_input_port(input_port);
-        self.connect_input_port(diagram_port_index.clone(), input_port);
+        // Check before declaring so that a failed export leaves the builder unchanged.
+        let input_port_locator = InputPortLocator {
+            system_weak_link: input_port.system_weak_link().clone(),
+            input_port_index: input_port.index().clone(),
+        };
+        self.check_input_not_connected(&input_port_locator, input_port)?;
+        let diagram_port_index = self.try_declare_input_port(input_port)?;
+        self.try_connect_input_port(diagram_port_index.clone(), input_port)?;
 
-        diagram_port_index
+        Ok(diagram_port_index)
     }
 
     pub fn declare_input_port<I>(&mut self, input_port: I) -> InputPortIndex
+    where
+        I: Deref<Target = InputPort<T>>,
+    {
+        self.try_declare_input_port(input_port)
+            .unwrap_or_else(|error| panic!("DiagramBuilder::declare_input_port: {}", error))
+    }
+
+    pub fn try_declare_input_port<I>(&mut self, input_port: I) -> Result<InputPortIndex, AtlasError>
     where
         I: Deref<Target = InputPort<T>>,
     {
@@ -143,64 +197,74 @@ impl<T: AtlasScalar> DiagramBuilder<T> {
             system_weak_link: input_port.system_weak_link().clone(),
             input_port_index: input_port.index().clone(),
         };
-        self.assert_if_system_not_registered(input_port.system_weak_link());
+        self.check_system_registered(input_port.system_weak_link())?;
 
         let input_port_index = InputPortIndex::new(self.diagram_input_data.len());
         self.input_port_names.push(input_port.name().to_string());
         self.diagram_input_data
             .push(ExportedInputPortData { input_port_locator });
 
-        input_port_index
+        Ok(input_port_index)
     }
 
-    pub fn connect_input_port<I>(
+    pub fn connect_input_port<I>(&mut self, diagram_input_port_index: InputPortIndex, input_port: I)
+    where
+        I: Deref<Target = InputPort<T>>,
+    {
+        self.try_connect_input_port(diagram_input_port_index, input_port)
+            .unwrap_or_else(|error| panic!("DiagramBuilder::connect_input_port: {}", error))
+    }
+
+    pub fn try_connect_input_port<I>(
         &mut self,
-        _diagram_input_port_index: InputPortIndex,
+        diagram_input_port_index: InputPortIndex,
         input_port: I,
-    ) where
+    ) -> Result<(), AtlasError>
+    where
         I: Deref<Target = InputPort<T>>,
     {
         let input_port = &*input_port;
-        self.assert_if_already_built();
         let input_port_locator = InputPortLocator {
             system_weak_link: input_port.system_weak_link().clone(),
             input_port_index: input_port.index().clone(),
         };
-        self.assert_if_input_already_connected(&input_port_locator);
-        self.assert_if_system_not_registered(input_port.system_weak_link());
-
-        // TODO: Restore
-        // Check that port types match.
-        // let exported_input_port_data = &self.diagram_input_data[diagram_input_port_index.value()];
-        // let input_port_locator = &exported_input_port_data.input_port_locator;
-        // let diagram_input_port = input_port_locator
-        //     .system_weak_link
-        //     .input_port(input_port_locator.input_port_index.clone());
-        // if input_port.data_type() != diagram_input_port.data_type() {
-        //     panic!(
-        //         "DiagramBuilder::connect_input_port: Cannot mix vector-valued and abstract-valued ports while connecting input port (data type {:?}) of System to input port (data type {:?}) of Diagram",
-        //         input_port.data_type(),
-        //         input_port.data_type(),
-        //     );
-        // }
-
-        // if *input_port.data_type() == PortDataType::AbstractValued {
-        //     let input_port_model = input_port
-        //         .system_weak_link()
-        //         .allocate_input_abstract(input_port);
-        //     let diagram_input_port_model = diagram_input_port
-        //         .system_weak_link()
-        //         .allocate_input_abstract(&diagram_input_port);
-        //     if input_port_model.type_id() != diagram_input_port_model.type_id() {
-        //         panic!(
-        //             "DiagramBuilder::connect_input_port: Mismatched value types while connecting input port (type {:?}) of System to input port (type {:?}) of Diagram",
-        //             input_port_model.type_id(),
-        //             diagram_input_port_model.type_id()
-        //         );
-        //     }
-        // }
+        self.check_input_not_connected(&input_port_locator, input_port)?;
+        self.check_system_registered(input_port.system_weak_link())?;
+
+        // Every input port connected to the same Diagram input must agree with the port that
+        // first defined it.
+        let diagram_input_port_locator =
+            &self.diagram_input_data[diagram_input_port_index.value()].input_port_locator;
+        let diagram_input_system_link = diagram_input_port_locator.system_weak_link.upgrade();
+        let diagram_input_port = diagram_input_system_link
+            .input_port(diagram_input_port_locator.input_port_index.clone());
+        Self::check_vector_compatible(
+            diagram_input_port.system_weak_link().name(),
+            diagram_input_port.name(),
+            diagram_input_port.data_type(),
+            diagram_input_port.size(),
+            input_port,
+        )?;
+        if *input_port.data_type() == PortDataType::AbstractValued {
+            let model_input = input_port
+                .system_weak_link()
+                .allocate_input_abstract(input_port);
+            let model_diagram_input = diagram_input_port
+                .system_weak_link()
+                .allocate_input_abstract(&diagram_input_port);
+            if model_input.type_id() != model_diagram_input.type_id() {
+                return Err(AtlasError::AbstractTypeMismatch {
+                    source_system: diagram_input_port.system_weak_link().name(),
+                    source_port: diagram_input_port.name().to_string(),
+                    destination_system: input_port.system_weak_link().name(),
+                    destination_port: input_port.name().to_string(),
+                });
+            }
+        }
 
         self.input_port_ids.push(input_port_locator.clone());
+
+        Ok(())
     }
 
     pub fn export_output_port<CN, O>(&mut self, output_port: O) -> OutputPortIndex
@@ -208,9 +272,20 @@ impl<T: AtlasScalar> DiagramBuilder<T> {
         CN: Context<T>,
         O: Deref<Target = dyn OutputPort<T, CN = CN>>,
     {
-        self.assert_if_already_built();
+        self.try_export_output_port(output_port)
+            .unwrap_or_else(|error| panic!("DiagramBuilder::export_output_port: {}", error))
+    }
 
+    pub fn try_export_output_port<CN, O>(
+        &mut self,
+        output_port: O,
+    ) -> Result<OutputPortIndex, AtlasError>
+    where
+        CN: Context<T>,
+        O: Deref<Target = dyn OutputPort<T, CN = CN>>,
+    {
         let output_port = &*output_port;
+        self.check_system_registered(&output_port.system_weak_link())?;
         let output_port_index = OutputPortIndex::new(self.output_port_ids.len());
 
         let output_port_locator = OutputPortLocator {
@@ -222,24 +297,30 @@ impl<T: AtlasScalar> DiagramBuilder<T> {
         let port_name = output_port.system_weak_link().name() + "_" + output_port.name();
         self.output_port_names.push(port_name);
 
-        output_port_index
+        Ok(output_port_index)
     }
 
+    // Consumes the builder, so that a builder cannot be built twice.
     pub fn build(self) -> Rc<RefCell<Diagram<T>>> {
-        self.assert_if_already_built();
-        let blueprint = self.compile();
+        self.try_build()
+            .unwrap_or_else(|error| panic!("DiagramBuilder::build: {}", error))
+    }
+
+    pub fn try_build(self) -> Result<Rc<RefCell<Diagram<T>>>, AtlasError> {
+        let blueprint = self.compile()?;
 
-        Diagram::from_blueprint(blueprint)
+        Ok(Diagram::from_blueprint(blueprint))
     }
 
-    fn compile(mut self) -> DiagramBlueprint<T> {
+    fn compile(self) -> Result<DiagramBlueprint<T>, AtlasError> {
         if self.registered_systems.systems.is_empty() {
-            panic!("Cannot compile an empty DiagramBuilder");
+            return Err(AtlasError::EmptyBuilder);
         }
 
+        self.check_no_algebraic_loops()?;
+
         let mut blueprint = DiagramBlueprint::new();
 
-        self.already_built = true;
         blueprint.input_port_ids = self.input_port_ids.clone();
         blueprint.input_port_names = self.input_port_names.clone();
         blueprint.output_port_ids = self.output_port_ids.clone();
@@ -248,24 +329,456 @@ impl<T: AtlasScalar> DiagramBuilder<T> {
         blueprint.system_weak_links = self.system_weak_links.clone();
         blueprint.registered_systems = self.registered_systems;
 
-        blueprint
+        Ok(blueprint)
+    }
+
+    // An algebraic loop is a cycle of connections where each subsystem output depends directly on
+    // the input fed by the previous one. Output ports are linked to the output ports they feed
+    // directly, and the graph is searched depth-first for a cycle.
+    fn check_no_algebraic_loops(&self) -> Result<(), AtlasError> {
+        let mut edges: HashMap<OutputPortLocator<T>, Vec<(InputPortLocator<T>, OutputPortIndex)>> =
+            HashMap::new();
+        let subsystem_feedthroughs: HashMap<_, _> = self
+            .system_weak_links
+            .iter()
+            .map(|system_weak_link| {
+                (
+                    system_weak_link.clone(),
+                    system_weak_link.get_direct_feedthroughs(),
+                )
+            })
+            .collect();
+        for (input_port_locator, output_port_locator) in self.connection_map.iter() {
+            for (input_port_index, output_port_index) in
+                subsystem_feedthroughs[&input_port_locator.system_weak_link].iter()
+            {
+                if *input_port_index == input_port_locator.input_port_index {
+                    edges
+                        .entry(output_port_locator.clone())
+                        .or_default()
+                        .push((input_port_locator.clone(), output_port_index.clone()));
+                }
+            }
+        }
+
+        let mut finished: Vec<OutputPortLocator<T>> = vec![];
+        for system_weak_link in self.system_weak_links.iter() {
+            for i in 0..system_weak_link.num_output_ports() {
+                let output_port_locator = OutputPortLocator {
+                    system_weak_link: system_weak_link.clone(),
+                    output_port_index: OutputPortIndex::new(i),
+                };
+                let mut path = vec![];
+                if let Some(cycle) =
+                    Self::find_cycle(&output_port_locator, &edges, &mut path, &mut finished)
+                {
+                    return Err(AtlasError::AlgebraicLoop { cycle });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // The path holds the output ports being visited, each with the input port it feeds next.
+    fn find_cycle(
+        output_port_locator: &OutputPortLocator<T>,
+        edges: &HashMap<OutputPortLocator<T>, Vec<(InputPortLocator<T>, OutputPortIndex)>>,
+        path: &mut Vec<(OutputPortLocator<T>, Option<InputPortLocator<T>>)>,
+        finished: &mut Vec<OutputPortLocator<T>>,
+    ) -> Option<Vec<(String, String)>> {
+        if let Some(start) = path
+            .iter()
+            .position(|(locator, _)| locator == output_port_locator)
+        {
+            let mut cycle = vec![];
+            for (output_port_locator, input_port_locator) in path[start..].iter() {
+                let input_port_locator = input_port_locator.as_ref().unwrap();
+                cycle.push((
+                    output_port_locator.system_weak_link.name(),
+                    output_port_locator
+                        .system_weak_link
+                        .output_port_name(&output_port_locator.output_port_index),
+                ));
+                cycle.push((
+                    input_port_locator.system_weak_link.name(),
+                    input_port_locator
+                        .system_weak_link
+                        .input_port_name(&input_port_locator.input_port_index),
+                ));
+            }
+            return Some(cycle);
+        }
+        if finished.contains(output_port_locator) {
+            return None;
+        }
+
+        path.push((output_port_locator.clone(), None));
+        for (input_port_locator, output_port_index) in
+            edges.get(output_port_locator).into_iter().flatten()
+        {
+            path.last_mut().unwrap().1 = Some(input_port_locator.clone());
+            let next_output_port_locator = OutputPortLocator {
+                system_weak_link: input_port_locator.system_weak_link.clone(),
+                output_port_index: output_port_index.clone(),
+            };
+            let cycle = Self::find_cycle(&next_output_port_locator, edges, path, finished);
+            if cycle.is_some() {
+                return cycle;
+            }
+        }
+        path.pop();
+        finished.push(output_port_locator.clone());
+        None
     }
 
-    fn assert_if_already_built(&self) {
-        if self.already_built {
-            panic!("DiagramBuilder already built");
+    fn check_input_not_connected(
+        &self,
+        input_port_locator: &InputPortLocator<T>,
+        input_port: &InputPort<T>,
+    ) -> Result<(), AtlasError> {
+        // An input port is either connected to an output port or exported, but only once.
+        if self.connection_map.contains_key(input_port_locator)
+            || self.input_port_ids.contains(input_port_locator)
+        {
+            return Err(AtlasError::AlreadyConnected {
+                input_system: input_port.system_weak_link().name(),
+                input_port: input_port.name().to_string(),
+            });
         }
+        Ok(())
     }
 
-    fn assert_if_input_already_connected(&self, input_port_locator: &InputPortLocator<T>) {
-        if self.connection_map.contains_key(input_port_locator) {
-            panic!(" Input port is already connected");
+    // Checks that a port with the given data type and size can feed input_port. Abstract value
+    // types are checked separately, since allocating their models differs between ports.
+    fn check_vector_compatible(
+        source_system: String,
+        source_port: &str,
+        source_data_type: &PortDataType,
+        source_size: usize,
+        input_port: &InputPort<T>,
+    ) -> Result<(), AtlasError> {
+        if source_data_type != input_port.data_type() {
+            return Err(AtlasError::PortDataTypeMismatch {
+                source_system,
+                source_port: source_port.to_string(),
+                source_data_type: source_data_type.clone(),
+                destination_system: input_port.system_weak_link().name(),
+                destination_port: input_port.name().to_string(),
+                destination_data_type: input_port.data_type().clone(),
+            });
+        }
+        if *source_data_type == PortDataType::VectorValued && source_size != input_port.size() {
+            return Err(AtlasError::PortSizeMismatch {
+                source_system,
+                source_port: source_port.to_string(),
+                source_size,
+                destination_system: input_port.system_weak_link().name(),
+                destination_port: input_port.name().to_string(),
+                destination_size: input_port.size(),
+            });
         }
+        Ok(())
     }
 
-    fn assert_if_system_not_registered(&self, system_weak_link: &SystemWeakLink<T>) {
+    fn check_system_registered(
+        &self,
+        system_weak_link: &SystemWeakLink<T>,
+    ) -> Result<(), AtlasError> {
         if !self.system_weak_links.contains(system_weak_link) {
-            panic!("System has not been registered to this DiagramBuilder");
+            return Err(AtlasError::UnknownSystem {
+                system: system_weak_link.name(),
+            });
         }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    extern crate nalgebra as na;
+
+    use atlas_derives::leaf_system;
+
+    use crate::common::value::Value;
+    use crate::systems::controllers::pid_controller::PIDController;
+    use crate::systems::framework::diagram::SystemLinkExt;
+    use crate::systems::framework::system_base::SystemBase;
+    use crate::systems::primitives::adder::Adder;
+    use crate::systems::primitives::constant_value_source::ConstantValueSource;
+
+    // A sink with abstract input ports of two value types.
+    #[leaf_system]
+    struct AbstractSink<T: AtlasScalar> {
+        #[input(abstract, model = "String::new()")]
+        label: InputPortIndex,
+        #[input(abstract, model = "0usize")]
+        count: InputPortIndex,
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut diagram_builder = DiagramBuilder::<f64>::new();
+        let adder1 = Adder::<f64>::new(2, 1);
+        let adder2 = Adder::<f64>::new(2, 1);
+        let unregistered_adder = Adder::<f64>::new(2, 1);
+        diagram_builder.add_leaf_system(&adder1);
+        diagram_builder.add_leaf_system(&adder2);
+
+        assert_eq!(
+            diagram_builder
+                .try_export_input_port(unregistered_adder.input_port(InputPortIndex::new(0))),
+            Err(AtlasError::UnknownSystem {
+                system: "adder".to_string()
+            })
+        );
+
+        assert!(diagram_builder
+            .try_connect(
+                adder1.output_port_mut(OutputPortIndex::new(0)),
+                adder2.input_port(InputPortIndex::new(0)),
+            )
+            .is_ok());
+        let error = diagram_builder
+            .try_export_input_port(adder2.input_port(InputPortIndex::new(0)))
+            .unwrap_err();
+        assert_eq!(
+            error,
+            AtlasError::AlreadyConnected {
+                input_system: "adder".to_string(),
+                input_port: "input_0".to_string(),
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "Input port input_0 of System adder is already connected"
+        );
+
+        // The failed export left the builder usable.
+        assert!(diagram_builder
+            .try_export_input_port(adder2.input_port(InputPortIndex::new(1)))
+            .is_ok());
+        assert!(diagram_builder.try_build().is_ok());
+    }
+
+    #[test]
+    fn test_port_size_mismatch() {
+        let mut diagram_builder = DiagramBuilder::<f64>::new();
+        let adder = Adder::<f64>::new(2, 3);
+        let narrow_adder = Adder::<f64>::new(2, 2);
+        let pid_controller = PIDController::<f64>::new(
+            na::DVector::from_element(1, 1.0),
+            na::DVector::from_element(1, 1.0),
+            na::DVector::from_element(1, 1.0),
+        );
+        diagram_builder.add_leaf_system(&adder);
+        diagram_builder.add_leaf_system(&narrow_adder);
+        diagram_builder.add_leaf_system(&pid_controller);
+
+        let error = diagram_builder
+            .try_connect(
+                adder.output_port_mut(OutputPortIndex::new(0)),
+                pid_controller.input_port(InputPortIndex::new(0)),
+            )
+            .unwrap_err();
+        assert_eq!(
+            error,
+            AtlasError::PortSizeMismatch {
+                source_system: "adder".to_string(),
+                source_port: "sum".to_string(),
+                source_size: 3,
+                destination_system: "pid_controller".to_string(),
+                destination_port: "estimated_state".to_string(),
+                destination_size: 2,
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "Mismatched vector sizes while connecting port sum of System adder (size 3) to input port estimated_state of System pid_controller (size 2)"
+        );
+        assert!(diagram_builder
+            .try_connect(
+                narrow_adder.output_port_mut(OutputPortIndex::new(0)),
+                pid_controller.input_port(InputPortIndex::new(0)),
+            )
+            .is_ok());
+
+        // Input ports sharing a Diagram input must match the port that was exported first.
+        let diagram_input_port_index = diagram_builder
+            .try_export_input_port(adder.input_port(InputPortIndex::new(0)))
+            .unwrap();
+        assert!(matches!(
+            diagram_builder.try_connect_input_port(
+                diagram_input_port_index.clone(),
+                narrow_adder.input_port(InputPortIndex::new(0)),
+            ),
+            Err(AtlasError::PortSizeMismatch {
+                source_size: 3,
+                destination_size: 2,
+                ..
+            })
+        ));
+        assert!(diagram_builder
+            .try_connect_input_port(
+                diagram_input_port_index,
+                adder.input_port(InputPortIndex::new(1)),
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn test_port_data_type_mismatch() {
+        let mut diagram_builder = DiagramBuilder::<f64>::new();
+        let adder = Adder::<f64>::new(2, 1);
+        let abstract_sink = AbstractSink::<f64>::new();
+        let label = abstract_sink.borrow().label.clone();
+        diagram_builder.add_leaf_system(&adder);
+        diagram_builder.add_leaf_system(&abstract_sink);
+
+        let error = diagram_builder
+            .try_connect(
+                adder.output_port_mut(OutputPortIndex::new(0)),
+                abstract_sink.input_port(label.clone()),
+            )
+            .unwrap_err();
+        assert_eq!(
+            error,
+            AtlasError::PortDataTypeMismatch {
+                source_system: "adder".to_string(),
+                source_port: "sum".to_string(),
+                source_data_type: PortDataType::VectorValued,
+                destination_system: "abstract_sink".to_string(),
+                destination_port: "label".to_string(),
+                destination_data_type: PortDataType::AbstractValued,
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "Cannot mix vector-valued and abstract-valued ports while connecting port sum of System adder (VectorValued) to input port label of System abstract_sink (AbstractValued)"
+        );
+
+        // Input ports sharing a Diagram input must have the data type of the port exported first.
+        let diagram_input_port_index = diagram_builder
+            .try_export_input_port(abstract_sink.input_port(label))
+            .unwrap();
+        assert!(matches!(
+            diagram_builder.try_connect_input_port(
+                diagram_input_port_index,
+                adder.input_port(InputPortIndex::new(0)),
+            ),
+            Err(AtlasError::PortDataTypeMismatch {
+                source_data_type: PortDataType::AbstractValued,
+                destination_data_type: PortDataType::VectorValued,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_abstract_type_mismatch() {
+        let mut diagram_builder = DiagramBuilder::<f64>::new();
+        let count_source = ConstantValueSource::<f64>::new(&Value::new(1usize));
+        let abstract_sink1 = AbstractSink::<f64>::new();
+        let abstract_sink2 = AbstractSink::<f64>::new();
+        let (label, count) = {
+            let abstract_sink = abstract_sink1.borrow();
+            (abstract_sink.label.clone(), abstract_sink.count.clone())
+        };
+        diagram_builder.add_leaf_system(&count_source);
+        diagram_builder.add_leaf_system(&abstract_sink1);
+        diagram_builder.add_leaf_system(&abstract_sink2);
+
+        let error = diagram_builder
+            .try_connect(
+                count_source.output_port_mut(OutputPortIndex::new(0)),
+                abstract_sink1.input_port(label.clone()),
+            )
+            .unwrap_err();
+        assert_eq!(
+            error,
+            AtlasError::AbstractTypeMismatch {
+                source_system: "constant_value_source".to_string(),
+                source_port: "y".to_string(),
+                destination_system: "abstract_sink".to_string(),
+                destination_port: "label".to_string(),
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "Mismatched value types while connecting port y of System constant_value_source to input port label of System abstract_sink"
+        );
+        assert!(diagram_builder
+            .try_connect(
+                count_source.output_port_mut(OutputPortIndex::new(0)),
+                abstract_sink1.input_port(count.clone()),
+            )
+            .is_ok());
+
+        // Input ports sharing a Diagram input must have the value type of the port exported first.
+        let diagram_input_port_index = diagram_builder
+            .try_export_input_port(abstract_sink1.input_port(label.clone()))
+            .unwrap();
+        assert_eq!(
+            diagram_builder.try_connect_input_port(
+                diagram_input_port_index.clone(),
+                abstract_sink2.input_port(count),
+            ),
+            Err(AtlasError::AbstractTypeMismatch {
+                source_system: "abstract_sink".to_string(),
+                source_port: "label".to_string(),
+                destination_system: "abstract_sink".to_string(),
+                destination_port: "count".to_string(),
+            })
+        );
+        assert!(diagram_builder
+            .try_connect_input_port(diagram_input_port_index, abstract_sink2.input_port(label))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_algebraic_loop() {
+        let mut diagram_builder = DiagramBuilder::<f64>::new();
+        let adder1 = Adder::<f64>::new(2, 1);
+        let adder2 = Adder::<f64>::new(1, 1);
+        adder1.borrow_mut().set_name("adder1".to_string());
+        adder2.borrow_mut().set_name("adder2".to_string());
+        diagram_builder.add_leaf_system(&adder1);
+        diagram_builder.add_leaf_system(&adder2);
+
+        diagram_builder.export_input_port(adder1.input_port(InputPortIndex::new(0)));
+        diagram_builder.connect(
+            adder1.output_port_mut(OutputPortIndex::new(0)),
+            adder2.input_port(InputPortIndex::new(0)),
+        );
+        diagram_builder.connect(
+            adder2.output_port_mut(OutputPortIndex::new(0)),
+            adder1.input_port(InputPortIndex::new(1)),
+        );
+
+        let error = diagram_builder.try_build().err().unwrap();
+        assert_eq!(
+            error,
+            AtlasError::AlgebraicLoop {
+                cycle: vec![
+                    ("adder1".to_string(), "sum".to_string()),
+                    ("adder2".to_string(), "input_0".to_string()),
+                    ("adder2".to_string(), "sum".to_string()),
+                    ("adder1".to_string(), "input_1".to_string()),
+                ]
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "Algebraic loop detected through the ports: sum of System adder1 -> input_0 of System adder2 -> sum of System adder2 -> input_1 of System adder1"
+        );
+    }
+
+    #[test]
+    fn test_empty_builder() {
+        let diagram_builder = DiagramBuilder::<f64>::new();
+        assert_eq!(
+            diagram_builder.try_build().err(),
+            Some(AtlasError::EmptyBuilder)
+        );
     }
 }
@@ -3,14 +3,17 @@ use std::rc::Rc;
 
 use crate::common::atlas_scalar::AtlasScalar;
 use crate::common::value::AbstractValue;
-use crate::systems::framework::cache::Cache;
 use crate::systems::framework::context::Context;
 use crate::systems::framework::context_base::ContextBase;
 use crate::systems::framework::continuous_state::ContinuousState;
-use crate::systems::framework::diagram_state::DiagramState;
+use crate::systems::framework::dependency_tracker::DependencyGraph;
+use crate::systems::framework::diagram_state::{DiagramState, StatePtr};
 use crate::systems::framework::fixed_input_port_value::FixedInputPortValue;
-use crate::systems::framework::framework_common::{SubsystemIndex, SystemId};
+use crate::systems::framework::framework_common::{
+    DependencyTicket, InputPortIndex, SubsystemIndex, SystemId,
+};
 use crate::systems::framework::leaf_context::LeafContext;
+use crate::systems::framework::parameters::Parameters;
 use crate::systems::framework::state::State;
 use crate::systems::framework::vector_base::VectorBase;
 
@@ -28,6 +31,13 @@ impl<T: AtlasScalar> ContextLink<T> {
         }
     }
 
+    pub fn set_time(&self, time: T) {
+        match self {
+            ContextLink::LeafContextLink(ctx) => ctx.borrow_mut().set_time(time),
+            ContextLink::DiagramContextLink(ctx) => ctx.borrow_mut().set_time(time),
+        }
+    }
+
     pub fn as_leaf_context(&self) -> Option<Rc<RefCell<LeafContext<T>>>> {
         match self {
             ContextLink::LeafContextLink(ctx) => Some(ctx.clone()),
@@ -46,10 +56,12 @@ impl<T: AtlasScalar> ContextLink<T> {
 #[derive(Default)]
 pub struct DiagramContext<T: AtlasScalar> {
     system_id: SystemId,
+    system_name: String,
     parent: Option<Rc<RefCell<dyn ContextBase>>>,
-    cache: RefCell<Cache>,
+    graph: Rc<DependencyGraph>,
     time: T,
     state: DiagramState<T>,
+    parameters: Parameters<T>,
     input_port_values: Vec<Option<FixedInputPortValue>>,
     is_context_base_initialized: bool,
     contexts: Vec<Option<ContextLink<T>>>,
@@ -70,6 +82,12 @@ impl<T: AtlasScalar> ContextBase for DiagramContext<T> {
     fn system_id(&self) -> &SystemId {
         &self.system_id
     }
+    fn set_system_name(&mut self, system_name: String) {
+        self.system_name = system_name;
+    }
+    fn system_name(&self) -> &str {
+        &self.system_name
+    }
 
     fn parent_base(&self) -> &Option<Rc<RefCell<dyn ContextBase>>> {
         &self.parent
@@ -79,8 +97,8 @@ impl<T: AtlasScalar> ContextBase for DiagramContext<T> {
         &mut self.parent
     }
 
-    fn cache(&self) -> &RefCell<Cache> {
-        &self.cache
+    fn graph(&self) -> &Rc<DependencyGraph> {
+        &self.graph
     }
 
     fn input_port_values(&mut self) -> &mut Vec<Option<FixedInputPortValue>> {
@@ -97,6 +115,7 @@ impl<T: AtlasScalar> ContextBase for DiagramContext<T> {
         value: &dyn AbstractValue,
     ) -> Option<&FixedInputPortValue> {
         self.input_port_values[index] = Some(FixedInputPortValue::new(value.clone_box()));
+        self.note_value_change(&DependencyTicket::InputPort(InputPortIndex::new(index)));
 
         self.fixed_input_port_value(index)
     }
@@ -106,12 +125,23 @@ impl<T: AtlasScalar> ContextBase for DiagramContext<T> {
     }
 
     fn fixed_input_port_value_mut(&mut self, index: usize) -> Option<&mut FixedInputPortValue> {
+        self.note_value_change(&DependencyTicket::InputPort(InputPortIndex::new(index)));
         self.input_port_values[index].as_mut()
     }
 
     fn is_context_base_initialized_mut(&mut self) -> &mut bool {
         &mut self.is_context_base_initialized
     }
+
+    fn mark_all_cache_entries_out_of_date(&self) {
+        self.cache().borrow_mut().mark_all_out_of_date();
+        for context in self.contexts.iter().flatten() {
+            context
+                .as_context_base()
+                .borrow()
+                .mark_all_cache_entries_out_of_date();
+        }
+    }
 }
 
 impl<T: AtlasScalar> Context<T> for DiagramContext<T> {
@@ -121,14 +151,32 @@ impl<T: AtlasScalar> Context<T> for DiagramContext<T> {
         &self.time
     }
 
+    fn set_time(&mut self, time: T) {
+        self.note_value_change(&DependencyTicket::Time);
+        for context in self.contexts.iter().flatten() {
+            context.set_time(time.clone());
+        }
+        self.time = time;
+    }
+
     fn state(&self) -> &Self::S {
         &self.state
     }
 
     fn state_mut(&mut self) -> &mut Self::S {
+        self.note_all_state_changed();
         &mut self.state
     }
 
+    fn parameters(&self) -> &Parameters<T> {
+        &self.parameters
+    }
+
+    fn parameters_mut(&mut self) -> &mut Parameters<T> {
+        self.note_value_change(&DependencyTicket::Parameters);
+        &mut self.parameters
+    }
+
     fn init_continuous_state(&mut self, continuous_state: Box<<Self::S as State<T>>::CS>) {
         self.set_continuous_state(continuous_state);
     }
@@ -142,6 +190,7 @@ impl<T: AtlasScalar> Context<T> for DiagramContext<T> {
     }
 
     fn continuous_state_mut(&mut self) -> &mut <Self::S as State<T>>::CS {
+        self.note_value_change(&DependencyTicket::ContinuousState);
         self.state.continuous_state_mut()
     }
 
@@ -150,6 +199,7 @@ impl<T: AtlasScalar> Context<T> for DiagramContext<T> {
     }
 
     fn continuous_state_vector_mut(&mut self) -> &mut dyn VectorBase<T, Output = T> {
+        self.note_value_change(&DependencyTicket::ContinuousState);
         self.state.continuous_state_mut().vector_mut()
     }
 
@@ -166,10 +216,12 @@ impl<T: AtlasScalar> DiagramContext<T> {
     pub fn new(num_subsystems: usize) -> Self {
         Self {
             system_id: SystemId::default(),
+            system_name: String::new(),
             parent: None,
-            cache: RefCell::new(Cache::default()),
+            graph: Rc::default(),
             time: T::default(),
             state: DiagramState::default(),
+            parameters: Parameters::default(),
             input_port_values: vec![],
             is_context_base_initialized: false,
             contexts: vec![None; num_subsystems],
@@ -180,6 +232,44 @@ impl<T: AtlasScalar> DiagramContext<T> {
         let index = subsystem_index.value();
         self.contexts[index].clone().unwrap()
     }
+
+    // Builds the DiagramState from the states of the subcontexts, which must all have been added.
+    pub fn make_state(&mut self) {
+        let mut state = DiagramState::<T>::new(self.contexts.len());
+        for (index, context) in self.contexts.iter().enumerate() {
+            let substate = match context.as_ref().unwrap() {
+                ContextLink::LeafContextLink(ctx) => {
+                    StatePtr::LeafStatePtr(ctx.borrow_mut().state_mut() as *mut _)
+                }
+                ContextLink::DiagramContextLink(ctx) => {
+                    StatePtr::LeafDiagramPtr(ctx.borrow_mut().state_mut() as *mut _)
+                }
+            };
+            state.set_substate(index, substate);
+        }
+        state.finalize();
+        state
+            .continuous_state_mut()
+            .set_system_id(self.system_id.clone());
+        self.state = state;
+    }
+
+    // Builds the Diagram's parameters as views of those of the subcontexts, which must all have
+    // been added.
+    pub fn make_parameters(&mut self) {
+        let mut parameters = Parameters::<T>::default();
+        for context in self.contexts.iter() {
+            match context.as_ref().unwrap() {
+                ContextLink::LeafContextLink(ctx) => {
+                    parameters.append_views(ctx.borrow_mut().parameters_mut())
+                }
+                ContextLink::DiagramContextLink(ctx) => {
+                    parameters.append_views(ctx.borrow_mut().parameters_mut())
+                }
+            }
+        }
+        self.parameters = parameters;
+    }
 }
 
 pub trait DiagramContextExt<T: AtlasScalar> {
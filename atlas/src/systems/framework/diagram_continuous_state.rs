@@ -1,9 +1,12 @@
 extern crate nalgebra as na;
 
 use crate::common::atlas_scalar::AtlasScalar;
+use crate::systems::framework::basic_vector::BasicVector;
 use crate::systems::framework::continuous_state::ContinuousState;
 use crate::systems::framework::framework_common::SystemId;
 use crate::systems::framework::leaf_continuous_state::LeafContinuousState;
+use crate::systems::framework::subvector::Subvector;
+use crate::systems::framework::supervector::Supervector;
 use crate::systems::framework::vector_base::VectorBase;
 
 pub enum ContinuousStatePtr<T: AtlasScalar> {
@@ -12,8 +15,7 @@ pub enum ContinuousStatePtr<T: AtlasScalar> {
 }
 
 impl<T: AtlasScalar> ContinuousStatePtr<T> {
-    #[allow(dead_code)]
-    fn num_q(&self) -> usize {
+    pub fn num_q(&self) -> usize {
         match self {
             ContinuousStatePtr::LeafContinuousStatePtr(ptr) => unsafe {
                 ptr.as_ref().unwrap().num_q()
@@ -24,8 +26,7 @@ impl<T: AtlasScalar> ContinuousStatePtr<T> {
         }
     }
 
-    #[allow(dead_code)]
-    fn num_v(&self) -> usize {
+    pub fn num_v(&self) -> usize {
         match self {
             ContinuousStatePtr::LeafContinuousStatePtr(ptr) => unsafe {
                 ptr.as_ref().unwrap().num_v()
@@ -36,8 +37,7 @@ impl<T: AtlasScalar> ContinuousStatePtr<T> {
         }
     }
 
-    #[allow(dead_code)]
-    fn num_z(&self) -> usize {
+    pub fn num_z(&self) -> usize {
         match self {
             ContinuousStatePtr::LeafContinuousStatePtr(ptr) => unsafe {
                 ptr.as_ref().unwrap().num_z()
@@ -48,8 +48,7 @@ impl<T: AtlasScalar> ContinuousStatePtr<T> {
         }
     }
 
-    #[allow(dead_code)]
-    fn size(&self) -> usize {
+    pub fn size(&self) -> usize {
         match self {
             ContinuousStatePtr::LeafContinuousStatePtr(ptr) => unsafe {
                 ptr.as_ref().unwrap().size()
@@ -60,8 +59,7 @@ impl<T: AtlasScalar> ContinuousStatePtr<T> {
         }
     }
 
-    #[allow(dead_code)]
-    fn system_id(&self) -> &SystemId {
+    pub fn system_id(&self) -> &SystemId {
         match self {
             ContinuousStatePtr::LeafContinuousStatePtr(ptr) => unsafe {
                 ptr.as_ref().unwrap().system_id()
@@ -72,8 +70,7 @@ impl<T: AtlasScalar> ContinuousStatePtr<T> {
         }
     }
 
-    #[allow(dead_code)]
-    fn set_system_id(&mut self, system_id: SystemId) {
+    pub fn set_system_id(&mut self, system_id: SystemId) {
         match self {
             ContinuousStatePtr::LeafContinuousStatePtr(ptr) => unsafe {
                 ptr.as_mut().unwrap().set_system_id(system_id)
@@ -84,8 +81,7 @@ impl<T: AtlasScalar> ContinuousStatePtr<T> {
         }
     }
 
-    #[allow(dead_code)]
-    fn set_from_vector(&mut self, value: &na::DVector<T>) {
+    pub fn set_from_vector(&mut self, value: &na::DVector<T>) {
         match self {
             ContinuousStatePtr::LeafContinuousStatePtr(ptr) => unsafe {
                 ptr.as_mut().unwrap().set_from_vector(value)
@@ -96,8 +92,7 @@ impl<T: AtlasScalar> ContinuousStatePtr<T> {
         }
     }
 
-    #[allow(dead_code)]
-    fn vector(&self) -> &dyn VectorBase<T, Output = T> {
+    pub fn vector(&self) -> &dyn VectorBase<T, Output = T> {
         match self {
             ContinuousStatePtr::LeafContinuousStatePtr(ptr) => unsafe {
                 ptr.as_ref().unwrap().vector()
@@ -108,8 +103,7 @@ impl<T: AtlasScalar> ContinuousStatePtr<T> {
         }
     }
 
-    #[allow(dead_code)]
-    fn vector_mut(&mut self) -> &mut dyn VectorBase<T, Output = T> {
+    pub fn vector_mut(&mut self) -> &mut dyn VectorBase<T, Output = T> {
         match self {
             ContinuousStatePtr::LeafContinuousStatePtr(ptr) => unsafe {
                 ptr.as_mut().unwrap().vector_mut()
@@ -119,14 +113,64 @@ impl<T: AtlasScalar> ContinuousStatePtr<T> {
             },
         }
     }
+
+    pub fn as_leaf_continuous_state_mut(&mut self) -> Option<&mut LeafContinuousState<T>> {
+        match self {
+            ContinuousStatePtr::LeafContinuousStatePtr(ptr) => unsafe { ptr.as_mut() },
+            ContinuousStatePtr::DiagramContinuousStatePtr(_) => None,
+        }
+    }
+
+    pub fn as_diagram_continuous_state_mut(&mut self) -> Option<&mut DiagramContinuousState<T>> {
+        match self {
+            ContinuousStatePtr::LeafContinuousStatePtr(_) => None,
+            ContinuousStatePtr::DiagramContinuousStatePtr(ptr) => unsafe { ptr.as_mut() },
+        }
+    }
+
+    fn bind_to_storage(&mut self, q: Subvector<T>, v: Subvector<T>, z: Subvector<T>) {
+        match self {
+            ContinuousStatePtr::LeafContinuousStatePtr(ptr) => unsafe {
+                ptr.as_mut().unwrap().bind_to_storage(q, v, z)
+            },
+            ContinuousStatePtr::DiagramContinuousStatePtr(ptr) => unsafe {
+                ptr.as_mut().unwrap().bind_to_storage(q, v, z)
+            },
+        }
+    }
+}
+
+// Continuous states owned by a DiagramContinuousState, e.g. the time derivatives of a Diagram.
+pub enum OwnedContinuousState<T: AtlasScalar> {
+    LeafContinuousState(Box<LeafContinuousState<T>>),
+    DiagramContinuousState(Box<DiagramContinuousState<T>>),
 }
 
+impl<T: AtlasScalar> OwnedContinuousState<T> {
+    fn continuous_state_ptr(&mut self) -> ContinuousStatePtr<T> {
+        match self {
+            OwnedContinuousState::LeafContinuousState(continuous_state) => {
+                ContinuousStatePtr::LeafContinuousStatePtr(
+                    continuous_state.as_mut() as *mut LeafContinuousState<T>
+                )
+            }
+            OwnedContinuousState::DiagramContinuousState(continuous_state) => {
+                ContinuousStatePtr::DiagramContinuousStatePtr(
+                    continuous_state.as_mut() as *mut DiagramContinuousState<T>
+                )
+            }
+        }
+    }
+}
+
+// The continuous state of a Diagram. The q, v and z of every substate are stitched together so
+// that the Diagram's state is ordered [q_all, v_all, z_all] and the substates view their portions
+// of it in place.
 #[derive(Default)]
 pub struct DiagramContinuousState<T: AtlasScalar> {
-    #[allow(dead_code)]
     state: Box<dyn VectorBase<T, Output = T>>,
-    #[allow(dead_code)]
     substates: Vec<ContinuousStatePtr<T>>,
+    owned_substates: Vec<OwnedContinuousState<T>>,
     num_q: usize,
     num_v: usize,
     num_z: usize,
@@ -134,33 +178,78 @@ pub struct DiagramContinuousState<T: AtlasScalar> {
 }
 
 impl<T: AtlasScalar> DiagramContinuousState<T> {
-    pub fn new(_substates: Vec<ContinuousStatePtr<T>>) -> Self {
-        todo!()
-        // let state = Box::new(BasicVector
-        // DiagramContinuousState::<T> {
-        //     substates,
-        //     state:
-        //     num_q: 0,
-        //     num_v: 0,
-        //     num_z: 0,
-        //     system_id: SystemId::default(),
-        // }
-    }
-
-    #[allow(dead_code)]
-    fn num_substates(&self) -> usize {
+    // The substates must outlive the DiagramContinuousState.
+    pub fn new(substates: Vec<ContinuousStatePtr<T>>) -> Self {
+        let num_q = substates.iter().map(|substate| substate.num_q()).sum();
+        let num_v = substates.iter().map(|substate| substate.num_v()).sum();
+        let num_z = substates.iter().map(|substate| substate.num_z()).sum();
+
+        let mut state: Box<dyn VectorBase<T, Output = T>> =
+            Box::new(BasicVector::<T>::zeros(num_q + num_v + num_z));
+        let q = state.subvector_mut(0, num_q);
+        let v = state.subvector_mut(num_q, num_v);
+        let z = state.subvector_mut(num_q + num_v, num_z);
+
+        let mut continuous_state = DiagramContinuousState::<T> {
+            state,
+            substates,
+            owned_substates: vec![],
+            num_q,
+            num_v,
+            num_z,
+            system_id: SystemId::default(),
+        };
+        continuous_state.bind_substates(&q, &v, &z);
+        continuous_state
+    }
+
+    pub fn from_owned(mut owned_substates: Vec<OwnedContinuousState<T>>) -> Self {
+        let substates = owned_substates
+            .iter_mut()
+            .map(|substate| substate.continuous_state_ptr())
+            .collect();
+        let mut continuous_state = DiagramContinuousState::<T>::new(substates);
+        continuous_state.owned_substates = owned_substates;
+        continuous_state
+    }
+
+    pub fn num_substates(&self) -> usize {
         self.substates.len()
     }
 
-    #[allow(dead_code)]
-    fn substate(&self, index: usize) -> &ContinuousStatePtr<T> {
+    pub fn substate(&self, index: usize) -> &ContinuousStatePtr<T> {
         &self.substates[index]
     }
 
-    #[allow(dead_code)]
-    fn substate_mut(&mut self, index: usize) -> &mut ContinuousStatePtr<T> {
+    pub fn substate_mut(&mut self, index: usize) -> &mut ContinuousStatePtr<T> {
         &mut self.substates[index]
     }
+
+    // Moves the storage of this state and its substates into q, v and z, which view the
+    // corresponding partitions of an enclosing Diagram's state.
+    pub fn bind_to_storage(&mut self, q: Subvector<T>, v: Subvector<T>, z: Subvector<T>) {
+        assert_eq!(q.size(), self.num_q);
+        assert_eq!(v.size(), self.num_v);
+        assert_eq!(z.size(), self.num_z);
+
+        self.bind_substates(&q, &v, &z);
+        self.state = Box::new(Supervector::new(vec![q, v, z]));
+    }
+
+    fn bind_substates(&mut self, q: &Subvector<T>, v: &Subvector<T>, z: &Subvector<T>) {
+        let (mut q_start, mut v_start, mut z_start) = (0, 0, 0);
+        for substate in self.substates.iter_mut() {
+            let (num_q, num_v, num_z) = (substate.num_q(), substate.num_v(), substate.num_z());
+            substate.bind_to_storage(
+                q.subvector(q_start, num_q),
+                v.subvector(v_start, num_v),
+                z.subvector(z_start, num_z),
+            );
+            q_start += num_q;
+            v_start += num_v;
+            z_start += num_z;
+        }
+    }
 }
 
 impl<T: AtlasScalar> ContinuousState<T> for DiagramContinuousState<T> {
@@ -188,15 +277,16 @@ impl<T: AtlasScalar> ContinuousState<T> for DiagramContinuousState<T> {
         self.system_id = system_id;
     }
 
-    fn set_from_vector(&mut self, _value: &na::DVector<T>) {
-        todo!()
+    fn set_from_vector(&mut self, value: &na::DVector<T>) {
+        assert_eq!(value.len(), self.size());
+        self.state.set_from_vector(value);
     }
 
     fn vector(&self) -> &dyn VectorBase<T, Output = T> {
-        todo!()
+        self.state.as_ref()
     }
 
     fn vector_mut(&mut self) -> &mut dyn VectorBase<T, Output = T> {
-        todo!()
+        self.state.as_mut()
     }
 }
@@ -62,13 +62,13 @@ impl<T: AtlasScalar> OutputPort<T> for DiagramOutputPort<T> {
                 .unwrap()
                 .borrow()
                 .output_port(&self.output_port_index)
-                .eval_abstract(&*subcontext.as_leaf_context().unwrap().borrow_mut()),
+                .eval_abstract(&*subcontext.as_leaf_context().unwrap().borrow()),
             SystemWeakLink::DiagramWeakLink(diagram_system_weak_link) => diagram_system_weak_link
                 .upgrade()
                 .unwrap()
                 .borrow()
                 .output_port(&self.output_port_index)
-                .eval_abstract(&*subcontext.as_diagram_context().unwrap().borrow_mut()),
+                .eval_abstract(&*subcontext.as_diagram_context().unwrap().borrow()),
         }
     }
 
@@ -1,7 +1,10 @@
 extern crate nalgebra as na;
 
 use crate::common::atlas_scalar::AtlasScalar;
+use crate::systems::framework::abstract_values::AbstractValues;
+use crate::systems::framework::continuous_state::ContinuousState;
 use crate::systems::framework::diagram_continuous_state::ContinuousStatePtr;
+use crate::systems::framework::discrete_values::DiscreteValues;
 use crate::systems::framework::leaf_state::LeafState;
 use crate::systems::framework::state::State;
 
@@ -14,7 +17,6 @@ pub enum StatePtr<T: AtlasScalar> {
 }
 
 impl<T: AtlasScalar> StatePtr<T> {
-    #[allow(dead_code)]
     fn continuous_state_ptr(&self) -> ContinuousStatePtr<T> {
         match self {
             StatePtr::LeafStatePtr(ptr) => unsafe {
@@ -29,11 +31,59 @@ impl<T: AtlasScalar> StatePtr<T> {
             },
         }
     }
+
+    fn discrete_state_ptr(&self) -> *mut DiscreteValues<T> {
+        match self {
+            StatePtr::LeafStatePtr(ptr) => unsafe { ptr.as_mut().unwrap().discrete_state_mut() },
+            StatePtr::LeafDiagramPtr(ptr) => unsafe { ptr.as_mut().unwrap().discrete_state_mut() },
+        }
+    }
+
+    fn abstract_state_ptr(&self) -> *mut AbstractValues {
+        match self {
+            StatePtr::LeafStatePtr(ptr) => unsafe { ptr.as_mut().unwrap().abstract_state_mut() },
+            StatePtr::LeafDiagramPtr(ptr) => unsafe { ptr.as_mut().unwrap().abstract_state_mut() },
+        }
+    }
+
+    fn clone_owned(&self) -> OwnedState<T> {
+        match self {
+            StatePtr::LeafStatePtr(ptr) => unsafe {
+                OwnedState::LeafState(Box::new(ptr.as_ref().unwrap().clone()))
+            },
+            StatePtr::LeafDiagramPtr(ptr) => unsafe {
+                OwnedState::DiagramState(Box::new(ptr.as_ref().unwrap().clone()))
+            },
+        }
+    }
+}
+
+// States owned by a DiagramState, e.g. a copy of a Diagram's state made for an update.
+pub enum OwnedState<T: AtlasScalar> {
+    LeafState(Box<LeafState<T>>),
+    DiagramState(Box<DiagramState<T>>),
+}
+
+impl<T: AtlasScalar> OwnedState<T> {
+    fn state_ptr(&mut self) -> StatePtr<T> {
+        match self {
+            OwnedState::LeafState(state) => {
+                StatePtr::LeafStatePtr(state.as_mut() as *mut LeafState<T>)
+            }
+            OwnedState::DiagramState(state) => {
+                StatePtr::LeafDiagramPtr(state.as_mut() as *mut DiagramState<T>)
+            }
+        }
+    }
 }
 
 #[derive(Default)]
 pub struct DiagramState<T: AtlasScalar> {
     substates: Vec<Option<StatePtr<T>>>,
+    owned_substates: Vec<OwnedState<T>>,
+    continuous_state: Box<DiagramContinuousState<T>>,
+    discrete_state: DiscreteValues<T>,
+    abstract_state: AbstractValues,
     is_finalized: bool,
 }
 
@@ -41,21 +91,70 @@ impl<T: AtlasScalar> State<T> for DiagramState<T> {
     type CS = DiagramContinuousState<T>;
 
     fn continuous_state(&self) -> &Self::CS {
-        todo!()
+        self.continuous_state.as_ref()
     }
 
     fn continuous_state_mut(&mut self) -> &mut Self::CS {
-        todo!()
+        self.continuous_state.as_mut()
+    }
+
+    fn discrete_state(&self) -> &DiscreteValues<T> {
+        &self.discrete_state
+    }
+
+    fn discrete_state_mut(&mut self) -> &mut DiscreteValues<T> {
+        &mut self.discrete_state
+    }
+
+    fn abstract_state(&self) -> &AbstractValues {
+        &self.abstract_state
+    }
+
+    fn abstract_state_mut(&mut self) -> &mut AbstractValues {
+        &mut self.abstract_state
+    }
+}
+
+// Cloning copies every substate into an owned one, even when self views the subcontexts' states.
+impl<T: AtlasScalar> Clone for DiagramState<T> {
+    fn clone(&self) -> Self {
+        assert!(self.is_finalized);
+        let owned_substates = self
+            .substates
+            .iter()
+            .map(|substate| substate.as_ref().unwrap().clone_owned())
+            .collect();
+        let mut state = DiagramState::<T>::from_owned(owned_substates);
+        state
+            .continuous_state_mut()
+            .set_system_id(self.continuous_state().system_id().clone());
+        state
     }
 }
 
 impl<T: AtlasScalar> DiagramState<T> {
-    pub fn new(_size: usize) -> Self {
-        todo!()
+    pub fn new(size: usize) -> Self {
+        DiagramState::<T> {
+            substates: (0..size).map(|_| None).collect(),
+            owned_substates: vec![],
+            continuous_state: Box::default(),
+            discrete_state: DiscreteValues::default(),
+            abstract_state: AbstractValues::default(),
+            is_finalized: false,
+        }
+    }
+
+    pub fn from_owned(mut owned_substates: Vec<OwnedState<T>>) -> Self {
+        let mut state = DiagramState::<T>::new(owned_substates.len());
+        for (index, substate) in owned_substates.iter_mut().enumerate() {
+            state.set_substate(index, substate.state_ptr());
+        }
+        state.owned_substates = owned_substates;
+        state.finalize();
+        state
     }
 
-    #[allow(dead_code)]
-    fn num_substates(&self) -> usize {
+    pub fn num_substates(&self) -> usize {
         self.substates.len()
     }
 
@@ -71,8 +170,37 @@ impl<T: AtlasScalar> DiagramState<T> {
         self.substates[index].as_mut().unwrap()
     }
 
+    // Builds the composite continuous, discrete and abstract states once every substate has been
+    // set. The substates must outlive the DiagramState.
     pub fn finalize(&mut self) {
-        self.is_finalized = true;
+        assert!(!self.is_finalized);
         assert!(self.substates.iter().all(|x| x.is_some()));
+        self.is_finalized = true;
+
+        let continuous_substates = self
+            .substates
+            .iter()
+            .map(|substate| substate.as_ref().unwrap().continuous_state_ptr())
+            .collect();
+        *self.continuous_state = DiagramContinuousState::new(continuous_substates);
+
+        // The discrete and abstract states view those of every substate, in substate order.
+        let discrete_groups = self
+            .substates
+            .iter()
+            .flat_map(|substate| unsafe {
+                (*substate.as_ref().unwrap().discrete_state_ptr()).group_views()
+            })
+            .collect();
+        self.discrete_state = DiscreteValues::new(discrete_groups);
+
+        let abstract_values = self
+            .substates
+            .iter()
+            .flat_map(|substate| unsafe {
+                (*substate.as_ref().unwrap().abstract_state_ptr()).value_ptrs()
+            })
+            .collect();
+        self.abstract_state = AbstractValues::from_views(abstract_values);
     }
 }
@@ -0,0 +1,146 @@
+extern crate nalgebra as na;
+
+use crate::common::atlas_scalar::AtlasScalar;
+use crate::systems::framework::basic_vector::BasicVector;
+use crate::systems::framework::framework_common::DiscreteStateIndex;
+use crate::systems::framework::vector_base::VectorBase;
+
+// The discrete state of a System, made of groups of numeric values. A Diagram's discrete values
+// view the groups of its subsystems, in subsystem order.
+#[derive(Default)]
+pub struct DiscreteValues<T: AtlasScalar> {
+    data: Vec<Box<dyn VectorBase<T, Output = T>>>,
+}
+
+impl<T: AtlasScalar> DiscreteValues<T> {
+    pub fn new(data: Vec<Box<dyn VectorBase<T, Output = T>>>) -> Self {
+        DiscreteValues { data }
+    }
+
+    pub fn add_group(&mut self, group: BasicVector<T>) -> DiscreteStateIndex {
+        self.data.push(Box::new(group));
+        DiscreteStateIndex::new(self.data.len() - 1)
+    }
+
+    pub fn num_groups(&self) -> usize {
+        self.data.len()
+    }
+
+    // The size of the only group.
+    pub fn size(&self) -> usize {
+        assert_eq!(
+            self.num_groups(),
+            1,
+            "DiscreteValues::size: there must be exactly one group"
+        );
+        self.data[0].size()
+    }
+
+    pub fn vector(&self, index: &DiscreteStateIndex) -> &dyn VectorBase<T, Output = T> {
+        self.data[index.value()].as_ref()
+    }
+
+    pub fn vector_mut(&mut self, index: &DiscreteStateIndex) -> &mut dyn VectorBase<T, Output = T> {
+        self.data[index.value()].as_mut()
+    }
+
+    pub fn value(&self, index: &DiscreteStateIndex) -> na::DVector<T> {
+        self.vector(index).copy_to_vector()
+    }
+
+    pub fn set_value(&mut self, index: &DiscreteStateIndex, value: &na::DVector<T>) {
+        let vector = self.vector_mut(index);
+        assert_eq!(vector.size(), value.len());
+        vector.set_from_vector(value);
+    }
+
+    // Copies the values of other, which must have the same groups, without reallocating.
+    pub fn set_from(&mut self, other: &DiscreteValues<T>) {
+        assert_eq!(self.num_groups(), other.num_groups());
+        for (vector, other_vector) in self.data.iter_mut().zip(other.data.iter()) {
+            assert_eq!(vector.size(), other_vector.size());
+            vector.set_from(other_vector.as_ref());
+        }
+    }
+
+    // Views of every group, for a Diagram to aggregate the discrete values of its subsystems.
+    pub fn group_views(&mut self) -> Vec<Box<dyn VectorBase<T, Output = T>>> {
+        let num_groups = self.num_groups();
+        self.subset_views(0, num_groups).data
+    }
+
+    // Appends groups, e.g. views of the groups of another DiscreteValues.
+    pub fn append_groups(&mut self, groups: Vec<Box<dyn VectorBase<T, Output = T>>>) {
+        self.data.extend(groups);
+    }
+
+    // DiscreteValues viewing num_groups groups starting at first_group.
+    pub fn subset_views(&mut self, first_group: usize, num_groups: usize) -> DiscreteValues<T> {
+        DiscreteValues {
+            data: self.data[first_group..first_group + num_groups]
+                .iter_mut()
+                .map(|vector| {
+                    let size = vector.size();
+                    Box::new(vector.subvector_mut(0, size)) as Box<dyn VectorBase<T, Output = T>>
+                })
+                .collect(),
+        }
+    }
+}
+
+// Cloning copies the values into newly allocated groups, even when self views other storage.
+impl<T: AtlasScalar> Clone for DiscreteValues<T> {
+    fn clone(&self) -> Self {
+        DiscreteValues {
+            data: self
+                .data
+                .iter()
+                .map(|vector| {
+                    Box::new(BasicVector::<T>::new(vector.copy_to_vector()))
+                        as Box<dyn VectorBase<T, Output = T>>
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_groups() {
+        let mut discrete_values = DiscreteValues::<f64>::default();
+        let index0 = discrete_values.add_group(BasicVector::<f64>::from_vec(vec![1.0, 2.0]));
+        let index1 = discrete_values.add_group(BasicVector::<f64>::zeros(1));
+        assert_eq!(discrete_values.num_groups(), 2);
+
+        discrete_values.set_value(&index1, &na::DVector::<f64>::from_vec(vec![3.0]));
+        assert_eq!(
+            discrete_values.value(&index0),
+            na::DVector::<f64>::from_vec(vec![1.0, 2.0])
+        );
+        assert_eq!(discrete_values.vector(&index1)[0], 3.0);
+    }
+
+    #[test]
+    fn test_views_and_clone() {
+        let mut discrete_values = DiscreteValues::<f64>::default();
+        let index = discrete_values.add_group(BasicVector::<f64>::from_vec(vec![1.0, 2.0]));
+
+        let mut view = DiscreteValues::<f64>::new(discrete_values.group_views());
+        let mut copy = view.clone();
+        view.set_value(&index, &na::DVector::<f64>::from_vec(vec![3.0, 4.0]));
+        assert_eq!(
+            discrete_values.value(&index),
+            na::DVector::<f64>::from_vec(vec![3.0, 4.0])
+        );
+
+        copy.set_value(&index, &na::DVector::<f64>::from_vec(vec![5.0, 6.0]));
+        view.set_from(&copy);
+        assert_eq!(
+            discrete_values.value(&index),
+            na::DVector::<f64>::from_vec(vec![5.0, 6.0])
+        );
+    }
+}
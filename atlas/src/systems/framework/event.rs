@@ -0,0 +1,138 @@
+use crate::common::atlas_scalar::AtlasScalar;
+use crate::systems::framework::discrete_values::DiscreteValues;
+use crate::systems::framework::leaf_context::LeafContext;
+use crate::systems::framework::leaf_state::LeafState;
+
+// The timing of a periodic event, which triggers at offset_sec + k * period_sec for k = 0, 1, ...
+#[derive(Clone, Debug, PartialEq)]
+pub struct PeriodicEventData {
+    period_sec: f64,
+    offset_sec: f64,
+}
+
+impl PeriodicEventData {
+    pub fn new(period_sec: f64, offset_sec: f64) -> Self {
+        assert!(
+            period_sec > 0.0,
+            "PeriodicEventData: period must be positive"
+        );
+        assert!(
+            offset_sec >= 0.0,
+            "PeriodicEventData: offset must be non-negative"
+        );
+        PeriodicEventData {
+            period_sec,
+            offset_sec,
+        }
+    }
+
+    pub fn period_sec(&self) -> f64 {
+        self.period_sec
+    }
+
+    pub fn offset_sec(&self) -> f64 {
+        self.offset_sec
+    }
+
+    // Returns the first trigger time after time, or at time when inclusive is set.
+    pub fn next_event_time(&self, time: f64, inclusive: bool) -> f64 {
+        if time < self.offset_sec || (inclusive && time == self.offset_sec) {
+            return self.offset_sec;
+        }
+
+        // Trigger times are always computed as offset + k * period, so that comparing them for
+        // equality is exact. Start one period early in case the division rounds up.
+        let mut k = ((time - self.offset_sec) / self.period_sec).floor() - 1.0;
+        loop {
+            let next_time = self.offset_sec + k.max(0.0) * self.period_sec;
+            if next_time > time || (inclusive && next_time == time) {
+                return next_time;
+            }
+            k += 1.0;
+        }
+    }
+}
+
+pub type DiscreteUpdateCallback<T> = Box<dyn Fn(&LeafContext<T>, &mut DiscreteValues<T>)>;
+
+// Computes new discrete state values from the context. The discrete state passed to the callback
+// starts out as a copy of the context's discrete state.
+pub struct DiscreteUpdateEvent<T: AtlasScalar> {
+    periodic_event_data: PeriodicEventData,
+    callback: DiscreteUpdateCallback<T>,
+}
+
+impl<T: AtlasScalar> DiscreteUpdateEvent<T> {
+    pub fn new(
+        periodic_event_data: PeriodicEventData,
+        callback: DiscreteUpdateCallback<T>,
+    ) -> Self {
+        DiscreteUpdateEvent {
+            periodic_event_data,
+            callback,
+        }
+    }
+
+    pub fn periodic_event_data(&self) -> &PeriodicEventData {
+        &self.periodic_event_data
+    }
+
+    pub fn handle(&self, context: &LeafContext<T>, discrete_state: &mut DiscreteValues<T>) {
+        (self.callback)(context, discrete_state);
+    }
+}
+
+pub type UnrestrictedUpdateCallback<T> = Box<dyn Fn(&LeafContext<T>, &mut LeafState<T>)>;
+
+// Computes new values of any part of the state from the context. The state passed to the callback
+// starts out as a copy of the context's state.
+pub struct UnrestrictedUpdateEvent<T: AtlasScalar> {
+    periodic_event_data: PeriodicEventData,
+    callback: UnrestrictedUpdateCallback<T>,
+}
+
+impl<T: AtlasScalar> UnrestrictedUpdateEvent<T> {
+    pub fn new(
+        periodic_event_data: PeriodicEventData,
+        callback: UnrestrictedUpdateCallback<T>,
+    ) -> Self {
+        UnrestrictedUpdateEvent {
+            periodic_event_data,
+            callback,
+        }
+    }
+
+    pub fn periodic_event_data(&self) -> &PeriodicEventData {
+        &self.periodic_event_data
+    }
+
+    pub fn handle(&self, context: &LeafContext<T>, state: &mut LeafState<T>) {
+        (self.callback)(context, state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_event_time() {
+        let periodic_event_data = PeriodicEventData::new(0.5, 0.25);
+
+        assert_eq!(periodic_event_data.next_event_time(0.0, false), 0.25);
+        assert_eq!(periodic_event_data.next_event_time(0.25, true), 0.25);
+        assert_eq!(periodic_event_data.next_event_time(0.25, false), 0.75);
+        assert_eq!(periodic_event_data.next_event_time(0.5, false), 0.75);
+        assert_eq!(periodic_event_data.next_event_time(0.75, true), 0.75);
+        assert_eq!(periodic_event_data.next_event_time(0.75, false), 1.25);
+
+        // 3.0 * 0.1 is not 0.3 in floating point, but it is still an event time.
+        let periodic_event_data = PeriodicEventData::new(0.1, 0.0);
+        assert_eq!(periodic_event_data.next_event_time(0.2, false), 3.0 * 0.1);
+        assert_eq!(
+            periodic_event_data.next_event_time(3.0 * 0.1, true),
+            3.0 * 0.1
+        );
+        assert_eq!(periodic_event_data.next_event_time(3.0 * 0.1, false), 0.4);
+    }
+}
@@ -37,6 +37,46 @@ pub struct ContinuousStateTag;
 
 pub type ContinuousStateIndex = TypeSafeIndex<ContinuousStateTag>;
 
+#[allow(dead_code)]
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct DiscreteStateTag;
+
+pub type DiscreteStateIndex = TypeSafeIndex<DiscreteStateTag>;
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct AbstractStateTag;
+
+pub type AbstractStateIndex = TypeSafeIndex<AbstractStateTag>;
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct NumericParameterTag;
+
+pub type NumericParameterIndex = TypeSafeIndex<NumericParameterTag>;
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct AbstractParameterTag;
+
+pub type AbstractParameterIndex = TypeSafeIndex<AbstractParameterTag>;
+
+// Identifies a value that a context tracks for changes: one of the sources every context has, an
+// input or output port, or a cache entry.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum DependencyTicket {
+    Time,
+    ContinuousState,
+    DiscreteState,
+    AbstractState,
+    Parameters,
+    AllInputPorts,
+    AllSources,
+    InputPort(InputPortIndex),
+    OutputPort(OutputPortIndex),
+    CacheEntry(CacheIndex),
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum PortDataType {
     VectorValued,
@@ -3,23 +3,26 @@ use std::rc::Rc;
 
 use crate::common::atlas_scalar::AtlasScalar;
 use crate::common::value::AbstractValue;
-use crate::systems::framework::cache::Cache;
 use crate::systems::framework::context::Context;
 use crate::systems::framework::context_base::ContextBase;
 use crate::systems::framework::continuous_state::ContinuousState;
+use crate::systems::framework::dependency_tracker::DependencyGraph;
 use crate::systems::framework::fixed_input_port_value::FixedInputPortValue;
-use crate::systems::framework::framework_common::SystemId;
+use crate::systems::framework::framework_common::{DependencyTicket, InputPortIndex, SystemId};
 use crate::systems::framework::leaf_state::LeafState;
+use crate::systems::framework::parameters::Parameters;
 use crate::systems::framework::state::State;
 use crate::systems::framework::vector_base::VectorBase;
 
 #[derive(Default)]
 pub struct LeafContext<T: AtlasScalar> {
     system_id: SystemId,
+    system_name: String,
     parent: Option<Rc<RefCell<dyn ContextBase>>>,
-    cache: RefCell<Cache>,
+    graph: Rc<DependencyGraph>,
     time: T,
     state: Box<LeafState<T>>,
+    parameters: Parameters<T>,
     input_port_values: Vec<Option<FixedInputPortValue>>,
     is_context_base_initialized: bool,
 }
@@ -39,6 +42,12 @@ impl<T: AtlasScalar> ContextBase for LeafContext<T> {
     fn system_id(&self) -> &SystemId {
         &self.system_id
     }
+    fn set_system_name(&mut self, system_name: String) {
+        self.system_name = system_name;
+    }
+    fn system_name(&self) -> &str {
+        &self.system_name
+    }
 
     fn parent_base(&self) -> &Option<Rc<RefCell<dyn ContextBase>>> {
         &self.parent
@@ -48,8 +57,8 @@ impl<T: AtlasScalar> ContextBase for LeafContext<T> {
         &mut self.parent
     }
 
-    fn cache(&self) -> &RefCell<Cache> {
-        &self.cache
+    fn graph(&self) -> &Rc<DependencyGraph> {
+        &self.graph
     }
 
     fn input_port_values(&mut self) -> &mut Vec<Option<FixedInputPortValue>> {
@@ -66,6 +75,7 @@ impl<T: AtlasScalar> ContextBase for LeafContext<T> {
         value: &dyn AbstractValue,
     ) -> Option<&FixedInputPortValue> {
         self.input_port_values[index] = Some(FixedInputPortValue::new(value.clone_box()));
+        self.note_value_change(&DependencyTicket::InputPort(InputPortIndex::new(index)));
 
         self.fixed_input_port_value(index)
     }
@@ -75,6 +85,7 @@ impl<T: AtlasScalar> ContextBase for LeafContext<T> {
     }
 
     fn fixed_input_port_value_mut(&mut self, index: usize) -> Option<&mut FixedInputPortValue> {
+        self.note_value_change(&DependencyTicket::InputPort(InputPortIndex::new(index)));
         self.input_port_values[index].as_mut()
     }
 
@@ -90,14 +101,29 @@ impl<T: AtlasScalar> Context<T> for LeafContext<T> {
         &self.time
     }
 
+    fn set_time(&mut self, time: T) {
+        self.note_value_change(&DependencyTicket::Time);
+        self.time = time;
+    }
+
     fn state(&self) -> &Self::S {
         &self.state
     }
 
     fn state_mut(&mut self) -> &mut Self::S {
+        self.note_all_state_changed();
         &mut self.state
     }
 
+    fn parameters(&self) -> &Parameters<T> {
+        &self.parameters
+    }
+
+    fn parameters_mut(&mut self) -> &mut Parameters<T> {
+        self.note_value_change(&DependencyTicket::Parameters);
+        &mut self.parameters
+    }
+
     fn init_continuous_state(&mut self, continuous_state: Box<<Self::S as State<T>>::CS>) {
         self.set_continuous_state(continuous_state);
     }
@@ -111,6 +137,7 @@ impl<T: AtlasScalar> Context<T> for LeafContext<T> {
     }
 
     fn continuous_state_mut(&mut self) -> &mut <Self::S as State<T>>::CS {
+        self.note_value_change(&DependencyTicket::ContinuousState);
         self.state.continuous_state_mut()
     }
 
@@ -119,6 +146,7 @@ impl<T: AtlasScalar> Context<T> for LeafContext<T> {
     }
 
     fn continuous_state_vector_mut(&mut self) -> &mut dyn VectorBase<T, Output = T> {
+        self.note_value_change(&DependencyTicket::ContinuousState);
         self.state.continuous_state_mut().vector_mut()
     }
 
@@ -132,6 +160,10 @@ impl<T: AtlasScalar> Context<T> for LeafContext<T> {
 }
 
 impl<T: AtlasScalar> LeafContext<T> {
+    pub fn init_parameters(&mut self, parameters: Parameters<T>) {
+        self.parameters = parameters;
+    }
+
     pub fn as_context(&self) -> &dyn Context<T, S = LeafState<T>> {
         self
     }
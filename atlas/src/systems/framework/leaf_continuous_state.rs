@@ -1,8 +1,11 @@
 extern crate nalgebra as na;
 
 use crate::common::atlas_scalar::AtlasScalar;
+use crate::systems::framework::basic_vector::BasicVector;
 use crate::systems::framework::continuous_state::ContinuousState;
 use crate::systems::framework::framework_common::SystemId;
+use crate::systems::framework::subvector::Subvector;
+use crate::systems::framework::supervector::Supervector;
 use crate::systems::framework::vector_base::VectorBase;
 
 #[derive(Default)]
@@ -29,6 +32,45 @@ impl<T: AtlasScalar> LeafContinuousState<T> {
             system_id: SystemId::new(0),
         }
     }
+
+    // Copies the current values into q, v and z, which view the corresponding partitions of an
+    // enclosing Diagram's state, and uses them as the storage from then on.
+    pub fn bind_to_storage(
+        &mut self,
+        mut q: Subvector<T>,
+        mut v: Subvector<T>,
+        mut z: Subvector<T>,
+    ) {
+        assert_eq!(q.size(), self.num_q);
+        assert_eq!(v.size(), self.num_v);
+        assert_eq!(z.size(), self.num_z);
+
+        let (num_q, num_v) = (self.num_q, self.num_v);
+        for i in 0..num_q {
+            q[i] = self.state[i].clone();
+        }
+        for i in 0..num_v {
+            v[i] = self.state[num_q + i].clone();
+        }
+        for i in 0..self.num_z {
+            z[i] = self.state[num_q + num_v + i].clone();
+        }
+        self.state = Box::new(Supervector::new(vec![q, v, z]));
+    }
+}
+
+// Cloning copies the values into newly allocated storage, even when self views a Diagram's state.
+impl<T: AtlasScalar> Clone for LeafContinuousState<T> {
+    fn clone(&self) -> Self {
+        let mut continuous_state = LeafContinuousState::<T>::new(
+            Box::new(BasicVector::<T>::new(self.state.copy_to_vector())),
+            self.num_q,
+            self.num_v,
+            self.num_z,
+        );
+        continuous_state.set_system_id(self.system_id.clone());
+        continuous_state
+    }
 }
 
 impl<T: AtlasScalar> ContinuousState<T> for LeafContinuousState<T> {
@@ -1,15 +1,31 @@
 use crate::common::atlas_scalar::AtlasScalar;
+use crate::systems::framework::abstract_values::AbstractValues;
+use crate::systems::framework::discrete_values::DiscreteValues;
 use crate::systems::framework::leaf_continuous_state::LeafContinuousState;
 use crate::systems::framework::state::State;
 
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct LeafState<T: AtlasScalar> {
     continuous_state: Box<LeafContinuousState<T>>,
+    discrete_state: DiscreteValues<T>,
+    abstract_state: AbstractValues,
 }
 
 impl<T: AtlasScalar> LeafState<T> {
     pub fn new(continuous_state: Box<LeafContinuousState<T>>) -> Self {
-        Self { continuous_state }
+        Self {
+            continuous_state,
+            discrete_state: DiscreteValues::default(),
+            abstract_state: AbstractValues::default(),
+        }
+    }
+
+    pub fn set_discrete_state(&mut self, discrete_state: DiscreteValues<T>) {
+        self.discrete_state = discrete_state;
+    }
+
+    pub fn set_abstract_state(&mut self, abstract_state: AbstractValues) {
+        self.abstract_state = abstract_state;
     }
 }
 
@@ -23,4 +39,20 @@ impl<T: AtlasScalar> State<T> for LeafState<T> {
     fn continuous_state_mut(&mut self) -> &mut Self::CS {
         self.continuous_state.as_mut()
     }
+
+    fn discrete_state(&self) -> &DiscreteValues<T> {
+        &self.discrete_state
+    }
+
+    fn discrete_state_mut(&mut self) -> &mut DiscreteValues<T> {
+        &mut self.discrete_state
+    }
+
+    fn abstract_state(&self) -> &AbstractValues {
+        &self.abstract_state
+    }
+
+    fn abstract_state_mut(&mut self) -> &mut AbstractValues {
+        &mut self.abstract_state
+    }
 }
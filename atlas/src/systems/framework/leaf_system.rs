@@ -1,24 +1,34 @@
 use std::cell::RefCell;
 use std::fmt::Debug;
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
 
 extern crate nalgebra as na;
 
 use crate::common::atlas_scalar::AtlasScalar;
 use crate::common::value::{AbstractValue, Value};
+use crate::systems::framework::abstract_values::AbstractValues;
 use crate::systems::framework::basic_vector::BasicVector;
 use crate::systems::framework::cache_entry::CacheEntry;
 use crate::systems::framework::context::Context;
 use crate::systems::framework::context_base::ContextBase;
 use crate::systems::framework::continuous_state::ContinuousState;
+use crate::systems::framework::diagram::SystemWeakLink;
+use crate::systems::framework::discrete_values::DiscreteValues;
+use crate::systems::framework::event::{
+    DiscreteUpdateCallback, DiscreteUpdateEvent, PeriodicEventData, UnrestrictedUpdateCallback,
+    UnrestrictedUpdateEvent,
+};
 use crate::systems::framework::framework_common::{
-    ContinuousStateIndex, OutputPortIndex, PortDataType,
+    AbstractParameterIndex, AbstractStateIndex, ContinuousStateIndex, DependencyTicket,
+    DiscreteStateIndex, InputPortIndex, NumericParameterIndex, OutputPortIndex, PortDataType,
 };
 use crate::systems::framework::input_port::InputPort;
 use crate::systems::framework::leaf_context::LeafContext;
 use crate::systems::framework::leaf_continuous_state::LeafContinuousState;
 use crate::systems::framework::leaf_output_port::LeafOutputPort;
+use crate::systems::framework::leaf_state::LeafState;
 use crate::systems::framework::model_values::ModelValues;
+use crate::systems::framework::parameters::Parameters;
 use crate::systems::framework::port_base::PortBase;
 use crate::systems::framework::system::System;
 use crate::systems::framework::value_producer::{AllocateCallback, ValueProducer};
@@ -29,6 +39,16 @@ pub trait LeafSystem<T: AtlasScalar>: System<T, CN = LeafContext<T>> {
     fn model_input_values_mut(&mut self) -> &mut ModelValues;
     fn model_continuous_state_vector(&self) -> &BasicVector<T>;
     fn model_continuous_state_vector_mut(&mut self) -> &mut BasicVector<T>;
+    fn model_discrete_state(&self) -> &DiscreteValues<T>;
+    fn model_discrete_state_mut(&mut self) -> &mut DiscreteValues<T>;
+    fn discrete_update_events(&self) -> &Vec<DiscreteUpdateEvent<T>>;
+    fn discrete_update_events_mut(&mut self) -> &mut Vec<DiscreteUpdateEvent<T>>;
+    fn model_abstract_state(&self) -> &AbstractValues;
+    fn model_abstract_state_mut(&mut self) -> &mut AbstractValues;
+    fn unrestricted_update_events(&self) -> &Vec<UnrestrictedUpdateEvent<T>>;
+    fn unrestricted_update_events_mut(&mut self) -> &mut Vec<UnrestrictedUpdateEvent<T>>;
+    fn model_parameters(&self) -> &Parameters<T>;
+    fn model_parameters_mut(&mut self) -> &mut Parameters<T>;
     fn leaf_output_port(&self, output_port_index: &OutputPortIndex) -> &LeafOutputPort<T>;
     fn leaf_output_port_mut(
         &mut self,
@@ -43,6 +63,25 @@ pub trait LeafSystem<T: AtlasScalar>: System<T, CN = LeafContext<T>> {
     fn do_allocate_context(&self) -> Rc<RefCell<LeafContext<T>>> {
         let mut context = self.do_make_leaf_context();
         self.initialize_context_base(context.as_mutable_base());
+        context.init_continuous_state(self.allocate_continuous_state());
+        context
+            .state_mut()
+            .set_discrete_state(self.model_discrete_state().clone());
+        context
+            .state_mut()
+            .set_abstract_state(self.model_abstract_state().clone());
+        context.init_parameters(self.model_parameters().clone());
+
+        let graph = context.graph().clone();
+        for index in 0..self.num_output_ports() {
+            let output_port_index = OutputPortIndex::new(index);
+            let cache_entry = self.leaf_output_port(&output_port_index).cache_entry();
+            graph.subscribe_to_prerequisite(
+                &DependencyTicket::OutputPort(output_port_index),
+                &graph,
+                &cache_entry.ticket(),
+            );
+        }
 
         Rc::new(RefCell::new(context))
     }
@@ -66,7 +105,7 @@ pub trait LeafSystem<T: AtlasScalar>: System<T, CN = LeafContext<T>> {
         LeafContext::<T>::default()
     }
 
-    fn allocate_time_derivatives(&mut self) -> Box<LeafContinuousState<T>> {
+    fn allocate_time_derivatives(&self) -> Box<LeafContinuousState<T>> {
         self.allocate_continuous_state()
     }
     fn allocate_continuous_state(&self) -> Box<LeafContinuousState<T>> {
@@ -87,10 +126,40 @@ pub trait LeafSystem<T: AtlasScalar>: System<T, CN = LeafContext<T>> {
     }
 
     fn set_default_state(&self, context: &mut Self::CN) {
-        self.validate_context(context.as_base());
+        self.validate_context(context.as_base())
+            .unwrap_or_else(|error| panic!("{}", error));
 
         let continuous_state = context.continuous_state_mut();
         continuous_state.set_from_vector(self.model_continuous_state_vector().value());
+
+        context
+            .discrete_state_mut()
+            .set_from(self.model_discrete_state());
+        context
+            .abstract_state_mut()
+            .set_from(self.model_abstract_state());
+    }
+
+    fn set_default_parameters(&self, context: &mut Self::CN) {
+        self.validate_context(context.as_base())
+            .unwrap_or_else(|error| panic!("{}", error));
+
+        context.parameters_mut().set_from(self.model_parameters());
+    }
+
+    // Declares a group of numeric parameters whose default value is model_vector.
+    fn declare_numeric_parameter(&mut self, model_vector: BasicVector<T>) -> NumericParameterIndex {
+        self.model_parameters_mut()
+            .add_numeric_parameter(model_vector)
+    }
+
+    // Declares a parameter of arbitrary type whose default value is model_value.
+    fn declare_abstract_parameter(
+        &mut self,
+        model_value: &dyn AbstractValue,
+    ) -> AbstractParameterIndex {
+        self.model_parameters_mut()
+            .add_abstract_parameter(model_value.clone_box())
     }
 
     fn declare_continuous_state(
@@ -112,6 +181,139 @@ pub trait LeafSystem<T: AtlasScalar>: System<T, CN = LeafContext<T>> {
         ContinuousStateIndex::new(0)
     }
 
+    // Declares a group of discrete state variables, initially zero.
+    fn declare_discrete_state(&mut self, size: usize) -> DiscreteStateIndex {
+        let index = self
+            .model_discrete_state_mut()
+            .add_group(BasicVector::<T>::zeros(size));
+        self.context_sizes_mut().num_discrete_state_groups += 1;
+
+        index
+    }
+
+    // Declares an update of the discrete state at offset_sec + k * period_sec, k = 0, 1, ...
+    fn declare_periodic_discrete_update_event(
+        &mut self,
+        period_sec: f64,
+        offset_sec: f64,
+        callback: DiscreteUpdateCallback<T>,
+    ) {
+        let event =
+            DiscreteUpdateEvent::new(PeriodicEventData::new(period_sec, offset_sec), callback);
+        self.discrete_update_events_mut().push(event);
+    }
+
+    // Declares a value of arbitrary type in the state, initially a copy of model_value.
+    fn declare_abstract_state(&mut self, model_value: &dyn AbstractValue) -> AbstractStateIndex {
+        let index = self
+            .model_abstract_state_mut()
+            .add_value(model_value.clone_box());
+        self.context_sizes_mut().num_abstract_states += 1;
+
+        index
+    }
+
+    // Declares an update of any part of the state at offset_sec + k * period_sec, k = 0, 1, ...
+    fn declare_periodic_unrestricted_update_event(
+        &mut self,
+        period_sec: f64,
+        offset_sec: f64,
+        callback: UnrestrictedUpdateCallback<T>,
+    ) {
+        let event =
+            UnrestrictedUpdateEvent::new(PeriodicEventData::new(period_sec, offset_sec), callback);
+        self.unrestricted_update_events_mut().push(event);
+    }
+
+    // The output port depends on the input port when the prerequisites of its cache entry,
+    // followed through any other cache entries, include the input. Outputs that only declare the
+    // default AllSources prerequisite are therefore assumed to depend on every input.
+    fn has_direct_feedthrough(
+        &self,
+        input_port_index: &InputPortIndex,
+        output_port_index: &OutputPortIndex,
+    ) -> bool {
+        let mut pending = vec![self
+            .leaf_output_port(output_port_index)
+            .cache_entry()
+            .ticket()];
+        let mut visited: Vec<DependencyTicket> = vec![];
+        while let Some(ticket) = pending.pop() {
+            if visited.contains(&ticket) {
+                continue;
+            }
+            match &ticket {
+                DependencyTicket::AllSources | DependencyTicket::AllInputPorts => return true,
+                DependencyTicket::InputPort(index) if index == input_port_index => return true,
+                DependencyTicket::CacheEntry(cache_index) => pending.extend(
+                    self.cache_entry(cache_index)
+                        .prerequisites()
+                        .iter()
+                        .cloned(),
+                ),
+                _ => {}
+            }
+            visited.push(ticket);
+        }
+        false
+    }
+
+    fn periodic_discrete_update_timings(&self) -> Vec<PeriodicEventData> {
+        let mut timings: Vec<PeriodicEventData> = vec![];
+        for event in self.discrete_update_events() {
+            if !timings.contains(event.periodic_event_data()) {
+                timings.push(event.periodic_event_data().clone());
+            }
+        }
+        timings
+    }
+
+    fn periodic_unrestricted_update_timings(&self) -> Vec<PeriodicEventData> {
+        let mut timings: Vec<PeriodicEventData> = vec![];
+        for event in self.unrestricted_update_events() {
+            if !timings.contains(event.periodic_event_data()) {
+                timings.push(event.periodic_event_data().clone());
+            }
+        }
+        timings
+    }
+
+    fn calc_discrete_variable_updates(
+        &self,
+        context: &LeafContext<T>,
+        timings: &[PeriodicEventData],
+        discrete_state: &mut DiscreteValues<T>,
+    ) {
+        self.validate_context(context.as_base())
+            .unwrap_or_else(|error| panic!("{}", error));
+        assert_eq!(
+            discrete_state.num_groups(),
+            context.num_discrete_state_groups()
+        );
+
+        for event in self.discrete_update_events() {
+            if timings.contains(event.periodic_event_data()) {
+                event.handle(context, discrete_state);
+            }
+        }
+    }
+
+    fn calc_unrestricted_updates(
+        &self,
+        context: &LeafContext<T>,
+        timings: &[PeriodicEventData],
+        state: &mut LeafState<T>,
+    ) {
+        self.validate_context(context.as_base())
+            .unwrap_or_else(|error| panic!("{}", error));
+
+        for event in self.unrestricted_update_events() {
+            if timings.contains(event.periodic_event_data()) {
+                event.handle(context, state);
+            }
+        }
+    }
+
     // Declare input port
     fn declare_vector_input_port(&mut self, name: String, size: usize) -> &InputPort<T>
     where
@@ -140,12 +342,30 @@ pub trait LeafSystem<T: AtlasScalar>: System<T, CN = LeafContext<T>> {
     }
 
     // Declare output port
+    // The value of an output port is recomputed only after a change to one of its prerequisites,
+    // which are all the sources of the context unless given.
     #[allow(clippy::type_complexity)]
     fn declare_vector_output_port(
         &mut self,
         name: String,
         size: usize,
         calc: Box<dyn Fn(&Self::CN, &mut BasicVector<T>)>,
+    ) -> &LeafOutputPort<T> {
+        self.declare_vector_output_port_with_prerequisites(
+            name,
+            size,
+            calc,
+            vec![DependencyTicket::AllSources],
+        )
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn declare_vector_output_port_with_prerequisites(
+        &mut self,
+        name: String,
+        size: usize,
+        calc: Box<dyn Fn(&Self::CN, &mut BasicVector<T>)>,
+        prerequisites_of_calc: Vec<DependencyTicket>,
     ) -> &LeafOutputPort<T> {
         let model_vector = BasicVector::<T>::zeros(size);
         self.create_vector_leaf_output_port(
@@ -153,6 +373,90 @@ pub trait LeafSystem<T: AtlasScalar>: System<T, CN = LeafContext<T>> {
             size,
             Self::make_allocate_callback(model_vector),
             calc,
+            prerequisites_of_calc,
+        )
+    }
+
+    // Wraps calc, a method of this system or a closure taking the system, into a callback for an
+    // output port. The callback only holds the weak link to the system, so the port does not keep
+    // its system alive.
+    #[allow(clippy::type_complexity)]
+    fn make_vector_output_calc(
+        &self,
+        calc: impl Fn(&Self, &Self::CN, &mut BasicVector<T>) + 'static,
+    ) -> Box<dyn Fn(&Self::CN, &mut BasicVector<T>)>
+    where
+        Self: Sized,
+    {
+        let system_weak_link = self.system_weak_link();
+        Box::new(
+            move |context: &LeafContext<T>, output: &mut BasicVector<T>| {
+                let system = system_weak_link
+                    .as_leaf_system_weak_link()
+                    .unwrap()
+                    .upgrade()
+                    .unwrap();
+                let system = system.borrow();
+                calc(
+                    system.as_any().downcast_ref::<Self>().unwrap(),
+                    context,
+                    output,
+                )
+            },
+        )
+    }
+
+    // Like make_vector_output_calc, for abstract-valued output ports.
+    #[allow(clippy::type_complexity)]
+    fn make_abstract_output_calc(
+        &self,
+        calc: impl Fn(&Self, &Self::CN, &mut dyn AbstractValue) + 'static,
+    ) -> Box<dyn Fn(&Self::CN, &mut dyn AbstractValue)>
+    where
+        Self: Sized,
+    {
+        let system_weak_link = self.system_weak_link();
+        Box::new(
+            move |context: &LeafContext<T>, output: &mut dyn AbstractValue| {
+                let system = system_weak_link
+                    .as_leaf_system_weak_link()
+                    .unwrap()
+                    .upgrade()
+                    .unwrap();
+                let system = system.borrow();
+                calc(
+                    system.as_any().downcast_ref::<Self>().unwrap(),
+                    context,
+                    output,
+                )
+            },
+        )
+    }
+
+    // Wraps update, a method of this system, into a callback for a discrete update event, holding
+    // only the weak link to the system like the output calcs.
+    fn make_discrete_update_callback(
+        &self,
+        update: fn(&Self, &Self::CN, &mut DiscreteValues<T>),
+    ) -> DiscreteUpdateCallback<T>
+    where
+        Self: Sized,
+    {
+        let system_weak_link = self.system_weak_link();
+        Box::new(
+            move |context: &LeafContext<T>, discrete_state: &mut DiscreteValues<T>| {
+                let system = system_weak_link
+                    .as_leaf_system_weak_link()
+                    .unwrap()
+                    .upgrade()
+                    .unwrap();
+                let system = system.borrow();
+                update(
+                    system.as_any().downcast_ref::<Self>().unwrap(),
+                    context,
+                    discrete_state,
+                )
+            },
         )
     }
 
@@ -171,6 +475,22 @@ pub trait LeafSystem<T: AtlasScalar>: System<T, CN = LeafContext<T>> {
         name: String,
         alloc: Box<AllocateCallback>,
         calc: Box<dyn Fn(&Self::CN, &mut dyn AbstractValue)>,
+    ) -> &LeafOutputPort<T> {
+        self.declare_abstract_output_port_with_prerequisites(
+            name,
+            alloc,
+            calc,
+            vec![DependencyTicket::AllSources],
+        )
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn declare_abstract_output_port_with_prerequisites(
+        &mut self,
+        name: String,
+        alloc: Box<AllocateCallback>,
+        calc: Box<dyn Fn(&Self::CN, &mut dyn AbstractValue)>,
+        prerequisites_of_calc: Vec<DependencyTicket>,
     ) -> &LeafOutputPort<T> {
         let calc_ = Box::new(
             move |context_base: &dyn ContextBase, abstract_value: &mut dyn AbstractValue| {
@@ -180,7 +500,7 @@ pub trait LeafSystem<T: AtlasScalar>: System<T, CN = LeafContext<T>> {
         );
         let value_producer = ValueProducer::new(alloc, calc_);
 
-        self.create_abstract_leaf_output_port(name, value_producer)
+        self.create_abstract_leaf_output_port(name, value_producer, prerequisites_of_calc)
     }
 
     #[allow(clippy::type_complexity)]
@@ -190,6 +510,7 @@ pub trait LeafSystem<T: AtlasScalar>: System<T, CN = LeafContext<T>> {
         fixed_size: usize,
         alloc: Box<AllocateCallback>,
         calc: Box<dyn Fn(&Self::CN, &mut BasicVector<T>)>,
+        prerequisites_of_calc: Vec<DependencyTicket>,
     ) -> &LeafOutputPort<T> {
         let cache_calc = Box::new(
             move |context_base: &dyn ContextBase, abstract_value: &mut dyn AbstractValue| {
@@ -205,15 +526,21 @@ pub trait LeafSystem<T: AtlasScalar>: System<T, CN = LeafContext<T>> {
         );
         let value_producer = ValueProducer::new(alloc, cache_calc);
 
-        self.create_cached_leaf_output_port(name, Some(fixed_size), value_producer)
+        self.create_cached_leaf_output_port(
+            name,
+            Some(fixed_size),
+            value_producer,
+            prerequisites_of_calc,
+        )
     }
 
     fn create_abstract_leaf_output_port(
         &mut self,
         name: String,
         value_producer: ValueProducer,
+        prerequisites_of_calc: Vec<DependencyTicket>,
     ) -> &LeafOutputPort<T> {
-        self.create_cached_leaf_output_port(name, None, value_producer)
+        self.create_cached_leaf_output_port(name, None, value_producer, prerequisites_of_calc)
     }
 
     fn create_cached_leaf_output_port(
@@ -221,10 +548,11 @@ pub trait LeafSystem<T: AtlasScalar>: System<T, CN = LeafContext<T>> {
         name: String,
         fixed_size: Option<usize>,
         value_producer: ValueProducer,
+        prerequisites_of_calc: Vec<DependencyTicket>,
     ) -> &LeafOutputPort<T> {
         let output_port_index = OutputPortIndex::new(self.num_output_ports());
         let _system_id = self.system_id().clone();
-        let cache_entry = self.declare_cache_entry(value_producer);
+        let cache_entry = self.declare_cache_entry(value_producer, prerequisites_of_calc);
         let cache_entry_ptr: *const CacheEntry = cache_entry;
         let output_port = if let Some(size) = fixed_size {
             let data_type = PortDataType::VectorValued;
@@ -255,3 +583,165 @@ pub trait LeafSystem<T: AtlasScalar>: System<T, CN = LeafContext<T>> {
         self.leaf_output_port(&output_port_index)
     }
 }
+
+// Creates a leaf system that links to itself: make_system receives the system's own weak link,
+// to be returned as its system_weak_link, before the system is shared. Ports, state and
+// parameters are declared afterwards, through the returned Rc.
+pub fn make_leaf_system<T, S>(make_system: impl FnOnce(SystemWeakLink<T>) -> S) -> Rc<RefCell<S>>
+where
+    T: AtlasScalar,
+    S: LeafSystem<T>,
+{
+    Rc::new_cyclic(|system_weak: &Weak<RefCell<S>>| {
+        let system_weak: Weak<RefCell<dyn System<T, CN = LeafContext<T>>>> = system_weak.clone();
+        RefCell::new(make_system(SystemWeakLink::LeafSystemWeakLink(system_weak)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use atlas_derives::leaf_system;
+
+    use super::*;
+    use crate::systems::framework::system_base::SystemBase;
+
+    #[leaf_system(name = "scaler")]
+    struct Scaler<T: AtlasScalar> {
+        gain: T,
+        #[input(vector, size = "size")]
+        input: InputPortIndex,
+        #[input(abstract, model = "String::new()")]
+        label: InputPortIndex,
+        #[output(
+            vector,
+            size = "size",
+            calc = "calc_scaled",
+            prerequisites = "vec![DependencyTicket::InputPort(InputPortIndex::new(0))]"
+        )]
+        scaled: OutputPortIndex,
+        #[output(
+            abstract,
+            model = "String::new()",
+            calc = "calc_label",
+            prerequisites = "vec![DependencyTicket::InputPort(InputPortIndex::new(1))]"
+        )]
+        echoed_label: OutputPortIndex,
+        size: usize,
+    }
+
+    impl<T: AtlasScalar> Scaler<T> {
+        fn calc_scaled(&self, context: &LeafContext<T>, scaled: &mut BasicVector<T>) {
+            let input = self
+                .input_port(&self.input)
+                .eval::<LeafState<T>, BasicVector<T>>(context);
+            scaled.set_value(&(input.value() * self.gain.clone()));
+        }
+
+        fn calc_label(&self, context: &LeafContext<T>, label: &mut dyn AbstractValue) {
+            let input = self
+                .input_port(&self.label)
+                .eval::<LeafState<T>, String>(context);
+            label.set_from(&Value::new(input));
+        }
+    }
+
+    // Named by default after the struct, with the run of capitals PID as a single word.
+    #[leaf_system]
+    struct PIDGainSource<T: AtlasScalar> {
+        gain: T,
+    }
+
+    #[test]
+    fn test_leaf_system_attribute() {
+        let scaler = Scaler::<f64>::new(2.0, 3);
+        assert_eq!(Rc::strong_count(&scaler), 1);
+        assert_eq!(scaler.borrow().name(), "scaler");
+        assert_eq!(scaler.borrow().size, 3);
+        assert_eq!(scaler.borrow().scaled, OutputPortIndex::new(0));
+        assert_eq!(scaler.borrow().echoed_label, OutputPortIndex::new(1));
+
+        let input_ports = System::input_ports(&*scaler.borrow())
+            .iter()
+            .map(|input_port| (input_port.name().to_string(), input_port.size()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            input_ports,
+            vec![("input".to_string(), 3), ("label".to_string(), 0)]
+        );
+        let output_ports = System::output_ports(&*scaler.borrow())
+            .iter()
+            .map(|output_port| (output_port.name().to_string(), output_port.size()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            output_ports,
+            vec![("scaled".to_string(), 3), ("echoed_label".to_string(), 0)]
+        );
+        assert_eq!(
+            scaler.borrow().get_direct_feedthroughs(),
+            vec![
+                (InputPortIndex::new(0), OutputPortIndex::new(0)),
+                (InputPortIndex::new(1), OutputPortIndex::new(1)),
+            ]
+        );
+
+        let context = scaler.borrow().create_default_context();
+        scaler
+            .borrow_mut()
+            .input_port_mut(&InputPortIndex::new(0))
+            .fix_value(
+                &mut *context.borrow_mut(),
+                BasicVector::<f64>::from_vec(vec![1.0, 2.0, 3.0]),
+            );
+        scaler
+            .borrow_mut()
+            .input_port_mut(&InputPortIndex::new(1))
+            .fix_value(&mut *context.borrow_mut(), "label".to_string());
+        let scaled = scaler
+            .borrow()
+            .leaf_output_port(&OutputPortIndex::new(0))
+            .eval::<BasicVector<f64>>(&mut context.borrow_mut());
+        assert_eq!(
+            *scaled.value(),
+            na::DVector::<f64>::from_vec(vec![2.0, 4.0, 6.0])
+        );
+        let label = scaler
+            .borrow()
+            .leaf_output_port(&OutputPortIndex::new(1))
+            .eval::<String>(&mut context.borrow_mut());
+        assert_eq!(label, "label");
+    }
+
+    #[test]
+    fn test_leaf_system_default_name() {
+        let pid_gain_source = PIDGainSource::<f64>::new(2.0);
+        assert_eq!(pid_gain_source.borrow().name(), "pid_gain_source");
+        assert_eq!(pid_gain_source.borrow().gain, 2.0);
+    }
+    #[test]
+    fn test_abstract_output_calc_closure() {
+        let pid_gain_source = PIDGainSource::<f64>::new(2.0);
+        let prefix = "gain".to_string();
+        {
+            let mut pid_gain_source = pid_gain_source.borrow_mut();
+            let calc = pid_gain_source.make_abstract_output_calc(
+                move |pid_gain_source: &PIDGainSource<f64>,
+                      _context: &LeafContext<f64>,
+                      output: &mut dyn AbstractValue| {
+                    output.set_from(&Value::new(format!("{} {}", prefix, pid_gain_source.gain)));
+                },
+            );
+            pid_gain_source.declare_abstract_output_port(
+                "description".to_string(),
+                <PIDGainSource<f64> as LeafSystem<f64>>::make_allocate_callback(String::new()),
+                calc,
+            );
+        }
+
+        let context = pid_gain_source.borrow().create_default_context();
+        let description = pid_gain_source
+            .borrow()
+            .leaf_output_port(&OutputPortIndex::new(0))
+            .eval::<String>(&mut context.borrow_mut());
+        assert_eq!(description, "gain 2");
+    }
+}
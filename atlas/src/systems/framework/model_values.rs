@@ -19,7 +19,7 @@ impl ModelValues {
 
     pub fn add_model(&mut self, index: usize, model_value: Box<dyn AbstractValue>) {
         if index >= self.size() {
-            self.values.resize_with(index, Default::default)
+            self.values.resize_with(index + 1, Default::default)
         }
         self.values[index] = Some(model_value);
     }
@@ -0,0 +1,149 @@
+use std::fmt::Debug;
+
+use crate::common::atlas_scalar::AtlasScalar;
+use crate::common::value::AbstractValue;
+use crate::systems::framework::abstract_values::AbstractValues;
+use crate::systems::framework::basic_vector::BasicVector;
+use crate::systems::framework::discrete_values::DiscreteValues;
+use crate::systems::framework::framework_common::{
+    AbstractParameterIndex, AbstractStateIndex, DiscreteStateIndex, NumericParameterIndex,
+};
+use crate::systems::framework::vector_base::VectorBase;
+
+// The parameters of a System: groups of numeric values and values of arbitrary types. A Diagram's
+// parameters view those of its subsystems, in subsystem order.
+#[derive(Clone, Default)]
+pub struct Parameters<T: AtlasScalar> {
+    numeric_parameters: DiscreteValues<T>,
+    abstract_parameters: AbstractValues,
+}
+
+impl<T: AtlasScalar> Parameters<T> {
+    pub fn new(numeric_parameters: DiscreteValues<T>, abstract_parameters: AbstractValues) -> Self {
+        Parameters {
+            numeric_parameters,
+            abstract_parameters,
+        }
+    }
+
+    pub fn add_numeric_parameter(&mut self, model_vector: BasicVector<T>) -> NumericParameterIndex {
+        let index = self.numeric_parameters.add_group(model_vector);
+        NumericParameterIndex::new(index.value())
+    }
+
+    pub fn add_abstract_parameter(
+        &mut self,
+        model_value: Box<dyn AbstractValue>,
+    ) -> AbstractParameterIndex {
+        let index = self.abstract_parameters.add_value(model_value);
+        AbstractParameterIndex::new(index.value())
+    }
+
+    pub fn num_numeric_parameter_groups(&self) -> usize {
+        self.numeric_parameters.num_groups()
+    }
+
+    pub fn num_abstract_parameters(&self) -> usize {
+        self.abstract_parameters.size()
+    }
+
+    pub fn numeric_parameters(&self) -> &DiscreteValues<T> {
+        &self.numeric_parameters
+    }
+
+    pub fn numeric_parameters_mut(&mut self) -> &mut DiscreteValues<T> {
+        &mut self.numeric_parameters
+    }
+
+    pub fn abstract_parameters(&self) -> &AbstractValues {
+        &self.abstract_parameters
+    }
+
+    pub fn abstract_parameters_mut(&mut self) -> &mut AbstractValues {
+        &mut self.abstract_parameters
+    }
+
+    pub fn numeric_parameter(
+        &self,
+        index: &NumericParameterIndex,
+    ) -> &dyn VectorBase<T, Output = T> {
+        self.numeric_parameters
+            .vector(&DiscreteStateIndex::new(index.value()))
+    }
+
+    pub fn numeric_parameter_mut(
+        &mut self,
+        index: &NumericParameterIndex,
+    ) -> &mut dyn VectorBase<T, Output = T> {
+        self.numeric_parameters
+            .vector_mut(&DiscreteStateIndex::new(index.value()))
+    }
+
+    pub fn abstract_parameter_value(&self, index: &AbstractParameterIndex) -> &dyn AbstractValue {
+        self.abstract_parameters
+            .value(&AbstractStateIndex::new(index.value()))
+    }
+
+    // Panics if the parameter at index is not a ValueType.
+    pub fn abstract_parameter<ValueType: 'static + Clone + Debug>(
+        &self,
+        index: &AbstractParameterIndex,
+    ) -> &ValueType {
+        self.abstract_parameters
+            .get_value::<ValueType>(&AbstractStateIndex::new(index.value()))
+    }
+
+    pub fn abstract_parameter_mut<ValueType: 'static + Clone + Debug>(
+        &mut self,
+        index: &AbstractParameterIndex,
+    ) -> &mut ValueType {
+        self.abstract_parameters
+            .get_value_mut::<ValueType>(&AbstractStateIndex::new(index.value()))
+    }
+
+    // Copies the values of other, which must have the same structure, without reallocating.
+    pub fn set_from(&mut self, other: &Parameters<T>) {
+        self.numeric_parameters.set_from(&other.numeric_parameters);
+        self.abstract_parameters
+            .set_from(&other.abstract_parameters);
+    }
+
+    // Appends the views of other's parameters, which must outlive self.
+    pub fn append_views(&mut self, other: &mut Parameters<T>) {
+        self.numeric_parameters
+            .append_groups(other.numeric_parameters.group_views());
+        self.abstract_parameters
+            .append_views(other.abstract_parameters.value_ptrs());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate nalgebra as na;
+
+    use super::*;
+    use crate::common::value::Value;
+
+    #[test]
+    fn test_numeric_and_abstract_parameters() {
+        let mut parameters = Parameters::<f64>::default();
+        let numeric_index =
+            parameters.add_numeric_parameter(BasicVector::<f64>::from_vec(vec![1.0, 2.0]));
+        let abstract_index = parameters.add_abstract_parameter(Box::new(Value::<bool>::new(true)));
+        assert_eq!(parameters.num_numeric_parameter_groups(), 1);
+        assert_eq!(parameters.num_abstract_parameters(), 1);
+
+        let mut views = Parameters::<f64>::default();
+        views.append_views(&mut parameters);
+        views.numeric_parameter_mut(&numeric_index)[1] = 3.0;
+        *views.abstract_parameter_mut::<bool>(&abstract_index) = false;
+
+        assert_eq!(
+            parameters
+                .numeric_parameter(&numeric_index)
+                .copy_to_vector(),
+            na::DVector::<f64>::from_vec(vec![1.0, 3.0])
+        );
+        assert!(!*parameters.abstract_parameter::<bool>(&abstract_index));
+    }
+}
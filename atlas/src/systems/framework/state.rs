@@ -1,10 +1,28 @@
 use crate::common::atlas_scalar::AtlasScalar;
+use crate::systems::framework::abstract_values::AbstractValues;
 use crate::systems::framework::continuous_state::ContinuousState;
+use crate::systems::framework::discrete_values::DiscreteValues;
 
-pub trait State<T: AtlasScalar> {
+pub trait State<T: AtlasScalar>: Clone {
     type CS: ContinuousState<T>;
 
     fn continuous_state(&self) -> &Self::CS;
 
     fn continuous_state_mut(&mut self) -> &mut Self::CS;
+
+    fn discrete_state(&self) -> &DiscreteValues<T>;
+
+    fn discrete_state_mut(&mut self) -> &mut DiscreteValues<T>;
+
+    fn abstract_state(&self) -> &AbstractValues;
+
+    fn abstract_state_mut(&mut self) -> &mut AbstractValues;
+
+    // Copies the values of other, which must have the same structure, without reallocating.
+    fn set_from(&mut self, other: &Self) {
+        self.continuous_state_mut()
+            .set_from_vector(&other.continuous_state().vector().copy_to_vector());
+        self.discrete_state_mut().set_from(other.discrete_state());
+        self.abstract_state_mut().set_from(other.abstract_state());
+    }
 }
@@ -20,12 +20,12 @@ impl<T: AtlasScalar> Subvector<T> {
         }
     }
 
-    pub fn value(&self) -> na::DVectorView<T> {
+    pub fn value(&self) -> na::DVectorView<'_, T> {
         let vector = unsafe { &*self.vector };
         vector.rows(self.first_index, self.num_elements)
     }
 
-    pub fn value_mut(&mut self) -> na::DVectorViewMut<T> {
+    pub fn value_mut(&mut self) -> na::DVectorViewMut<'_, T> {
         let vector = unsafe { &mut *self.vector };
         vector.rows_mut(self.first_index, self.num_elements)
     }
@@ -0,0 +1,167 @@
+use std::ops::{Index, IndexMut};
+
+extern crate nalgebra as na;
+
+use crate::common::atlas_scalar::AtlasScalar;
+use crate::systems::framework::subvector::Subvector;
+use crate::systems::framework::vector_base::VectorBase;
+
+// A vector made by concatenating Subvectors that may point into different storage.
+pub struct Supervector<T: AtlasScalar> {
+    pieces: Vec<Subvector<T>>,
+}
+
+impl<T: AtlasScalar> Supervector<T> {
+    pub fn new(pieces: Vec<Subvector<T>>) -> Self {
+        Supervector { pieces }
+    }
+
+    pub fn num_pieces(&self) -> usize {
+        self.pieces.len()
+    }
+
+    // Returns the piece holding the element at index and the index within that piece.
+    fn piece_and_offset(&self, index: usize) -> (usize, usize) {
+        let mut offset = index;
+        for (piece_index, piece) in self.pieces.iter().enumerate() {
+            if offset < piece.size() {
+                return (piece_index, offset);
+            }
+            offset -= piece.size();
+        }
+        panic!(
+            "Supervector: index {} is out of range for a vector of size {}",
+            index,
+            self.size()
+        );
+    }
+}
+
+impl<T: AtlasScalar> Index<usize> for Supervector<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        self.at_index(index)
+    }
+}
+
+impl<T: AtlasScalar> IndexMut<usize> for Supervector<T> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        self.at_index_mut(index)
+    }
+}
+
+impl<T: AtlasScalar> VectorBase<T> for Supervector<T> {
+    fn size(&self) -> usize {
+        self.pieces.iter().map(|piece| piece.size()).sum()
+    }
+
+    fn at_index(&self, index: usize) -> &T {
+        let (piece_index, offset) = self.piece_and_offset(index);
+        self.pieces[piece_index].at_index(offset)
+    }
+
+    fn at_index_mut(&mut self, index: usize) -> &mut T {
+        let (piece_index, offset) = self.piece_and_offset(index);
+        self.pieces[piece_index].at_index_mut(offset)
+    }
+
+    // Subvectors view contiguous storage, so the requested range must lie within a single piece.
+    fn subvector(&self, start: usize, shape: usize) -> Subvector<T> {
+        let mut offset = start;
+        for piece in self.pieces.iter() {
+            if offset + shape <= piece.size() {
+                return piece.subvector(offset, shape);
+            }
+            if offset < piece.size() {
+                break;
+            }
+            offset -= piece.size();
+        }
+        panic!(
+            "Supervector: the range [{}, {}) spans more than one piece",
+            start,
+            start + shape
+        );
+    }
+
+    fn subvector_mut(&mut self, start: usize, shape: usize) -> Subvector<T> {
+        self.subvector(start, shape)
+    }
+
+    fn set_at_index(&mut self, index: usize, value: T) {
+        *self.at_index_mut(index) = value;
+    }
+
+    fn set_from(&mut self, value: &dyn VectorBase<T, Output = T>) {
+        assert_eq!(self.size(), value.size());
+        for i in 0..self.size() {
+            self.set_at_index(i, value.at_index(i).clone());
+        }
+    }
+
+    fn set_from_vector(&mut self, value: &na::DVector<T>) {
+        assert_eq!(self.size(), value.len());
+        let mut start = 0;
+        for piece in self.pieces.iter_mut() {
+            let size = piece.size();
+            piece.set_from_vector(&value.rows(start, size).into_owned());
+            start += size;
+        }
+    }
+
+    fn fill(&mut self, value: &T) {
+        for piece in self.pieces.iter_mut() {
+            piece.fill(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::systems::framework::basic_vector::BasicVector;
+
+    #[test]
+    fn test_concatenated_pieces() {
+        let mut a = BasicVector::<f64>::from_vec(vec![1.0, 2.0, 3.0]);
+        let mut b = BasicVector::<f64>::from_vec(vec![4.0, 5.0]);
+        let mut supervector = Supervector::new(vec![a.subvector_mut(1, 2), b.subvector_mut(0, 2)]);
+
+        assert_eq!(supervector.size(), 4);
+        assert_eq!(
+            supervector.copy_to_vector(),
+            na::DVector::<f64>::from_vec(vec![2.0, 3.0, 4.0, 5.0])
+        );
+
+        supervector.set_from_vector(&na::DVector::<f64>::from_vec(vec![6.0, 7.0, 8.0, 9.0]));
+        supervector[3] = 10.0;
+        assert_eq!(a, BasicVector::<f64>::from_vec(vec![1.0, 6.0, 7.0]));
+        assert_eq!(b, BasicVector::<f64>::from_vec(vec![8.0, 10.0]));
+    }
+
+    #[test]
+    fn test_subvector_within_piece() {
+        let mut a = BasicVector::<f64>::from_vec(vec![1.0, 2.0]);
+        let mut b = BasicVector::<f64>::from_vec(vec![3.0, 4.0]);
+        let supervector = Supervector::new(vec![
+            a.subvector_mut(0, 2),
+            b.subvector_mut(0, 0),
+            b.subvector_mut(0, 2),
+        ]);
+
+        let subvector = supervector.subvector(2, 2);
+        assert_eq!(subvector.copy_to_vector(), b.value().clone());
+        assert_eq!(supervector.subvector(2, 0).size(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "spans more than one piece")]
+    fn test_subvector_across_pieces() {
+        let mut a = BasicVector::<f64>::from_vec(vec![1.0, 2.0]);
+        let mut b = BasicVector::<f64>::from_vec(vec![3.0, 4.0]);
+        let supervector = Supervector::new(vec![a.subvector_mut(0, 2), b.subvector_mut(0, 2)]);
+
+        supervector.subvector(1, 2);
+    }
+}
@@ -1,24 +1,28 @@
-use std::any::Any;
+use std::any::{Any, TypeId};
 use std::cell::RefCell;
 use std::rc::Rc;
 
+extern crate nalgebra as na;
+
+use crate::common::atlas_error::AtlasError;
 use crate::common::atlas_scalar::AtlasScalar;
+use crate::common::autodiff::AutoDiffXd;
 use crate::common::value::AbstractValue;
 use crate::systems::framework::basic_vector::BasicVector;
-use crate::systems::framework::cache_entry::CacheEntry;
 use crate::systems::framework::context::Context;
 use crate::systems::framework::context_base::ContextBase;
 use crate::systems::framework::continuous_state::ContinuousState;
-use crate::systems::framework::diagram::SystemWeakLink;
-use crate::systems::framework::framework_common::{
-    CacheIndex, InputPortIndex, OutputPortIndex, PortDataType,
-};
+use crate::systems::framework::diagram::{SystemLink, SystemWeakLink};
+use crate::systems::framework::discrete_values::DiscreteValues;
+use crate::systems::framework::event::PeriodicEventData;
+use crate::systems::framework::framework_common::{InputPortIndex, OutputPortIndex, PortDataType};
 use crate::systems::framework::input_port::InputPort;
 use crate::systems::framework::input_port_base::InputPortBase;
 use crate::systems::framework::output_port::OutputPort;
 use crate::systems::framework::port_base::PortBase;
 use crate::systems::framework::state::State;
 use crate::systems::framework::system_base::SystemBase;
+use crate::systems::framework::system_scalar_converter::unbox_converted_system;
 
 pub trait AbstractSystem: Any {
     fn as_any(&self) -> &dyn Any;
@@ -116,8 +120,8 @@ where
     fn allocate_input_abstract(&self, input_port: &InputPort<T>) -> Box<dyn AbstractValue> {
         self.do_allocate_input(input_port)
     }
-    fn allocate_time_derivatives(&mut self) -> Box<<<Self::CN as Context<T>>::S as State<T>>::CS>;
-    fn create_default_context(&mut self) -> Rc<RefCell<Self::CN>> {
+    fn allocate_time_derivatives(&self) -> Box<<<Self::CN as Context<T>>::S as State<T>>::CS>;
+    fn create_default_context(&self) -> Rc<RefCell<Self::CN>> {
         let context = self.allocate_context();
         self.set_default_context(&mut context.borrow_mut());
         context
@@ -125,41 +129,135 @@ where
 
     // TODO: Consider inputting &dyn Context<T> and &mut State<T>
     fn set_default_state(&self, context: &mut Self::CN);
-    fn set_default_context(&mut self, context: &mut Self::CN) {
+    fn set_default_parameters(&self, context: &mut Self::CN);
+    fn set_default_context(&self, context: &mut Self::CN) {
+        self.set_default_parameters(context);
         self.set_default_state(context);
     }
 
-    // Cached evaluations
-    fn eval_time_derivatives<'a>(
-        &mut self,
-        context: &'a mut Self::CN,
-    ) -> &'a <<Self::CN as Context<T>>::S as State<T>>::CS {
-        let cache_entry = self.time_derivatives_cache_entry();
+    // Direct feedthrough
+    // Whether the value of the output port can depend on the value of the input port at the same
+    // time, without going through state. A cycle of such dependencies is an algebraic loop.
+    fn has_direct_feedthrough(
+        &self,
+        input_port_index: &InputPortIndex,
+        output_port_index: &OutputPortIndex,
+    ) -> bool;
+    // Every (input, output) pair with direct feedthrough. The default asks about each pair, so it
+    // is as conservative as has_direct_feedthrough.
+    fn get_direct_feedthroughs(&self) -> Vec<(InputPortIndex, OutputPortIndex)> {
+        let mut feedthroughs = vec![];
+        for i in 0..self.num_input_ports() {
+            for j in 0..self.num_output_ports() {
+                let input_port_index = InputPortIndex::new(i);
+                let output_port_index = OutputPortIndex::new(j);
+                if self.has_direct_feedthrough(&input_port_index, &output_port_index) {
+                    feedthroughs.push((input_port_index, output_port_index));
+                }
+            }
+        }
+        feedthroughs
+    }
+
+    // Events
+    // The distinct timings of the periodic events declared by this System and its subsystems.
+    fn periodic_discrete_update_timings(&self) -> Vec<PeriodicEventData>;
+    fn periodic_unrestricted_update_timings(&self) -> Vec<PeriodicEventData>;
+    // Applies the discrete updates of the events with the given timings to discrete_state, which
+    // must start out as a copy of the context's discrete state.
+    fn calc_discrete_variable_updates(
+        &self,
+        context: &Self::CN,
+        timings: &[PeriodicEventData],
+        discrete_state: &mut DiscreteValues<T>,
+    );
+    // Applies the unrestricted updates of the events with the given timings to state, which must
+    // start out as a copy of the context's state.
+    fn calc_unrestricted_updates(
+        &self,
+        context: &Self::CN,
+        timings: &[PeriodicEventData],
+        state: &mut <Self::CN as Context<T>>::S,
+    );
 
-        cache_entry.eval(context.as_mutable_base())
+    // Scalar conversion
+    // This System rebuilt with the scalar type U identified by scalar_type, as a boxed
+    // Result<SystemLink<U>, AtlasError>, or None if the System does not support U. Use
+    // SystemLink::try_to_scalar_type instead.
+    fn do_to_scalar_type(&self, _scalar_type: TypeId) -> Option<Box<dyn Any>> {
+        None
     }
-    fn time_derivatives_cache_entry(&self) -> &CacheEntry {
-        self.cache_entry(self.time_derivatives_cache_index())
+    // This System rebuilt with the AutoDiffXd scalar type, to evaluate partial derivatives.
+    fn to_autodiff(&self) -> Result<SystemLink<AutoDiffXd>, AtlasError> {
+        let converted = self.do_to_scalar_type(TypeId::of::<AutoDiffXd>());
+        unbox_converted_system(converted, self.name())
+    }
+
+    // Evaluations
+    fn eval_time_derivatives(
+        &self,
+        context: &Self::CN,
+    ) -> Box<<<Self::CN as Context<T>>::S as State<T>>::CS> {
+        let mut derivatives = self.allocate_time_derivatives();
+        self.calc_time_derivatives(context, Some(derivatives.as_mut()));
+
+        derivatives
     }
-    fn time_derivatives_cache_index(&self) -> &CacheIndex;
 
     // Calculations
     fn calc_time_derivatives(
-        &mut self,
-        context: &mut Self::CN,
+        &self,
+        context: &Self::CN,
         derivatives: Option<&mut <<Self::CN as Context<T>>::S as State<T>>::CS>,
     ) {
-        self.validate_context(context.as_base());
+        self.validate_context(context.as_base())
+            .unwrap_or_else(|error| panic!("{}", error));
         self.do_calc_time_derivatives(context, derivatives.unwrap());
     }
 
     fn do_calc_time_derivatives(
-        &mut self,
-        _context: &mut Self::CN,
+        &self,
+        _context: &Self::CN,
         derivatives: &mut <<Self::CN as Context<T>>::S as State<T>>::CS,
     ) {
         // This default implementation is only valid for Systems with no continuous
         // state. Other Systems must override this method!
         assert!(derivatives.size() == 0);
     }
+
+    // Evaluates the residual of the implicit form of the dynamics, 0 = f(t, x, xdot), for the
+    // proposed time derivatives. The residual has implicit_time_derivatives_residual_size()
+    // elements.
+    fn calc_implicit_time_derivatives_residual(
+        &self,
+        context: &Self::CN,
+        proposed_derivatives: &<<Self::CN as Context<T>>::S as State<T>>::CS,
+        residual: &mut na::DVector<T>,
+    ) {
+        assert_eq!(
+            residual.len(),
+            self.implicit_time_derivatives_residual_size(),
+            "System::calc_implicit_time_derivatives_residual: residual has the wrong size"
+        );
+        self.validate_context(context.as_base())
+            .unwrap_or_else(|error| panic!("{}", error));
+        self.do_calc_implicit_time_derivatives_residual(context, proposed_derivatives, residual);
+    }
+
+    fn do_calc_implicit_time_derivatives_residual(
+        &self,
+        context: &Self::CN,
+        proposed_derivatives: &<<Self::CN as Context<T>>::S as State<T>>::CS,
+        residual: &mut na::DVector<T>,
+    ) {
+        // The default residual, proposed_derivatives - xdot(t, x), is only valid when the
+        // residual size has not been changed. Systems declaring a different size must override
+        // this method!
+        assert_eq!(residual.len(), proposed_derivatives.size());
+        let derivatives = self.eval_time_derivatives(context);
+        for i in 0..residual.len() {
+            residual[i] = proposed_derivatives.vector().at_index(i).clone()
+                - derivatives.vector().at_index(i).clone();
+        }
+    }
 }
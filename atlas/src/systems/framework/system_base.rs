@@ -4,11 +4,13 @@ use std::{
     rc::Weak,
 };
 
+use crate::common::atlas_error::AtlasError;
 use crate::common::value::AbstractValue;
-use crate::systems::framework::cache_entry::CacheEntry;
+use crate::systems::framework::cache_entry::{CacheEntries, CacheEntry};
 use crate::systems::framework::context_base::ContextBase;
 use crate::systems::framework::framework_common::{
-    CacheIndex, InputPortIndex, OutputPortIndex, SystemId, SystemParentServiceInterface,
+    CacheIndex, DependencyTicket, InputPortIndex, OutputPortIndex, SystemId,
+    SystemParentServiceInterface,
 };
 use crate::systems::framework::input_port_base::InputPortBase;
 use crate::systems::framework::output_port_base::OutputPortBase;
@@ -19,6 +21,8 @@ pub struct ContextSizes {
     pub num_generalized_positions: usize,
     pub num_generalized_velocities: usize,
     pub num_misc_continuous_states: usize,
+    pub num_discrete_state_groups: usize,
+    pub num_abstract_states: usize,
 }
 
 impl Add for ContextSizes {
@@ -32,6 +36,9 @@ impl Add for ContextSizes {
                 + other.num_generalized_velocities,
             num_misc_continuous_states: self.num_misc_continuous_states
                 + other.num_misc_continuous_states,
+            num_discrete_state_groups: self.num_discrete_state_groups
+                + other.num_discrete_state_groups,
+            num_abstract_states: self.num_abstract_states + other.num_abstract_states,
         }
     }
 }
@@ -41,6 +48,8 @@ impl AddAssign<&ContextSizes> for ContextSizes {
         self.num_generalized_positions += rhs.num_generalized_positions;
         self.num_generalized_velocities += rhs.num_generalized_velocities;
         self.num_misc_continuous_states += rhs.num_misc_continuous_states;
+        self.num_discrete_state_groups += rhs.num_discrete_state_groups;
+        self.num_abstract_states += rhs.num_abstract_states;
     }
 }
 
@@ -49,6 +58,8 @@ impl AddAssign<&ContextSizes> for &mut ContextSizes {
         self.num_generalized_positions += rhs.num_generalized_positions;
         self.num_generalized_velocities += rhs.num_generalized_velocities;
         self.num_misc_continuous_states += rhs.num_misc_continuous_states;
+        self.num_discrete_state_groups += rhs.num_discrete_state_groups;
+        self.num_abstract_states += rhs.num_abstract_states;
     }
 }
 
@@ -60,8 +71,8 @@ pub trait SystemBase {
     fn input_ports_mut(&mut self) -> Vec<&mut dyn InputPortBase>;
     fn output_ports(&self) -> Vec<&dyn OutputPortBase>;
     fn output_ports_mut(&mut self) -> Vec<&mut dyn OutputPortBase>;
-    fn cache_entries(&self) -> &Vec<CacheEntry>;
-    fn cache_entries_mut(&mut self) -> &mut Vec<CacheEntry>;
+    fn cache_entries(&self) -> &CacheEntries;
+    fn cache_entries_mut(&mut self) -> &mut CacheEntries;
     fn context_sizes(&self) -> &ContextSizes;
     fn context_sizes_mut(&mut self) -> &mut ContextSizes;
     fn system_id(&self) -> &SystemId;
@@ -79,24 +90,53 @@ pub trait SystemBase {
 
     fn initialize_context_base(&self, context: &mut dyn ContextBase) {
         context.set_system_id(self.system_id().clone());
+        context.set_system_name(self.name().clone());
 
         self.create_source_trackers(context);
 
-        let mut cache = context.cache().borrow_mut();
-        for index in 0..self.num_cache_entries() {
-            let cache_index = CacheIndex::new(index);
-            let cache_entry = self.cache_entry(&cache_index);
-            let cache_value = cache.create_new_cache_entry_value(cache_index);
-            cache_value.set_initial_value(cache_entry.allocate());
+        let graph = context.graph().clone();
+        {
+            let mut cache = context.cache().borrow_mut();
+            for index in 0..self.num_cache_entries() {
+                let cache_index = CacheIndex::new(index);
+                let cache_entry = self.cache_entry(&cache_index);
+                let cache_value = cache.create_new_cache_entry_value(cache_index.clone());
+                cache_value.set_initial_value(cache_entry.allocate());
+                graph.add_cache_entry_tracker(cache_index);
+            }
+        }
+
+        // A cache entry may depend on another one declared after it, so every tracker is created
+        // before the subscriptions.
+        for cache_entry in self.cache_entries() {
+            for prerequisite in cache_entry.prerequisites() {
+                graph.subscribe_to_prerequisite(&cache_entry.ticket(), &graph, prerequisite);
+            }
         }
 
-        // TODO: Add an output port ticket to the context
-        // for output_port in self.output_ports() {
-        //     context.add_output_port(output_port.index());
-        // }
+        // The output ports subscribe to their sources when the System knows what they are, e.g.
+        // a LeafSystem subscribes each to its cache entry.
+        for output_port in self.output_ports() {
+            graph.add_tracker(DependencyTicket::OutputPort(output_port.index().clone()));
+        }
     }
 
     fn create_source_trackers(&self, context: &mut dyn ContextBase) {
+        let graph = context.graph().clone();
+        let sources = [
+            DependencyTicket::Time,
+            DependencyTicket::ContinuousState,
+            DependencyTicket::DiscreteState,
+            DependencyTicket::AbstractState,
+            DependencyTicket::Parameters,
+            DependencyTicket::AllInputPorts,
+        ];
+        graph.add_tracker(DependencyTicket::AllSources);
+        for source in sources {
+            graph.add_tracker(source.clone());
+            graph.subscribe_to_prerequisite(&DependencyTicket::AllSources, &graph, &source);
+        }
+
         for input_port in self.input_ports() {
             context.add_input_port(input_port.index());
         }
@@ -158,10 +198,15 @@ pub trait SystemBase {
     fn cache_mut_entry(&mut self, index: &CacheIndex) -> &mut CacheEntry {
         &mut self.cache_entries_mut()[index]
     }
-    fn declare_cache_entry(&mut self, value_producer: ValueProducer) -> &CacheEntry {
+    fn declare_cache_entry(
+        &mut self,
+        value_producer: ValueProducer,
+        prerequisites_of_calc: Vec<DependencyTicket>,
+    ) -> &CacheEntry {
         let cache_index = CacheIndex::new(self.num_cache_entries());
-        let cache_entry = CacheEntry::new(cache_index.clone(), value_producer);
-        self.cache_entries_mut().push(cache_entry);
+        let cache_entry =
+            CacheEntry::new(cache_index.clone(), value_producer, prerequisites_of_calc);
+        self.cache_entries_mut().push(Box::new(cache_entry));
 
         self.cache_entry(&cache_index)
     }
@@ -177,7 +222,15 @@ pub trait SystemBase {
 
     fn implicit_time_derivatives_residual_size(&self) -> usize;
 
-    fn validate_context(&self, context: &dyn ContextBase) {
-        assert!(*context.system_id() == *self.system_id())
+    fn validate_context(&self, context: &dyn ContextBase) -> Result<(), AtlasError> {
+        if *context.system_id() != *self.system_id() {
+            return Err(AtlasError::ContextMismatch {
+                system: self.name().clone(),
+                system_id: *self.system_id().value(),
+                context_system: context.system_name().to_string(),
+                context_system_id: *context.system_id().value(),
+            });
+        }
+        Ok(())
     }
 }
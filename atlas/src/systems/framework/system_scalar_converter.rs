@@ -0,0 +1,49 @@
+use std::any::{type_name, Any, TypeId};
+
+use crate::common::atlas_error::AtlasError;
+use crate::common::atlas_scalar::AtlasScalar;
+use crate::common::autodiff::AutoDiffXd;
+use crate::systems::framework::diagram::SystemLink;
+
+// A System that can be rebuilt with another scalar type U, with the same configuration. Such a
+// System forwards System::do_to_scalar_type to convert_to_scalar_type.
+pub trait ScalarConvertible {
+    fn convert_scalar_type<U: AtlasScalar>(&self) -> Result<SystemLink<U>, AtlasError>;
+}
+
+// Converts system to the scalar type identified by scalar_type, if it is one of the scalar types
+// Systems are converted between. The result is a Result<SystemLink<U>, AtlasError> for that type,
+// boxed since System::do_to_scalar_type cannot be generic.
+pub fn convert_to_scalar_type<S: ScalarConvertible>(
+    system: &S,
+    scalar_type: TypeId,
+) -> Option<Box<dyn Any>> {
+    if scalar_type == TypeId::of::<f64>() {
+        Some(Box::new(system.convert_scalar_type::<f64>()))
+    } else if scalar_type == TypeId::of::<f32>() {
+        Some(Box::new(system.convert_scalar_type::<f32>()))
+    } else if scalar_type == TypeId::of::<AutoDiffXd>() {
+        Some(Box::new(system.convert_scalar_type::<AutoDiffXd>()))
+    } else {
+        None
+    }
+}
+
+// Unboxes what System::do_to_scalar_type returned for U, and names the converted System after the
+// original one.
+pub fn unbox_converted_system<U: AtlasScalar>(
+    converted: Option<Box<dyn Any>>,
+    system_name: &str,
+) -> Result<SystemLink<U>, AtlasError> {
+    let converted = converted.ok_or_else(|| AtlasError::UnsupportedScalarType {
+        system: system_name.to_string(),
+        scalar_type: type_name::<U>().to_string(),
+    })?;
+    let converted = converted
+        .downcast::<Result<SystemLink<U>, AtlasError>>()
+        .unwrap();
+    let mut converted = (*converted)?;
+    converted.set_name(system_name.to_string());
+
+    Ok(converted)
+}
@@ -16,6 +16,9 @@ pub trait VectorBase<T: AtlasScalar, Output = T>: Index<usize> + IndexMut<usize>
     fn set_from(&mut self, value: &dyn VectorBase<T, Output = T>);
     fn set_from_vector(&mut self, value: &na::DVector<T>);
     fn fill(&mut self, value: &T);
+    fn copy_to_vector(&self) -> na::DVector<T> {
+        na::DVector::<T>::from_fn(self.size(), |i, _| self.at_index(i).clone())
+    }
 }
 
 impl<T: AtlasScalar> Default for Box<dyn VectorBase<T, Output = T>> {
@@ -1,2 +1,17 @@
 pub mod adder;
 pub mod affine_system;
+pub mod constant_value_source;
+pub mod constant_vector_source;
+pub mod dead_zone;
+pub mod demultiplexer;
+pub mod gain;
+pub mod integrator;
+pub mod linear_system;
+pub mod matrix_gain;
+pub mod multiplexer;
+pub mod ramp;
+pub mod rate_limiter;
+pub mod saturation;
+pub mod sine;
+pub mod step;
+pub mod time_source;
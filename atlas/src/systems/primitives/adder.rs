@@ -1,88 +1,30 @@
-use std::any::Any;
 use std::cell::RefCell;
-use std::rc::{Rc, Weak};
+use std::rc::Rc;
 
-use atlas_derives::{AbstractSystem, LeafSystem, System, SystemBase};
+use atlas_derives::leaf_system;
 
 extern crate nalgebra as na;
 
+use crate::common::atlas_error::AtlasError;
 use crate::common::atlas_scalar::AtlasScalar;
-use crate::common::value::AbstractValue;
 use crate::systems::framework::basic_vector::BasicVector;
-use crate::systems::framework::cache_entry::CacheEntry;
-use crate::systems::framework::context::Context;
-use crate::systems::framework::diagram::SystemWeakLink;
-use crate::systems::framework::framework_common::InputPortIndex;
-use crate::systems::framework::framework_common::OutputPortIndex;
-use crate::systems::framework::framework_common::{
-    CacheIndex, PortDataType, SystemId, SystemParentServiceInterface,
-};
-use crate::systems::framework::input_port::InputPort;
-use crate::systems::framework::input_port_base::InputPortBase;
-use crate::systems::framework::leaf_context::LeafContext;
-use crate::systems::framework::leaf_output_port::LeafOutputPort;
+use crate::systems::framework::diagram::SystemLink;
+use crate::systems::framework::framework_common::DependencyTicket;
+use crate::systems::framework::framework_common::PortDataType;
 use crate::systems::framework::leaf_state::LeafState;
 use crate::systems::framework::leaf_system::LeafSystem;
-use crate::systems::framework::model_values::ModelValues;
-use crate::systems::framework::output_port::OutputPort;
-use crate::systems::framework::output_port_base::OutputPortBase;
-use crate::systems::framework::state::State;
-use crate::systems::framework::system::{AbstractSystem, System};
-use crate::systems::framework::system_base::ContextSizes;
-use crate::systems::framework::system_base::SystemBase;
+use crate::systems::framework::port_base::PortBase;
+use crate::systems::framework::system::System;
+use crate::systems::framework::system_scalar_converter::ScalarConvertible;
 use crate::systems::framework::vector_base::VectorBase;
 
-#[derive(LeafSystem, AbstractSystem, System, SystemBase)]
-pub struct Adder<T: AtlasScalar> {
-    name: String,
-    input_ports: Vec<InputPort<T>>,
-    output_ports: Vec<LeafOutputPort<T>>,
-    cache_entries: Vec<CacheEntry>,
-    context_sizes: ContextSizes,
-    system_id: SystemId,
-    system_weak_link: Option<SystemWeakLink<T>>,
-    parent_service: Option<Weak<RefCell<dyn SystemParentServiceInterface>>>,
-    time_derivatives_cache_index: CacheIndex,
-    model_input_values: ModelValues,
-    model_continuous_state_vector: BasicVector<T>,
-    implicit_time_derivatives_residual_size: Option<usize>,
-}
+#[leaf_system(constructor = "allocate")]
+#[system(scalar_convertible)]
+pub struct Adder<T: AtlasScalar> {}
 
 impl<T: AtlasScalar> Adder<T> {
     pub fn new(num_inputs: usize, size: usize) -> Rc<RefCell<Self>> {
-        let adder = Rc::new(RefCell::new(Self {
-            name: "adder".to_string(),
-            input_ports: vec![],
-            output_ports: vec![],
-            cache_entries: vec![],
-            context_sizes: ContextSizes::default(),
-            system_id: SystemId::new(0),
-            system_weak_link: None,
-            parent_service: None,
-            time_derivatives_cache_index: CacheIndex::new(0),
-            model_input_values: ModelValues::default(),
-            model_continuous_state_vector: BasicVector::<T>::zeros(0),
-            implicit_time_derivatives_residual_size: None,
-        }));
-        // adder.borrow_mut().system_weak_link =
-        //     SystemWeakLink::LeafSystemWeakLink(Rc::downgrade(&adder));
-
-        unsafe {
-            let adder_weak = Rc::downgrade(&adder);
-            let adder_weak_ptr = Weak::into_raw(adder_weak);
-            let system_weak =
-                Weak::<RefCell<dyn System<T, CN = LeafContext<T>>>>::from_raw(adder_weak_ptr);
-            adder.borrow_mut().system_weak_link =
-                Some(SystemWeakLink::LeafSystemWeakLink(system_weak));
-        }
-
-        let calc = {
-            let weak_adder = Rc::downgrade(&adder);
-            Box::new(move |context: &LeafContext<T>, sum: &mut BasicVector<T>| {
-                let adder = weak_adder.upgrade().unwrap();
-                adder.borrow().calc_sum(context, sum);
-            })
-        };
+        let adder = Self::allocate();
 
         for i in 0..num_inputs {
             adder.borrow_mut().declare_input_port(
@@ -91,9 +33,15 @@ impl<T: AtlasScalar> Adder<T> {
                 size,
             );
         }
+        let calc = adder.borrow().make_vector_output_calc(Self::calc_sum);
         adder
             .borrow_mut()
-            .declare_vector_output_port("sum".to_string(), size, calc);
+            .declare_vector_output_port_with_prerequisites(
+                "sum".to_string(),
+                size,
+                calc,
+                vec![DependencyTicket::AllInputPorts],
+            );
 
         adder
     }
@@ -106,15 +54,34 @@ impl<T: AtlasScalar> Adder<T> {
     }
 }
 
+impl<T: AtlasScalar> ScalarConvertible for Adder<T> {
+    fn convert_scalar_type<U: AtlasScalar>(&self) -> Result<SystemLink<U>, AtlasError> {
+        let size = self.output_ports[0].size();
+        Ok(Adder::<U>::new(self.input_ports.len(), size).into())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::systems::framework::context::Context;
+    use crate::systems::framework::framework_common::{InputPortIndex, OutputPortIndex};
+    use crate::systems::framework::system_base::SystemBase;
 
     #[test]
     fn test_constructor() {
         let adder = Adder::<f64>::new(2, 3);
+        // The ports only link back to the adder weakly.
+        assert_eq!(Rc::strong_count(&adder), 1);
         assert_eq!(adder.borrow().input_ports.len(), 2);
         assert_eq!(adder.borrow().output_ports.len(), 1);
+        assert_eq!(
+            adder.borrow().get_direct_feedthroughs(),
+            vec![
+                (InputPortIndex::new(0), OutputPortIndex::new(0)),
+                (InputPortIndex::new(1), OutputPortIndex::new(0)),
+            ]
+        );
     }
 
     #[test]
@@ -123,6 +90,33 @@ mod tests {
         let _context = adder.borrow_mut().create_default_context();
     }
 
+    #[test]
+    fn test_validate_context() {
+        let adder1 = Adder::<f64>::new(2, 3);
+        let adder2 = Adder::<f64>::new(2, 3);
+        adder2.borrow_mut().set_name("other_adder".to_string());
+        let system_id1 = *adder1.borrow().system_id().value();
+        let system_id2 = *adder2.borrow().system_id().value();
+        assert!(system_id1 < system_id2);
+
+        let context1 = adder1.borrow().create_default_context();
+        assert!(adder1
+            .borrow()
+            .validate_context(context1.borrow().as_base())
+            .is_ok());
+        let error = adder2
+            .borrow()
+            .validate_context(context1.borrow().as_base())
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "A context created for System adder (id {}) was passed to System other_adder (id {})",
+                system_id1, system_id2
+            )
+        );
+    }
+
     #[test]
     fn test_fix_input_port_values() {
         let adder = Adder::<f64>::new(2, 3);
@@ -152,4 +146,25 @@ mod tests {
             na::DVector::<f64>::from_vec(vec![1.5, 3.2, 3.3])
         );
     }
+
+    #[test]
+    fn test_f32() {
+        let adder = Adder::<f32>::new(2, 2);
+        let context = adder.borrow().create_default_context();
+        for (i, values) in [vec![1.0f32, 2.0], vec![0.25, -1.0]]
+            .into_iter()
+            .enumerate()
+        {
+            adder
+                .borrow_mut()
+                .input_port_mut(&InputPortIndex::new(i))
+                .fix_value(&mut *context.borrow_mut(), BasicVector::from_vec(values));
+        }
+
+        let sum = adder
+            .borrow()
+            .leaf_output_port(&OutputPortIndex::new(0))
+            .eval::<BasicVector<f32>>(&mut *context.borrow_mut());
+        assert_eq!(sum, BasicVector::<f32>::from_vec(vec![1.25, 1.0]));
+    }
 }
@@ -1,65 +1,46 @@
-use std::any::Any;
 use std::cell::RefCell;
-use std::rc::{Rc, Weak};
+use std::rc::Rc;
 
-use atlas_derives::{AbstractSystem, LeafSystem, System, SystemBase};
+use atlas_derives::leaf_system;
 
 extern crate nalgebra as na;
 
+use crate::common::atlas_error::AtlasError;
 use crate::common::atlas_scalar::AtlasScalar;
-use crate::common::value::AbstractValue;
 use crate::systems::framework::basic_vector::BasicVector;
-use crate::systems::framework::cache_entry::CacheEntry;
 use crate::systems::framework::context::Context;
-use crate::systems::framework::diagram::SystemWeakLink;
+use crate::systems::framework::continuous_state::ContinuousState;
+use crate::systems::framework::diagram::SystemLink;
+use crate::systems::framework::discrete_values::DiscreteValues;
+use crate::systems::framework::framework_common::DiscreteStateIndex;
 use crate::systems::framework::framework_common::InputPortIndex;
 use crate::systems::framework::framework_common::OutputPortIndex;
-use crate::systems::framework::framework_common::{
-    CacheIndex, SystemId, SystemParentServiceInterface,
-};
 use crate::systems::framework::input_port::InputPort;
-use crate::systems::framework::input_port_base::InputPortBase;
 use crate::systems::framework::leaf_context::LeafContext;
+use crate::systems::framework::leaf_continuous_state::LeafContinuousState;
 use crate::systems::framework::leaf_output_port::LeafOutputPort;
+use crate::systems::framework::leaf_state::LeafState;
 use crate::systems::framework::leaf_system::LeafSystem;
-use crate::systems::framework::model_values::ModelValues;
-use crate::systems::framework::output_port::OutputPort;
-use crate::systems::framework::output_port_base::OutputPortBase;
-use crate::systems::framework::state::State;
-use crate::systems::framework::system::{AbstractSystem, System};
-use crate::systems::framework::system_base::ContextSizes;
-use crate::systems::framework::system_base::SystemBase;
-
-// TODO: implement
-#[derive(LeafSystem, System, AbstractSystem, SystemBase)]
+use crate::systems::framework::system_scalar_converter::ScalarConvertible;
+
+// The System xdot = A x + B u + f0, y = C x + D u + y0, with the input port u and the output port
+// y declared only when they are nonempty. With a positive time_period, x is discrete state updated
+// as x[n+1] = A x[n] + B u[n] + f0 every time_period seconds. Empty matrices stand for zeros of the
+// dimensions implied by the others.
+#[leaf_system(constructor = "allocate")]
+#[system(
+    time_derivatives = "calc_time_derivatives",
+    direct_feedthroughs = "direct_feedthroughs",
+    scalar_convertible
+)]
 pub struct AffineSystem<T: AtlasScalar> {
-    name: String,
-    #[allow(dead_code)]
     a: na::DMatrix<T>,
-    #[allow(dead_code)]
     b: na::DMatrix<T>,
-    #[allow(dead_code)]
     f0: na::DVector<T>,
-    #[allow(dead_code)]
-    c: na::DVector<T>,
-    #[allow(dead_code)]
+    c: na::DMatrix<T>,
     d: na::DMatrix<T>,
-    #[allow(dead_code)]
     y0: na::DVector<T>,
-    #[allow(dead_code)]
     time_period: f64,
-    #[allow(clippy::box_collection)]
-    input_ports: Vec<InputPort<T>>,
-    output_ports: Vec<LeafOutputPort<T>>,
-    cache_entries: Vec<CacheEntry>,
-    context_sizes: ContextSizes,
-    system_id: SystemId,
-    system_weak_link: Option<SystemWeakLink<T>>,
-    parent_service: Option<Weak<RefCell<dyn SystemParentServiceInterface>>>,
-    time_derivatives_cache_index: CacheIndex,
-    model_input_values: ModelValues,
-    model_continuous_state_vector: BasicVector<T>,
-    implicit_time_derivatives_residual_size: Option<usize>,
 }
 
 impl<T: AtlasScalar> AffineSystem<T> {
@@ -67,39 +48,206 @@ impl<T: AtlasScalar> AffineSystem<T> {
         a: na::DMatrix<T>,
         b: na::DMatrix<T>,
         f0: na::DVector<T>,
-        c: na::DVector<T>,
+        c: na::DMatrix<T>,
         d: na::DMatrix<T>,
         y0: na::DVector<T>,
         time_period: f64,
     ) -> Rc<RefCell<Self>> {
+        assert!(
+            time_period >= 0.0,
+            "AffineSystem::new: the time period must not be negative"
+        );
         let num_states = calc_num_states(&a, &b, &f0, &c);
         let num_inputs = calc_num_inputs(&b, &d);
         let num_outputs = calc_num_outputs(&c, &d, &y0);
-        assert!(num_states > 0);
-        assert!(num_inputs > 0);
-        assert!(num_outputs > 0);
-
-        Rc::new(RefCell::new(Self {
-            name: "affine_system".to_string(),
-            a,
-            b,
-            f0,
-            c,
-            d,
-            y0,
+        let or_zeros = |matrix: na::DMatrix<T>, nrows: usize, ncols: usize| {
+            if matrix.is_empty() {
+                na::DMatrix::zeros(nrows, ncols)
+            } else {
+                matrix
+            }
+        };
+        let or_zero_vector = |vector: na::DVector<T>, size: usize| {
+            if vector.is_empty() {
+                na::DVector::zeros(size)
+            } else {
+                vector
+            }
+        };
+
+        let affine_system = Self::allocate(
+            or_zeros(a, num_states, num_states),
+            or_zeros(b, num_states, num_inputs),
+            or_zero_vector(f0, num_states),
+            or_zeros(c, num_outputs, num_states),
+            or_zeros(d, num_outputs, num_inputs),
+            or_zero_vector(y0, num_outputs),
             time_period,
-            input_ports: vec![],
-            output_ports: vec![],
-            cache_entries: vec![],
-            context_sizes: ContextSizes::default(),
-            system_id: SystemId::new(0),
-            system_weak_link: None,
-            parent_service: None,
-            time_derivatives_cache_index: CacheIndex::new(0),
-            model_input_values: ModelValues::default(),
-            model_continuous_state_vector: BasicVector::<T>::zeros(0),
-            implicit_time_derivatives_residual_size: None,
-        }))
+        );
+
+        {
+            let mut affine_system = affine_system.borrow_mut();
+            if num_states > 0 {
+                if affine_system.is_discrete() {
+                    affine_system.declare_discrete_state(num_states);
+                    let update =
+                        affine_system.make_discrete_update_callback(Self::update_discrete_state);
+                    affine_system.declare_periodic_discrete_update_event(time_period, 0.0, update);
+                } else {
+                    affine_system.declare_continuous_state(0, 0, num_states);
+                }
+            }
+            if num_inputs > 0 {
+                affine_system.declare_vector_input_port("u".to_string(), num_inputs);
+            }
+            if num_outputs > 0 {
+                let calc = affine_system.make_vector_output_calc(Self::calc_output_y);
+                affine_system.declare_vector_output_port("y".to_string(), num_outputs, calc);
+            }
+        }
+
+        affine_system
+    }
+
+    pub fn input_port_u(&self) -> &InputPort<T> {
+        assert!(
+            !self.input_ports.is_empty(),
+            "AffineSystem::input_port_u: the system has no inputs"
+        );
+        &self.input_ports[&InputPortIndex::new(0)]
+    }
+
+    pub fn output_port_y(&self) -> &LeafOutputPort<T> {
+        assert!(
+            !self.output_ports.is_empty(),
+            "AffineSystem::output_port_y: the system has no outputs"
+        );
+        &self.output_ports[&OutputPortIndex::new(0)]
+    }
+
+    pub fn is_discrete(&self) -> bool {
+        self.time_period > 0.0
+    }
+
+    fn has_discrete_state(&self) -> bool {
+        self.is_discrete() && self.a.nrows() > 0
+    }
+
+    // The state x, continuous or discrete.
+    pub fn state(&self, context: &LeafContext<T>) -> na::DVector<T> {
+        if self.has_discrete_state() {
+            context.discrete_state().value(&DiscreteStateIndex::new(0))
+        } else {
+            context.continuous_state_vector().copy_to_vector()
+        }
+    }
+
+    pub fn set_state(&self, context: &mut LeafContext<T>, value: &na::DVector<T>) {
+        if self.has_discrete_state() {
+            context
+                .discrete_state_mut()
+                .set_value(&DiscreteStateIndex::new(0), value);
+        } else {
+            context.continuous_state_vector_mut().set_from_vector(value);
+        }
+    }
+
+    fn input(&self, context: &LeafContext<T>) -> na::DVector<T> {
+        if self.input_ports.is_empty() {
+            na::DVector::zeros(0)
+        } else {
+            self.input_port_u()
+                .eval::<LeafState<T>, BasicVector<T>>(context)
+                .value()
+                .clone()
+        }
+    }
+
+    // A x + B u + f0, the time derivatives or the next discrete state.
+    fn calc_state_update(&self, context: &LeafContext<T>) -> na::DVector<T> {
+        &self.a * self.state(context) + &self.b * self.input(context) + &self.f0
+    }
+
+    pub fn calc_time_derivatives(
+        &self,
+        context: &LeafContext<T>,
+        derivatives: &mut LeafContinuousState<T>,
+    ) {
+        if !self.is_discrete() {
+            derivatives
+                .vector_mut()
+                .set_from_vector(&self.calc_state_update(context));
+        }
+    }
+
+    fn update_discrete_state(
+        &self,
+        context: &LeafContext<T>,
+        discrete_state: &mut DiscreteValues<T>,
+    ) {
+        discrete_state.set_value(
+            &DiscreteStateIndex::new(0),
+            &self.calc_state_update(context),
+        );
+    }
+
+    // The output y = C x + D u + y0 depends on the input only through D.
+    fn direct_feedthroughs(&self) -> Vec<(InputPortIndex, OutputPortIndex)> {
+        if self.d.iter().any(|d| *d != T::zero()) {
+            vec![(InputPortIndex::new(0), OutputPortIndex::new(0))]
+        } else {
+            vec![]
+        }
+    }
+
+    pub fn calc_output_y(&self, context: &LeafContext<T>, y: &mut BasicVector<T>) {
+        let y_value = &self.c * self.state(context) + &self.d * self.input(context) + &self.y0;
+        y.set_value(&y_value);
+    }
+
+    pub fn a(&self) -> &na::DMatrix<T> {
+        &self.a
+    }
+
+    pub fn b(&self) -> &na::DMatrix<T> {
+        &self.b
+    }
+
+    pub fn f0(&self) -> &na::DVector<T> {
+        &self.f0
+    }
+
+    pub fn c(&self) -> &na::DMatrix<T> {
+        &self.c
+    }
+
+    pub fn d(&self) -> &na::DMatrix<T> {
+        &self.d
+    }
+
+    pub fn y0(&self) -> &na::DVector<T> {
+        &self.y0
+    }
+
+    pub fn time_period(&self) -> f64 {
+        self.time_period
+    }
+}
+
+impl<T: AtlasScalar> ScalarConvertible for AffineSystem<T> {
+    fn convert_scalar_type<U: AtlasScalar>(&self) -> Result<SystemLink<U>, AtlasError> {
+        let cast = |value: &T| value.cast_scalar::<U>();
+        let affine_system = AffineSystem::<U>::new(
+            self.a.map(|value| cast(&value)),
+            self.b.map(|value| cast(&value)),
+            self.f0.map(|value| cast(&value)),
+            self.c.map(|value| cast(&value)),
+            self.d.map(|value| cast(&value)),
+            self.y0.map(|value| cast(&value)),
+            self.time_period,
+        );
+
+        Ok(affine_system.into())
     }
 }
 
@@ -107,31 +255,43 @@ fn calc_num_states<T: AtlasScalar>(
     a: &na::DMatrix<T>,
     b: &na::DMatrix<T>,
     f0: &na::DVector<T>,
-    c: &na::DVector<T>,
+    c: &na::DMatrix<T>,
 ) -> usize {
     let mut num_states = 0;
 
     if !a.is_empty() {
         num_states = a.nrows();
-        assert_eq!(a.nrows(), a.ncols());
+        assert_eq!(a.nrows(), a.ncols(), "AffineSystem: A must be square");
     }
     if !b.is_empty() {
         if num_states > 0 {
-            assert_eq!(b.nrows(), b.ncols());
+            assert_eq!(
+                b.nrows(),
+                num_states,
+                "AffineSystem: the rows of B must match the states"
+            );
         } else {
             num_states = b.nrows();
         }
     }
     if !f0.is_empty() {
         if num_states > 0 {
-            assert_eq!(f0.len(), num_states);
+            assert_eq!(
+                f0.len(),
+                num_states,
+                "AffineSystem: the size of f0 must match the states"
+            );
         } else {
             num_states = f0.len();
         }
     }
     if !c.is_empty() {
         if num_states > 0 {
-            assert_eq!(c.ncols(), num_states);
+            assert_eq!(
+                c.ncols(),
+                num_states,
+                "AffineSystem: the columns of C must match the states"
+            );
         } else {
             num_states = c.ncols();
         }
@@ -148,7 +308,11 @@ fn calc_num_inputs<T: AtlasScalar>(b: &na::DMatrix<T>, d: &na::DMatrix<T>) -> us
     }
     if !d.is_empty() {
         if num_inputs > 0 {
-            assert_eq!(d.nrows(), d.ncols());
+            assert_eq!(
+                d.ncols(),
+                num_inputs,
+                "AffineSystem: the columns of D must match those of B"
+            );
         } else {
             num_inputs = d.ncols();
         }
@@ -158,7 +322,7 @@ fn calc_num_inputs<T: AtlasScalar>(b: &na::DMatrix<T>, d: &na::DMatrix<T>) -> us
 }
 
 fn calc_num_outputs<T: AtlasScalar>(
-    c: &na::DVector<T>,
+    c: &na::DMatrix<T>,
     d: &na::DMatrix<T>,
     y0: &na::DVector<T>,
 ) -> usize {
@@ -168,14 +332,22 @@ fn calc_num_outputs<T: AtlasScalar>(
     }
     if !d.is_empty() {
         if num_outputs > 0 {
-            assert_eq!(d.nrows(), d.ncols());
+            assert_eq!(
+                d.nrows(),
+                num_outputs,
+                "AffineSystem: the rows of D must match those of C"
+            );
         } else {
             num_outputs = d.nrows();
         }
     }
     if !y0.is_empty() {
         if num_outputs > 0 {
-            assert_eq!(y0.len(), num_outputs);
+            assert_eq!(
+                y0.len(),
+                num_outputs,
+                "AffineSystem: the size of y0 must match the outputs"
+            );
         } else {
             num_outputs = y0.len();
         }
@@ -183,3 +355,88 @@ fn calc_num_outputs<T: AtlasScalar>(
 
     num_outputs
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::systems::framework::system::System;
+    use crate::systems::framework::system_base::SystemBase;
+
+    #[test]
+    fn test_continuous_affine_system() {
+        let affine_system = AffineSystem::<f64>::new(
+            na::DMatrix::from_row_slice(2, 2, &[0.0, 1.0, -2.0, -3.0]),
+            na::DMatrix::from_row_slice(2, 1, &[0.0, 1.0]),
+            na::DVector::from_vec(vec![1.0, 0.0]),
+            na::DMatrix::from_row_slice(1, 2, &[1.0, 1.0]),
+            na::DMatrix::from_element(1, 1, 2.0),
+            na::DVector::from_vec(vec![-1.0]),
+            0.0,
+        );
+        let affine_system = affine_system.borrow();
+        let context = affine_system.create_default_context();
+        affine_system.set_state(
+            &mut context.borrow_mut(),
+            &na::DVector::from_vec(vec![1.0, 2.0]),
+        );
+        affine_system.input_port_u().fix_value(
+            context.borrow_mut(),
+            BasicVector::<f64>::from_vec(vec![3.0]),
+        );
+
+        let derivatives = affine_system
+            .eval_time_derivatives(&context.borrow())
+            .vector()
+            .copy_to_vector();
+        assert_eq!(derivatives, na::DVector::from_vec(vec![3.0, -5.0]));
+        let y = affine_system
+            .output_port_y()
+            .eval::<BasicVector<f64>>(&mut context.borrow_mut());
+        assert_eq!(*y.value(), na::DVector::from_vec(vec![8.0]));
+        assert!(System::has_direct_feedthrough(
+            &*affine_system,
+            &InputPortIndex::new(0),
+            &OutputPortIndex::new(0)
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "the columns of C must match the states")]
+    fn test_mismatched_dimensions() {
+        AffineSystem::<f64>::new(
+            na::DMatrix::from_element(2, 2, 1.0),
+            na::DMatrix::zeros(0, 0),
+            na::DVector::zeros(0),
+            na::DMatrix::from_element(1, 3, 1.0),
+            na::DMatrix::zeros(0, 0),
+            na::DVector::zeros(0),
+            0.0,
+        );
+    }
+
+    #[test]
+    fn test_to_scalar_type() {
+        let affine_system = AffineSystem::<f64>::new(
+            na::DMatrix::from_element(1, 1, -1.0),
+            na::DMatrix::from_element(1, 1, 2.0),
+            na::DVector::from_element(1, 0.0),
+            na::DMatrix::from_element(1, 1, 1.0),
+            na::DMatrix::from_element(1, 1, 0.5),
+            na::DVector::from_element(1, 0.25),
+            0.0,
+        );
+        let system_link = SystemLink::from(affine_system)
+            .try_to_scalar_type::<f32>()
+            .unwrap();
+
+        let SystemLink::LeafSystemLink(system) = system_link else {
+            panic!("an AffineSystem converts to a leaf system");
+        };
+        let system = system.borrow();
+        let affine_system = system.as_any().downcast_ref::<AffineSystem<f32>>().unwrap();
+        assert_eq!(affine_system.a[(0, 0)], -1.0f32);
+        assert_eq!(affine_system.d[(0, 0)], 0.5f32);
+        assert_eq!(affine_system.y0[0], 0.25f32);
+        assert_eq!(affine_system.name(), "affine_system");
+    }
+}
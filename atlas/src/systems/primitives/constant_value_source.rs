@@ -0,0 +1,100 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use atlas_derives::leaf_system;
+
+use crate::common::atlas_error::AtlasError;
+use crate::common::atlas_scalar::AtlasScalar;
+use crate::common::value::AbstractValue;
+use crate::systems::framework::context::Context;
+use crate::systems::framework::diagram::SystemLink;
+use crate::systems::framework::framework_common::AbstractParameterIndex;
+use crate::systems::framework::framework_common::DependencyTicket;
+use crate::systems::framework::framework_common::OutputPortIndex;
+use crate::systems::framework::leaf_context::LeafContext;
+use crate::systems::framework::leaf_output_port::LeafOutputPort;
+use crate::systems::framework::leaf_system::LeafSystem;
+use crate::systems::framework::system_scalar_converter::ScalarConvertible;
+
+// Outputs a constant value of any type. Like the vector of a ConstantVectorSource, the value is
+// a parameter, with the value given to new as the default.
+#[leaf_system(constructor = "allocate")]
+#[system(scalar_convertible)]
+pub struct ConstantValueSource<T: AtlasScalar> {
+    #[default]
+    source_value_index: AbstractParameterIndex,
+}
+
+impl<T: AtlasScalar> ConstantValueSource<T> {
+    pub fn new(source_value: &dyn AbstractValue) -> Rc<RefCell<Self>> {
+        let constant_value_source = Self::allocate();
+
+        {
+            let mut constant_value_source = constant_value_source.borrow_mut();
+            constant_value_source.source_value_index =
+                constant_value_source.declare_abstract_parameter(source_value);
+            let model_value = source_value.clone_box();
+            let calc = constant_value_source.make_abstract_output_calc(Self::calc_output);
+            constant_value_source.declare_abstract_output_port_with_prerequisites(
+                "y".to_string(),
+                Box::new(move || model_value.clone_box()),
+                calc,
+                vec![DependencyTicket::Parameters],
+            );
+        }
+
+        constant_value_source
+    }
+
+    pub fn output_port_y(&self) -> &LeafOutputPort<T> {
+        &self.output_ports[&OutputPortIndex::new(0)]
+    }
+
+    pub fn source_value<'a>(&self, context: &'a LeafContext<T>) -> &'a dyn AbstractValue {
+        context
+            .parameters()
+            .abstract_parameter_value(&self.source_value_index)
+    }
+
+    fn calc_output(&self, context: &LeafContext<T>, y: &mut dyn AbstractValue) {
+        y.set_from(self.source_value(context));
+    }
+}
+
+impl<T: AtlasScalar> ScalarConvertible for ConstantValueSource<T> {
+    fn convert_scalar_type<U: AtlasScalar>(&self) -> Result<SystemLink<U>, AtlasError> {
+        let source_value = self
+            .model_parameters
+            .abstract_parameter_value(&self.source_value_index);
+
+        Ok(ConstantValueSource::<U>::new(source_value).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::value::Value;
+    use crate::systems::framework::system::System;
+
+    #[test]
+    fn test_source_value() {
+        let source = ConstantValueSource::<f64>::new(&Value::new("constant".to_string()));
+        let source = source.borrow();
+        let context = source.create_default_context();
+
+        let y = source
+            .output_port_y()
+            .eval::<String>(&mut context.borrow_mut());
+        assert_eq!(y, "constant");
+
+        context
+            .borrow_mut()
+            .get_mutable_abstract_parameter::<String>(&AbstractParameterIndex::new(0))
+            .push_str(" value");
+        let y = source
+            .output_port_y()
+            .eval::<String>(&mut context.borrow_mut());
+        assert_eq!(y, "constant value");
+    }
+}
@@ -0,0 +1,116 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use atlas_derives::leaf_system;
+
+extern crate nalgebra as na;
+
+use crate::common::atlas_error::AtlasError;
+use crate::common::atlas_scalar::AtlasScalar;
+use crate::systems::framework::basic_vector::BasicVector;
+use crate::systems::framework::context::Context;
+use crate::systems::framework::diagram::SystemLink;
+use crate::systems::framework::framework_common::DependencyTicket;
+use crate::systems::framework::framework_common::NumericParameterIndex;
+use crate::systems::framework::framework_common::OutputPortIndex;
+use crate::systems::framework::leaf_context::LeafContext;
+use crate::systems::framework::leaf_output_port::LeafOutputPort;
+use crate::systems::framework::leaf_system::LeafSystem;
+use crate::systems::framework::system_scalar_converter::ScalarConvertible;
+
+// Outputs a constant vector. The constant is a numeric parameter, so each context can have its
+// own, with the value given to new as the default.
+#[leaf_system(constructor = "allocate")]
+#[system(scalar_convertible)]
+pub struct ConstantVectorSource<T: AtlasScalar> {
+    #[default]
+    source_value_index: NumericParameterIndex,
+}
+
+impl<T: AtlasScalar> ConstantVectorSource<T> {
+    pub fn new(source_value: na::DVector<T>) -> Rc<RefCell<Self>> {
+        let size = source_value.len();
+        let constant_vector_source = Self::allocate();
+
+        {
+            let mut constant_vector_source = constant_vector_source.borrow_mut();
+            constant_vector_source.source_value_index = constant_vector_source
+                .declare_numeric_parameter(BasicVector::<T>::new(source_value));
+            let calc = constant_vector_source.make_vector_output_calc(Self::calc_output);
+            constant_vector_source.declare_vector_output_port_with_prerequisites(
+                "y".to_string(),
+                size,
+                calc,
+                vec![DependencyTicket::Parameters],
+            );
+        }
+
+        constant_vector_source
+    }
+
+    pub fn output_port_y(&self) -> &LeafOutputPort<T> {
+        &self.output_ports[&OutputPortIndex::new(0)]
+    }
+
+    pub fn source_value(&self, context: &LeafContext<T>) -> na::DVector<T> {
+        context
+            .get_numeric_parameter(&self.source_value_index)
+            .copy_to_vector()
+    }
+
+    pub fn set_source_value(&self, context: &mut LeafContext<T>, value: &na::DVector<T>) {
+        let source_value = context.get_mutable_numeric_parameter(&self.source_value_index);
+        assert_eq!(
+            source_value.size(),
+            value.len(),
+            "ConstantVectorSource::set_source_value: the value must keep its size"
+        );
+        source_value.set_from_vector(value);
+    }
+
+    fn calc_output(&self, context: &LeafContext<T>, y: &mut BasicVector<T>) {
+        y.set_value(&self.source_value(context));
+    }
+}
+
+impl<T: AtlasScalar> ScalarConvertible for ConstantVectorSource<T> {
+    fn convert_scalar_type<U: AtlasScalar>(&self) -> Result<SystemLink<U>, AtlasError> {
+        let source_value = self
+            .model_parameters
+            .numeric_parameter(&self.source_value_index)
+            .copy_to_vector()
+            .map(|value| value.cast_scalar::<U>());
+
+        Ok(ConstantVectorSource::<U>::new(source_value).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::systems::framework::system::System;
+    use crate::systems::framework::system_base::SystemBase;
+
+    #[test]
+    fn test_source_value() {
+        let source = ConstantVectorSource::<f64>::new(na::DVector::from_vec(vec![1.0, 2.0]));
+        let source = source.borrow();
+        assert_eq!(source.num_input_ports(), 0);
+        let context = source.create_default_context();
+
+        let y = source
+            .output_port_y()
+            .eval::<BasicVector<f64>>(&mut context.borrow_mut());
+        assert_eq!(*y.value(), na::DVector::from_vec(vec![1.0, 2.0]));
+
+        // The output follows the parameter.
+        source.set_source_value(
+            &mut context.borrow_mut(),
+            &na::DVector::from_vec(vec![3.0, 4.0]),
+        );
+        let y = source
+            .output_port_y()
+            .eval::<BasicVector<f64>>(&mut context.borrow_mut());
+        assert_eq!(*y.value(), na::DVector::from_vec(vec![3.0, 4.0]));
+    }
+}
@@ -0,0 +1,110 @@
+use atlas_derives::leaf_system;
+
+extern crate nalgebra as na;
+
+use crate::common::atlas_error::AtlasError;
+use crate::common::atlas_scalar::AtlasScalar;
+use crate::systems::framework::basic_vector::BasicVector;
+use crate::systems::framework::diagram::SystemLink;
+use crate::systems::framework::framework_common::InputPortIndex;
+use crate::systems::framework::framework_common::OutputPortIndex;
+use crate::systems::framework::input_port::InputPort;
+use crate::systems::framework::leaf_context::LeafContext;
+use crate::systems::framework::leaf_output_port::LeafOutputPort;
+use crate::systems::framework::leaf_state::LeafState;
+use crate::systems::framework::system_scalar_converter::ScalarConvertible;
+
+// Zero while its input is within [lower_limit, upper_limit], and the input's distance beyond the
+// nearest limit outside it, elementwise.
+#[leaf_system]
+#[system(scalar_convertible)]
+pub struct DeadZone<T: AtlasScalar> {
+    #[input(vector, size = "Self::calc_size(&lower_limit, &upper_limit)")]
+    u: InputPortIndex,
+    #[output(
+        vector,
+        size = "lower_limit.len()",
+        calc = "calc_output",
+        prerequisites = "vec![DependencyTicket::InputPort(InputPortIndex::new(0))]"
+    )]
+    y: OutputPortIndex,
+    lower_limit: na::DVector<T>,
+    upper_limit: na::DVector<T>,
+}
+
+impl<T: AtlasScalar> DeadZone<T> {
+    fn calc_size(lower_limit: &na::DVector<T>, upper_limit: &na::DVector<T>) -> usize {
+        assert_eq!(
+            lower_limit.len(),
+            upper_limit.len(),
+            "DeadZone::new: the limits must have the same size"
+        );
+        assert!(
+            lower_limit
+                .iter()
+                .zip(upper_limit.iter())
+                .all(|(lower, upper)| lower <= upper),
+            "DeadZone::new: the lower limit must not exceed the upper limit"
+        );
+        lower_limit.len()
+    }
+
+    pub fn input_port_u(&self) -> &InputPort<T> {
+        &self.input_ports[&self.u]
+    }
+
+    pub fn output_port_y(&self) -> &LeafOutputPort<T> {
+        &self.output_ports[&self.y]
+    }
+
+    fn calc_output(&self, context: &LeafContext<T>, y: &mut BasicVector<T>) {
+        let u = self
+            .input_port_u()
+            .eval::<LeafState<T>, BasicVector<T>>(context);
+        let y = y.value_mut();
+        for (i, u) in u.value().iter().enumerate() {
+            y[i] = if *u > self.upper_limit[i] {
+                u.clone() - self.upper_limit[i].clone()
+            } else if *u < self.lower_limit[i] {
+                u.clone() - self.lower_limit[i].clone()
+            } else {
+                T::zero()
+            };
+        }
+    }
+}
+
+impl<T: AtlasScalar> ScalarConvertible for DeadZone<T> {
+    fn convert_scalar_type<U: AtlasScalar>(&self) -> Result<SystemLink<U>, AtlasError> {
+        let dead_zone = DeadZone::<U>::new(
+            self.lower_limit.map(|value| value.cast_scalar::<U>()),
+            self.upper_limit.map(|value| value.cast_scalar::<U>()),
+        );
+
+        Ok(dead_zone.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::systems::framework::system::System;
+
+    #[test]
+    fn test_dead_zone() {
+        let dead_zone = DeadZone::<f64>::new(
+            na::DVector::from_vec(vec![-1.0, -1.0, -1.0]),
+            na::DVector::from_vec(vec![0.5, 0.5, 0.5]),
+        );
+        let dead_zone = dead_zone.borrow();
+        let context = dead_zone.create_default_context();
+        dead_zone.input_port_u().fix_value(
+            context.borrow_mut(),
+            BasicVector::<f64>::from_vec(vec![-3.0, 0.25, 2.0]),
+        );
+        let y = dead_zone
+            .output_port_y()
+            .eval::<BasicVector<f64>>(&mut context.borrow_mut());
+        assert_eq!(*y.value(), na::DVector::from_vec(vec![-2.0, 0.0, 1.5]));
+    }
+}
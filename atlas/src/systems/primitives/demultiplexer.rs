@@ -0,0 +1,139 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use atlas_derives::leaf_system;
+
+extern crate nalgebra as na;
+
+use crate::common::atlas_error::AtlasError;
+use crate::common::atlas_scalar::AtlasScalar;
+use crate::systems::framework::basic_vector::BasicVector;
+use crate::systems::framework::diagram::SystemLink;
+use crate::systems::framework::framework_common::DependencyTicket;
+use crate::systems::framework::framework_common::InputPortIndex;
+use crate::systems::framework::input_port::InputPort;
+use crate::systems::framework::leaf_context::LeafContext;
+use crate::systems::framework::leaf_state::LeafState;
+use crate::systems::framework::leaf_system::LeafSystem;
+use crate::systems::framework::system_scalar_converter::ScalarConvertible;
+
+// Splits its vector input u into the outputs y0, y1, ... of the given sizes, in port order.
+#[leaf_system(constructor = "allocate")]
+#[system(scalar_convertible)]
+pub struct Demultiplexer<T: AtlasScalar> {
+    output_sizes: Vec<usize>,
+}
+
+impl<T: AtlasScalar> Demultiplexer<T> {
+    pub fn new(output_sizes: Vec<usize>) -> Rc<RefCell<Self>> {
+        assert!(
+            !output_sizes.is_empty(),
+            "Demultiplexer::new: there must be at least one output"
+        );
+        let input_size = output_sizes.iter().sum();
+
+        let demultiplexer = Self::allocate(output_sizes.clone());
+
+        {
+            let mut demultiplexer = demultiplexer.borrow_mut();
+            demultiplexer.declare_vector_input_port("u".to_string(), input_size);
+            let mut offset = 0;
+            for (i, output_size) in output_sizes.iter().enumerate() {
+                let calc = demultiplexer.make_vector_output_calc(
+                    move |demultiplexer: &Self,
+                          context: &LeafContext<T>,
+                          y: &mut BasicVector<T>| {
+                        demultiplexer.calc_output(offset, context, y)
+                    },
+                );
+                demultiplexer.declare_vector_output_port_with_prerequisites(
+                    format!("y{}", i),
+                    *output_size,
+                    calc,
+                    vec![DependencyTicket::InputPort(InputPortIndex::new(0))],
+                );
+                offset += output_size;
+            }
+        }
+
+        demultiplexer
+    }
+
+    pub fn output_sizes(&self) -> &[usize] {
+        &self.output_sizes
+    }
+
+    pub fn input_port_u(&self) -> &InputPort<T> {
+        &self.input_ports[&InputPortIndex::new(0)]
+    }
+
+    // The rows of u from offset on, as many as y has.
+    fn calc_output(&self, offset: usize, context: &LeafContext<T>, y: &mut BasicVector<T>) {
+        let u = self
+            .input_port_u()
+            .eval::<LeafState<T>, BasicVector<T>>(context);
+        let y = y.value_mut();
+        let size = y.len();
+        y.copy_from(&u.value().rows(offset, size));
+    }
+}
+
+impl<T: AtlasScalar> ScalarConvertible for Demultiplexer<T> {
+    fn convert_scalar_type<U: AtlasScalar>(&self) -> Result<SystemLink<U>, AtlasError> {
+        Ok(Demultiplexer::<U>::new(self.output_sizes.clone()).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::systems::framework::framework_common::OutputPortIndex;
+    use crate::systems::framework::system::System;
+
+    #[test]
+    fn test_demultiplexer() {
+        let demultiplexer = Demultiplexer::<f64>::new(vec![1, 2]);
+        let demultiplexer = demultiplexer.borrow();
+        assert_eq!(
+            demultiplexer.get_direct_feedthroughs(),
+            vec![
+                (InputPortIndex::new(0), OutputPortIndex::new(0)),
+                (InputPortIndex::new(0), OutputPortIndex::new(1)),
+            ]
+        );
+
+        let context = demultiplexer.create_default_context();
+        demultiplexer.input_port_u().fix_value(
+            context.borrow_mut(),
+            BasicVector::<f64>::from_vec(vec![1.0, 2.0, 3.0]),
+        );
+        let eval = |index: usize| {
+            demultiplexer.output_ports[index]
+                .eval::<BasicVector<f64>>(&mut context.borrow_mut())
+                .value()
+                .clone()
+        };
+        assert_eq!(eval(0), na::DVector::from_vec(vec![1.0]));
+        assert_eq!(eval(1), na::DVector::from_vec(vec![2.0, 3.0]));
+    }
+
+    #[test]
+    fn test_many_outputs() {
+        // Enough output ports for their cache entries to outgrow the first allocation.
+        let demultiplexer = Demultiplexer::<f64>::new(vec![1; 9]);
+        let demultiplexer = demultiplexer.borrow();
+        let context = demultiplexer.create_default_context();
+        demultiplexer.input_port_u().fix_value(
+            context.borrow_mut(),
+            BasicVector::<f64>::from_vec((0..9).map(f64::from).collect()),
+        );
+        for index in 0..9 {
+            assert_eq!(
+                demultiplexer.output_ports[index]
+                    .eval::<BasicVector<f64>>(&mut context.borrow_mut())
+                    .value()[0],
+                index as f64
+            );
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use atlas_derives::leaf_system;
+
+extern crate nalgebra as na;
+
+use crate::common::atlas_error::AtlasError;
+use crate::common::atlas_scalar::AtlasScalar;
+use crate::systems::framework::basic_vector::BasicVector;
+use crate::systems::framework::diagram::SystemLink;
+use crate::systems::framework::framework_common::InputPortIndex;
+use crate::systems::framework::framework_common::OutputPortIndex;
+use crate::systems::framework::input_port::InputPort;
+use crate::systems::framework::leaf_context::LeafContext;
+use crate::systems::framework::leaf_output_port::LeafOutputPort;
+use crate::systems::framework::leaf_state::LeafState;
+use crate::systems::framework::system_scalar_converter::ScalarConvertible;
+
+// Scales its input elementwise: y = k ⊙ u.
+#[leaf_system]
+#[system(scalar_convertible)]
+pub struct Gain<T: AtlasScalar> {
+    #[input(vector, size = "k.len()")]
+    u: InputPortIndex,
+    #[output(
+        vector,
+        size = "k.len()",
+        calc = "calc_output",
+        prerequisites = "vec![DependencyTicket::InputPort(InputPortIndex::new(0))]"
+    )]
+    y: OutputPortIndex,
+    k: na::DVector<T>,
+}
+
+impl<T: AtlasScalar> Gain<T> {
+    // The same gain k for each of the size elements.
+    pub fn new_scalar(k: T, size: usize) -> Rc<RefCell<Self>> {
+        Self::new(na::DVector::from_element(size, k))
+    }
+
+    pub fn input_port_u(&self) -> &InputPort<T> {
+        &self.input_ports[&self.u]
+    }
+
+    pub fn output_port_y(&self) -> &LeafOutputPort<T> {
+        &self.output_ports[&self.y]
+    }
+
+    pub fn k(&self) -> &na::DVector<T> {
+        &self.k
+    }
+
+    fn calc_output(&self, context: &LeafContext<T>, y: &mut BasicVector<T>) {
+        let u = self
+            .input_port_u()
+            .eval::<LeafState<T>, BasicVector<T>>(context);
+        y.set_value(&self.k.component_mul(u.value()));
+    }
+}
+
+impl<T: AtlasScalar> ScalarConvertible for Gain<T> {
+    fn convert_scalar_type<U: AtlasScalar>(&self) -> Result<SystemLink<U>, AtlasError> {
+        Ok(Gain::<U>::new(self.k.map(|k| k.cast_scalar::<U>())).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::systems::framework::system::System;
+    use crate::systems::framework::system_base::SystemBase;
+
+    #[test]
+    fn test_gain() {
+        let gain = Gain::<f64>::new(na::DVector::from_vec(vec![2.0, -1.0, 0.5]));
+        let gain = gain.borrow();
+        assert_eq!(gain.name(), "gain");
+        assert_eq!(
+            gain.get_direct_feedthroughs(),
+            vec![(InputPortIndex::new(0), OutputPortIndex::new(0))]
+        );
+
+        let context = gain.create_default_context();
+        gain.input_port_u().fix_value(
+            context.borrow_mut(),
+            BasicVector::<f64>::from_vec(vec![1.0, 2.0, 4.0]),
+        );
+        let y = gain
+            .output_port_y()
+            .eval::<BasicVector<f64>>(&mut context.borrow_mut());
+        assert_eq!(*y.value(), na::DVector::from_vec(vec![2.0, -2.0, 2.0]));
+    }
+
+    #[test]
+    fn test_scalar_gain() {
+        let gain = Gain::<f32>::new_scalar(3.0, 2);
+        let gain = gain.borrow();
+        let context = gain.create_default_context();
+        gain.input_port_u().fix_value(
+            context.borrow_mut(),
+            BasicVector::<f32>::from_vec(vec![1.0, -1.0]),
+        );
+        let y = gain
+            .output_port_y()
+            .eval::<BasicVector<f32>>(&mut context.borrow_mut());
+        assert_eq!(*y.value(), na::DVector::from_vec(vec![3.0f32, -3.0]));
+    }
+}
@@ -0,0 +1,127 @@
+use atlas_derives::leaf_system;
+
+extern crate nalgebra as na;
+
+use crate::common::atlas_error::AtlasError;
+use crate::common::atlas_scalar::AtlasScalar;
+use crate::systems::framework::basic_vector::BasicVector;
+use crate::systems::framework::context::Context;
+use crate::systems::framework::continuous_state::ContinuousState;
+use crate::systems::framework::diagram::SystemLink;
+use crate::systems::framework::framework_common::InputPortIndex;
+use crate::systems::framework::framework_common::OutputPortIndex;
+use crate::systems::framework::input_port::InputPort;
+use crate::systems::framework::leaf_context::LeafContext;
+use crate::systems::framework::leaf_continuous_state::LeafContinuousState;
+use crate::systems::framework::leaf_output_port::LeafOutputPort;
+use crate::systems::framework::leaf_state::LeafState;
+use crate::systems::framework::system_scalar_converter::ScalarConvertible;
+
+// Integrates its input: xdot = u, y = x. The output only depends on the state, so an Integrator
+// breaks algebraic loops in feedback diagrams.
+#[leaf_system]
+#[continuous_state(z = "size", derivatives = "calc_derivatives")]
+#[system(scalar_convertible)]
+pub struct Integrator<T: AtlasScalar> {
+    #[input(vector, size = "size")]
+    u: InputPortIndex,
+    #[output(
+        vector,
+        size = "size",
+        calc = "calc_output",
+        prerequisites = "vec![DependencyTicket::ContinuousState]"
+    )]
+    y: OutputPortIndex,
+    size: usize,
+}
+
+impl<T: AtlasScalar> Integrator<T> {
+    pub fn input_port_u(&self) -> &InputPort<T> {
+        &self.input_ports[&self.u]
+    }
+
+    pub fn output_port_y(&self) -> &LeafOutputPort<T> {
+        &self.output_ports[&self.y]
+    }
+
+    pub fn set_integral_value(&self, context: &mut LeafContext<T>, value: &na::DVector<T>) {
+        assert_eq!(
+            value.len(),
+            self.size,
+            "Integrator::set_integral_value: the value must have the size of the input"
+        );
+        context.continuous_state_vector_mut().set_from_vector(value);
+    }
+
+    fn calc_derivatives(&self, context: &LeafContext<T>, derivatives: &mut LeafContinuousState<T>) {
+        let u = self
+            .input_port_u()
+            .eval::<LeafState<T>, BasicVector<T>>(context);
+        derivatives.vector_mut().set_from_vector(u.value());
+    }
+
+    fn calc_output(&self, context: &LeafContext<T>, y: &mut BasicVector<T>) {
+        y.set_value(&context.continuous_state_vector().copy_to_vector());
+    }
+}
+
+impl<T: AtlasScalar> ScalarConvertible for Integrator<T> {
+    fn convert_scalar_type<U: AtlasScalar>(&self) -> Result<SystemLink<U>, AtlasError> {
+        Ok(Integrator::<U>::new(self.size).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::systems::analysis::simulator::Simulator;
+    use crate::systems::framework::diagram::{DiagramExt, SystemLinkExt};
+    use crate::systems::framework::diagram_builder::DiagramBuilder;
+    use crate::systems::framework::port_base::PortBase;
+    use crate::systems::framework::system::System;
+    use crate::systems::framework::system_base::SystemBase;
+
+    #[test]
+    fn test_constructor() {
+        let integrator = Integrator::<f64>::new(2);
+        let integrator = integrator.borrow();
+        assert_eq!(integrator.name(), "integrator");
+        assert_eq!(integrator.input_port_u().size(), 2);
+        assert_eq!(integrator.num_continuous_states(), 2);
+        assert!(integrator.get_direct_feedthroughs().is_empty());
+    }
+
+    #[test]
+    fn test_double_integrator() {
+        // q'' = 1 from q = 0, qdot = 1: qdot = 1 + t, q = t + t^2 / 2.
+        let mut diagram_builder = DiagramBuilder::<f64>::new();
+        let velocity = Integrator::<f64>::new(1);
+        let position = Integrator::<f64>::new(1);
+        diagram_builder.add_leaf_system(&velocity);
+        diagram_builder.add_leaf_system(&position);
+        diagram_builder.export_input_port(velocity.input_port(InputPortIndex::new(0)));
+        diagram_builder.connect(
+            velocity.output_port_mut(OutputPortIndex::new(0)),
+            position.input_port(InputPortIndex::new(0)),
+        );
+        let diagram = diagram_builder.build();
+
+        let mut simulator = Simulator::new(&diagram);
+        diagram.input_port_mut(&InputPortIndex::new(0)).fix_value(
+            simulator.get_mutable_context(),
+            BasicVector::<f64>::from_vec(vec![1.0]),
+        );
+        simulator
+            .get_mutable_context()
+            .continuous_state_vector_mut()
+            .set_from_vector(&na::DVector::from_vec(vec![1.0, 0.0]));
+        simulator.advance_to(1.0);
+
+        let state = simulator
+            .get_context()
+            .continuous_state_vector()
+            .copy_to_vector();
+        assert!((state[0] - 2.0).abs() < 1e-9);
+        assert!((state[1] - 1.5).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,79 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+extern crate nalgebra as na;
+
+use crate::common::atlas_scalar::AtlasScalar;
+use crate::systems::framework::system_base::SystemBase;
+use crate::systems::primitives::affine_system::AffineSystem;
+
+// The AffineSystem without offsets: xdot = A x + B u, y = C x + D u, or x[n+1] = A x[n] + B u[n]
+// with a positive time_period.
+pub type LinearSystem<T> = AffineSystem<T>;
+
+impl<T: AtlasScalar> AffineSystem<T> {
+    pub fn new_linear(
+        a: na::DMatrix<T>,
+        b: na::DMatrix<T>,
+        c: na::DMatrix<T>,
+        d: na::DMatrix<T>,
+        time_period: f64,
+    ) -> Rc<RefCell<Self>> {
+        let linear_system = Self::new(
+            a,
+            b,
+            na::DVector::zeros(0),
+            c,
+            d,
+            na::DVector::zeros(0),
+            time_period,
+        );
+        linear_system
+            .borrow_mut()
+            .set_name("linear_system".to_string());
+
+        linear_system
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::systems::analysis::simulator::Simulator;
+    use crate::systems::framework::basic_vector::BasicVector;
+    use crate::systems::framework::context::Context;
+
+    #[test]
+    fn test_discrete_linear_system() {
+        // A discrete double integrator driven by a unit input.
+        let linear_system = LinearSystem::<f64>::new_linear(
+            na::DMatrix::from_row_slice(2, 2, &[1.0, 1.0, 0.0, 1.0]),
+            na::DMatrix::from_row_slice(2, 1, &[0.0, 1.0]),
+            na::DMatrix::from_row_slice(1, 2, &[1.0, 0.0]),
+            na::DMatrix::zeros(0, 0),
+            0.5,
+        );
+        assert_eq!(linear_system.borrow().name(), "linear_system");
+        assert_eq!(linear_system.borrow().d().shape(), (1, 1));
+
+        let mut simulator = Simulator::new(&linear_system);
+        linear_system.borrow().input_port_u().fix_value(
+            simulator.get_mutable_context(),
+            BasicVector::<f64>::from_vec(vec![1.0]),
+        );
+        simulator.advance_to(1.75);
+
+        // Updates at 0, 0.5, 1.0 and 1.5.
+        let mut context = simulator.get_mutable_context();
+        assert_eq!(
+            linear_system.borrow().state(&context),
+            na::DVector::from_vec(vec![6.0, 4.0])
+        );
+        let y = linear_system
+            .borrow()
+            .output_port_y()
+            .eval::<BasicVector<f64>>(&mut context);
+        assert_eq!(*y.value(), na::DVector::from_vec(vec![6.0]));
+        assert_eq!(context.continuous_state_vector().size(), 0);
+    }
+}
@@ -0,0 +1,66 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+extern crate nalgebra as na;
+
+use crate::common::atlas_scalar::AtlasScalar;
+use crate::systems::framework::system_base::SystemBase;
+use crate::systems::primitives::affine_system::AffineSystem;
+
+// The stateless AffineSystem y = D u.
+pub type MatrixGain<T> = AffineSystem<T>;
+
+impl<T: AtlasScalar> AffineSystem<T> {
+    pub fn new_matrix_gain(d: na::DMatrix<T>) -> Rc<RefCell<Self>> {
+        assert!(
+            !d.is_empty(),
+            "AffineSystem::new_matrix_gain: D must have inputs and outputs"
+        );
+        let matrix_gain = Self::new(
+            na::DMatrix::zeros(0, 0),
+            na::DMatrix::zeros(0, 0),
+            na::DVector::zeros(0),
+            na::DMatrix::zeros(0, 0),
+            d,
+            na::DVector::zeros(0),
+            0.0,
+        );
+        matrix_gain.borrow_mut().set_name("matrix_gain".to_string());
+
+        matrix_gain
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::systems::framework::basic_vector::BasicVector;
+    use crate::systems::framework::framework_common::{InputPortIndex, OutputPortIndex};
+    use crate::systems::framework::system::System;
+
+    #[test]
+    fn test_matrix_gain() {
+        let matrix_gain = MatrixGain::<f64>::new_matrix_gain(na::DMatrix::from_row_slice(
+            2,
+            3,
+            &[1.0, 0.0, 2.0, 0.0, -1.0, 1.0],
+        ));
+        let matrix_gain = matrix_gain.borrow();
+        assert_eq!(matrix_gain.name(), "matrix_gain");
+        assert_eq!(matrix_gain.num_continuous_states(), 0);
+        assert_eq!(
+            matrix_gain.get_direct_feedthroughs(),
+            vec![(InputPortIndex::new(0), OutputPortIndex::new(0))]
+        );
+
+        let context = matrix_gain.create_default_context();
+        matrix_gain.input_port_u().fix_value(
+            context.borrow_mut(),
+            BasicVector::<f64>::from_vec(vec![1.0, 2.0, 3.0]),
+        );
+        let y = matrix_gain
+            .output_port_y()
+            .eval::<BasicVector<f64>>(&mut context.borrow_mut());
+        assert_eq!(*y.value(), na::DVector::from_vec(vec![7.0, 1.0]));
+    }
+}
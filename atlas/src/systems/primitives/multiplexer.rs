@@ -0,0 +1,112 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use atlas_derives::leaf_system;
+
+extern crate nalgebra as na;
+
+use crate::common::atlas_error::AtlasError;
+use crate::common::atlas_scalar::AtlasScalar;
+use crate::systems::framework::basic_vector::BasicVector;
+use crate::systems::framework::diagram::SystemLink;
+use crate::systems::framework::framework_common::DependencyTicket;
+use crate::systems::framework::framework_common::OutputPortIndex;
+use crate::systems::framework::leaf_context::LeafContext;
+use crate::systems::framework::leaf_output_port::LeafOutputPort;
+use crate::systems::framework::leaf_state::LeafState;
+use crate::systems::framework::leaf_system::LeafSystem;
+use crate::systems::framework::system_scalar_converter::ScalarConvertible;
+
+// Stacks its vector inputs u0, u1, ... of the given sizes into the output y, in port order.
+#[leaf_system(constructor = "allocate")]
+#[system(scalar_convertible)]
+pub struct Multiplexer<T: AtlasScalar> {
+    input_sizes: Vec<usize>,
+}
+
+impl<T: AtlasScalar> Multiplexer<T> {
+    pub fn new(input_sizes: Vec<usize>) -> Rc<RefCell<Self>> {
+        assert!(
+            !input_sizes.is_empty(),
+            "Multiplexer::new: there must be at least one input"
+        );
+        let output_size = input_sizes.iter().sum();
+
+        let multiplexer = Self::allocate(input_sizes.clone());
+
+        {
+            let mut multiplexer = multiplexer.borrow_mut();
+            for (i, input_size) in input_sizes.iter().enumerate() {
+                multiplexer.declare_vector_input_port(format!("u{}", i), *input_size);
+            }
+            let calc = multiplexer.make_vector_output_calc(Self::calc_output);
+            multiplexer.declare_vector_output_port_with_prerequisites(
+                "y".to_string(),
+                output_size,
+                calc,
+                vec![DependencyTicket::AllInputPorts],
+            );
+        }
+
+        multiplexer
+    }
+
+    pub fn input_sizes(&self) -> &[usize] {
+        &self.input_sizes
+    }
+
+    pub fn output_port_y(&self) -> &LeafOutputPort<T> {
+        &self.output_ports[&OutputPortIndex::new(0)]
+    }
+
+    // Each input is copied straight into its rows of y.
+    fn calc_output(&self, context: &LeafContext<T>, y: &mut BasicVector<T>) {
+        let y = y.value_mut();
+        let mut offset = 0;
+        for (input_port, input_size) in self.input_ports.iter().zip(self.input_sizes.iter()) {
+            let u = input_port.eval::<LeafState<T>, BasicVector<T>>(context);
+            y.rows_mut(offset, *input_size).copy_from(u.value());
+            offset += input_size;
+        }
+    }
+}
+
+impl<T: AtlasScalar> ScalarConvertible for Multiplexer<T> {
+    fn convert_scalar_type<U: AtlasScalar>(&self) -> Result<SystemLink<U>, AtlasError> {
+        Ok(Multiplexer::<U>::new(self.input_sizes.clone()).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::systems::framework::framework_common::InputPortIndex;
+    use crate::systems::framework::system::System;
+
+    #[test]
+    fn test_multiplexer() {
+        let multiplexer = Multiplexer::<f64>::new(vec![2, 1]);
+        let multiplexer = multiplexer.borrow();
+        assert_eq!(
+            multiplexer.get_direct_feedthroughs(),
+            vec![
+                (InputPortIndex::new(0), OutputPortIndex::new(0)),
+                (InputPortIndex::new(1), OutputPortIndex::new(0)),
+            ]
+        );
+
+        let context = multiplexer.create_default_context();
+        multiplexer.input_port(&InputPortIndex::new(0)).fix_value(
+            context.borrow_mut(),
+            BasicVector::<f64>::from_vec(vec![1.0, 2.0]),
+        );
+        multiplexer.input_port(&InputPortIndex::new(1)).fix_value(
+            context.borrow_mut(),
+            BasicVector::<f64>::from_vec(vec![3.0]),
+        );
+        let y = multiplexer
+            .output_port_y()
+            .eval::<BasicVector<f64>>(&mut context.borrow_mut());
+        assert_eq!(*y.value(), na::DVector::from_vec(vec![1.0, 2.0, 3.0]));
+    }
+}
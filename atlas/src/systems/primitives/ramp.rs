@@ -0,0 +1,90 @@
+use atlas_derives::leaf_system;
+
+extern crate nalgebra as na;
+
+use crate::common::atlas_error::AtlasError;
+use crate::common::atlas_scalar::AtlasScalar;
+use crate::systems::framework::basic_vector::BasicVector;
+use crate::systems::framework::context::Context;
+use crate::systems::framework::diagram::SystemLink;
+use crate::systems::framework::framework_common::OutputPortIndex;
+use crate::systems::framework::leaf_context::LeafContext;
+use crate::systems::framework::leaf_output_port::LeafOutputPort;
+use crate::systems::framework::system_scalar_converter::ScalarConvertible;
+
+// Outputs y = offset + slope (t - start_time) elementwise from start_time on, and offset before.
+#[leaf_system]
+#[system(scalar_convertible)]
+pub struct Ramp<T: AtlasScalar> {
+    #[output(
+        vector,
+        size = "Self::calc_size(&slope, &offset)",
+        calc = "calc_y",
+        prerequisites = "vec![DependencyTicket::Time]"
+    )]
+    y: OutputPortIndex,
+    slope: na::DVector<T>,
+    offset: na::DVector<T>,
+    start_time: f64,
+}
+
+impl<T: AtlasScalar> Ramp<T> {
+    fn calc_size(slope: &na::DVector<T>, offset: &na::DVector<T>) -> usize {
+        assert_eq!(
+            slope.len(),
+            offset.len(),
+            "Ramp::new: the slope and the offset must have the same size"
+        );
+        slope.len()
+    }
+
+    pub fn output_port_y(&self) -> &LeafOutputPort<T> {
+        &self.output_ports[&self.y]
+    }
+
+    fn calc_y(&self, context: &LeafContext<T>, y: &mut BasicVector<T>) {
+        let time = context.time().clone();
+        if time.as_f64() < self.start_time {
+            y.set_value(&self.offset);
+        } else {
+            let elapsed = time - T::from_f64(self.start_time);
+            y.set_value(&(&self.offset + self.slope.map(|slope| slope * elapsed.clone())));
+        }
+    }
+}
+
+impl<T: AtlasScalar> ScalarConvertible for Ramp<T> {
+    fn convert_scalar_type<U: AtlasScalar>(&self) -> Result<SystemLink<U>, AtlasError> {
+        let cast = |values: &na::DVector<T>| values.map(|value| value.cast_scalar::<U>());
+        let ramp = Ramp::<U>::new(cast(&self.slope), cast(&self.offset), self.start_time);
+
+        Ok(ramp.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::systems::framework::system::System;
+
+    #[test]
+    fn test_ramp() {
+        let ramp = Ramp::<f64>::new(
+            na::DVector::from_vec(vec![2.0, -1.0]),
+            na::DVector::from_vec(vec![1.0, 0.0]),
+            1.0,
+        );
+        let ramp = ramp.borrow();
+        let context = ramp.create_default_context();
+
+        let eval_at = |time: f64| {
+            context.borrow_mut().set_time(time);
+            ramp.output_port_y()
+                .eval::<BasicVector<f64>>(&mut context.borrow_mut())
+                .value()
+                .clone()
+        };
+        assert_eq!(eval_at(0.5), na::DVector::from_vec(vec![1.0, 0.0]));
+        assert_eq!(eval_at(2.5), na::DVector::from_vec(vec![4.0, -1.5]));
+    }
+}
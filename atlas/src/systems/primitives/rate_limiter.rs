@@ -0,0 +1,255 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use atlas_derives::leaf_system;
+
+extern crate nalgebra as na;
+
+use crate::common::atlas_error::AtlasError;
+use crate::common::atlas_scalar::AtlasScalar;
+use crate::systems::framework::basic_vector::BasicVector;
+use crate::systems::framework::context::Context;
+use crate::systems::framework::continuous_state::ContinuousState;
+use crate::systems::framework::diagram::SystemLink;
+use crate::systems::framework::discrete_values::DiscreteValues;
+use crate::systems::framework::framework_common::DependencyTicket;
+use crate::systems::framework::framework_common::DiscreteStateIndex;
+use crate::systems::framework::framework_common::InputPortIndex;
+use crate::systems::framework::framework_common::OutputPortIndex;
+use crate::systems::framework::input_port::InputPort;
+use crate::systems::framework::leaf_context::LeafContext;
+use crate::systems::framework::leaf_continuous_state::LeafContinuousState;
+use crate::systems::framework::leaf_output_port::LeafOutputPort;
+use crate::systems::framework::leaf_state::LeafState;
+use crate::systems::framework::leaf_system::LeafSystem;
+use crate::systems::framework::system_scalar_converter::ScalarConvertible;
+use crate::systems::primitives::saturation::saturate;
+
+// Follows its input with the rate of change of the output y limited to [falling_rate,
+// rising_rate] elementwise, where falling_rate <= 0 <= rising_rate. y is the state, either
+// continuous with ydot = (u - y) / time_constant within the rate limits, or discrete, moving by
+// u - y within the limits on the change over each time_period.
+#[leaf_system(constructor = "allocate")]
+#[system(time_derivatives = "calc_derivatives", scalar_convertible)]
+pub struct RateLimiter<T: AtlasScalar> {
+    rising_rate: na::DVector<T>,
+    falling_rate: na::DVector<T>,
+    time_constant: f64,
+    time_period: f64,
+}
+
+impl<T: AtlasScalar> RateLimiter<T> {
+    pub fn new_continuous(
+        rising_rate: na::DVector<T>,
+        falling_rate: na::DVector<T>,
+        time_constant: f64,
+    ) -> Rc<RefCell<Self>> {
+        assert!(
+            time_constant > 0.0,
+            "RateLimiter::new_continuous: the time constant must be positive"
+        );
+        Self::make(rising_rate, falling_rate, time_constant, 0.0)
+    }
+
+    pub fn new_discrete(
+        rising_rate: na::DVector<T>,
+        falling_rate: na::DVector<T>,
+        time_period: f64,
+    ) -> Rc<RefCell<Self>> {
+        assert!(
+            time_period > 0.0,
+            "RateLimiter::new_discrete: the time period must be positive"
+        );
+        Self::make(rising_rate, falling_rate, 0.0, time_period)
+    }
+
+    fn make(
+        rising_rate: na::DVector<T>,
+        falling_rate: na::DVector<T>,
+        time_constant: f64,
+        time_period: f64,
+    ) -> Rc<RefCell<Self>> {
+        assert_eq!(
+            rising_rate.len(),
+            falling_rate.len(),
+            "RateLimiter: the rates must have the same size"
+        );
+        assert!(
+            rising_rate.iter().all(|rate| *rate >= T::zero())
+                && falling_rate.iter().all(|rate| *rate <= T::zero()),
+            "RateLimiter: the rising rate must not be negative, nor the falling rate positive"
+        );
+        let size = rising_rate.len();
+
+        let rate_limiter = Self::allocate(rising_rate, falling_rate, time_constant, time_period);
+
+        {
+            let mut rate_limiter = rate_limiter.borrow_mut();
+            let state_ticket = if rate_limiter.is_discrete() {
+                rate_limiter.declare_discrete_state(size);
+                let update =
+                    rate_limiter.make_discrete_update_callback(Self::update_discrete_state);
+                rate_limiter.declare_periodic_discrete_update_event(time_period, 0.0, update);
+                DependencyTicket::DiscreteState
+            } else {
+                rate_limiter.declare_continuous_state(0, 0, size);
+                DependencyTicket::ContinuousState
+            };
+            rate_limiter.declare_vector_input_port("u".to_string(), size);
+            let calc = rate_limiter.make_vector_output_calc(Self::calc_output);
+            rate_limiter.declare_vector_output_port_with_prerequisites(
+                "y".to_string(),
+                size,
+                calc,
+                vec![state_ticket],
+            );
+        }
+
+        rate_limiter
+    }
+
+    pub fn is_discrete(&self) -> bool {
+        self.time_period > 0.0
+    }
+
+    pub fn input_port_u(&self) -> &InputPort<T> {
+        &self.input_ports[&InputPortIndex::new(0)]
+    }
+
+    pub fn output_port_y(&self) -> &LeafOutputPort<T> {
+        &self.output_ports[&OutputPortIndex::new(0)]
+    }
+
+    pub fn output_value(&self, context: &LeafContext<T>) -> na::DVector<T> {
+        if self.is_discrete() {
+            context.discrete_state().value(&DiscreteStateIndex::new(0))
+        } else {
+            context.continuous_state_vector().copy_to_vector()
+        }
+    }
+
+    pub fn set_output_value(&self, context: &mut LeafContext<T>, value: &na::DVector<T>) {
+        if self.is_discrete() {
+            context
+                .discrete_state_mut()
+                .set_value(&DiscreteStateIndex::new(0), value);
+        } else {
+            context.continuous_state_vector_mut().set_from_vector(value);
+        }
+    }
+
+    // The change of y towards u, limited to the rates over the given duration.
+    fn calc_limited_change(
+        &self,
+        context: &LeafContext<T>,
+        scale: T,
+        duration: T,
+    ) -> na::DVector<T> {
+        let u = self
+            .input_port_u()
+            .eval::<LeafState<T>, BasicVector<T>>(context);
+        let difference = u.value() - self.output_value(context);
+        na::DVector::from_iterator(
+            difference.len(),
+            difference.iter().enumerate().map(|(i, difference)| {
+                saturate(
+                    difference.clone() * scale.clone(),
+                    &(self.falling_rate[i].clone() * duration.clone()),
+                    &(self.rising_rate[i].clone() * duration.clone()),
+                )
+            }),
+        )
+    }
+
+    fn calc_derivatives(&self, context: &LeafContext<T>, derivatives: &mut LeafContinuousState<T>) {
+        if !self.is_discrete() {
+            let ydot = self.calc_limited_change(
+                context,
+                T::one() / T::from_f64(self.time_constant),
+                T::one(),
+            );
+            derivatives.vector_mut().set_from_vector(&ydot);
+        }
+    }
+
+    fn update_discrete_state(
+        &self,
+        context: &LeafContext<T>,
+        discrete_state: &mut DiscreteValues<T>,
+    ) {
+        let change = self.calc_limited_change(context, T::one(), T::from_f64(self.time_period));
+        discrete_state.set_value(
+            &DiscreteStateIndex::new(0),
+            &(self.output_value(context) + change),
+        );
+    }
+
+    fn calc_output(&self, context: &LeafContext<T>, y: &mut BasicVector<T>) {
+        y.set_value(&self.output_value(context));
+    }
+}
+
+impl<T: AtlasScalar> ScalarConvertible for RateLimiter<T> {
+    fn convert_scalar_type<U: AtlasScalar>(&self) -> Result<SystemLink<U>, AtlasError> {
+        let rising_rate = self.rising_rate.map(|rate| rate.cast_scalar::<U>());
+        let falling_rate = self.falling_rate.map(|rate| rate.cast_scalar::<U>());
+
+        Ok(RateLimiter::<U>::make(
+            rising_rate,
+            falling_rate,
+            self.time_constant,
+            self.time_period,
+        )
+        .into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::systems::analysis::simulator::Simulator;
+    use crate::systems::framework::system::System;
+
+    #[test]
+    fn test_discrete_rate_limiter() {
+        let rate_limiter = RateLimiter::<f64>::new_discrete(
+            na::DVector::from_vec(vec![1.0, 1.0]),
+            na::DVector::from_vec(vec![-2.0, -2.0]),
+            0.25,
+        );
+        assert!(rate_limiter.borrow().get_direct_feedthroughs().is_empty());
+
+        let mut simulator = Simulator::new(&rate_limiter);
+        rate_limiter.borrow().input_port_u().fix_value(
+            simulator.get_mutable_context(),
+            BasicVector::<f64>::from_vec(vec![10.0, -0.3]),
+        );
+        // Updates at 0, 0.25, 0.5 and 0.75.
+        simulator.advance_to(0.8);
+
+        let y = rate_limiter
+            .borrow()
+            .output_port_y()
+            .eval::<BasicVector<f64>>(&mut simulator.get_mutable_context());
+        assert!((y.value() - na::DVector::from_vec(vec![1.0, -0.3])).norm() < 1e-12);
+    }
+
+    #[test]
+    fn test_continuous_rate_limiter() {
+        let rate_limiter = RateLimiter::<f64>::new_continuous(
+            na::DVector::from_vec(vec![2.0]),
+            na::DVector::from_vec(vec![-2.0]),
+            0.01,
+        );
+        let mut simulator = Simulator::new(&rate_limiter);
+        rate_limiter.borrow().input_port_u().fix_value(
+            simulator.get_mutable_context(),
+            BasicVector::<f64>::from_vec(vec![1.0]),
+        );
+        simulator.advance_to(0.25);
+
+        // The output rises at the rate limit while far from the input.
+        let y = rate_limiter.borrow().output_value(&simulator.get_context());
+        assert!((y[0] - 0.5).abs() < 1e-9);
+    }
+}
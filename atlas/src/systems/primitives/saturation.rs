@@ -0,0 +1,243 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use atlas_derives::leaf_system;
+
+extern crate nalgebra as na;
+
+use crate::common::atlas_error::AtlasError;
+use crate::common::atlas_scalar::AtlasScalar;
+use crate::systems::framework::basic_vector::BasicVector;
+use crate::systems::framework::diagram::SystemLink;
+use crate::systems::framework::framework_common::DependencyTicket;
+use crate::systems::framework::framework_common::InputPortIndex;
+use crate::systems::framework::framework_common::OutputPortIndex;
+use crate::systems::framework::input_port::InputPort;
+use crate::systems::framework::leaf_context::LeafContext;
+use crate::systems::framework::leaf_output_port::LeafOutputPort;
+use crate::systems::framework::leaf_state::LeafState;
+use crate::systems::framework::leaf_system::LeafSystem;
+use crate::systems::framework::system_scalar_converter::ScalarConvertible;
+
+// Clamps its input elementwise: y = min(max(u, lower_limit), upper_limit). The limits are either
+// constants given to new, or the values of the input ports lower_limit and upper_limit.
+#[leaf_system(constructor = "allocate")]
+#[system(scalar_convertible)]
+pub struct Saturation<T: AtlasScalar> {
+    limits: Option<(na::DVector<T>, na::DVector<T>)>,
+    size: usize,
+}
+
+impl<T: AtlasScalar> Saturation<T> {
+    pub fn new(lower_limit: na::DVector<T>, upper_limit: na::DVector<T>) -> Rc<RefCell<Self>> {
+        assert_eq!(
+            lower_limit.len(),
+            upper_limit.len(),
+            "Saturation::new: the limits must have the same size"
+        );
+        assert!(
+            lower_limit
+                .iter()
+                .zip(upper_limit.iter())
+                .all(|(lower, upper)| lower <= upper),
+            "Saturation::new: the lower limit must not exceed the upper limit"
+        );
+        let size = lower_limit.len();
+        Self::make(Some((lower_limit, upper_limit)), size)
+    }
+
+    // Saturation with the limits read from the input ports lower_limit and upper_limit.
+    pub fn new_variable(size: usize) -> Rc<RefCell<Self>> {
+        Self::make(None, size)
+    }
+
+    fn make(limits: Option<(na::DVector<T>, na::DVector<T>)>, size: usize) -> Rc<RefCell<Self>> {
+        let has_limit_ports = limits.is_none();
+        let saturation = Self::allocate(limits, size);
+
+        {
+            let mut saturation = saturation.borrow_mut();
+            saturation.declare_vector_input_port("u".to_string(), size);
+            if has_limit_ports {
+                saturation.declare_vector_input_port("lower_limit".to_string(), size);
+                saturation.declare_vector_input_port("upper_limit".to_string(), size);
+            }
+            let calc = saturation.make_vector_output_calc(Self::calc_output);
+            saturation.declare_vector_output_port_with_prerequisites(
+                "y".to_string(),
+                size,
+                calc,
+                vec![DependencyTicket::AllInputPorts],
+            );
+        }
+
+        saturation
+    }
+
+    pub fn input_port_u(&self) -> &InputPort<T> {
+        &self.input_ports[&InputPortIndex::new(0)]
+    }
+
+    pub fn input_port_lower_limit(&self) -> &InputPort<T> {
+        assert!(
+            self.limits.is_none(),
+            "Saturation::input_port_lower_limit: the limits are constant"
+        );
+        &self.input_ports[&InputPortIndex::new(1)]
+    }
+
+    pub fn input_port_upper_limit(&self) -> &InputPort<T> {
+        assert!(
+            self.limits.is_none(),
+            "Saturation::input_port_upper_limit: the limits are constant"
+        );
+        &self.input_ports[&InputPortIndex::new(2)]
+    }
+
+    pub fn output_port_y(&self) -> &LeafOutputPort<T> {
+        &self.output_ports[&OutputPortIndex::new(0)]
+    }
+
+    fn calc_output(&self, context: &LeafContext<T>, y: &mut BasicVector<T>) {
+        let eval = |input_port: &InputPort<T>| {
+            input_port
+                .eval::<LeafState<T>, BasicVector<T>>(context)
+                .value()
+                .clone()
+        };
+        let u = eval(self.input_port_u());
+        let (lower_limit, upper_limit) = match &self.limits {
+            Some((lower_limit, upper_limit)) => (lower_limit.clone(), upper_limit.clone()),
+            None => (
+                eval(self.input_port_lower_limit()),
+                eval(self.input_port_upper_limit()),
+            ),
+        };
+
+        let y = y.value_mut();
+        for i in 0..self.size {
+            y[i] = saturate(u[i].clone(), &lower_limit[i], &upper_limit[i]);
+        }
+    }
+}
+
+impl<T: AtlasScalar> ScalarConvertible for Saturation<T> {
+    fn convert_scalar_type<U: AtlasScalar>(&self) -> Result<SystemLink<U>, AtlasError> {
+        let saturation = match &self.limits {
+            Some((lower_limit, upper_limit)) => Saturation::<U>::new(
+                lower_limit.map(|value| value.cast_scalar::<U>()),
+                upper_limit.map(|value| value.cast_scalar::<U>()),
+            ),
+            None => Saturation::<U>::new_variable(self.size),
+        };
+
+        Ok(saturation.into())
+    }
+}
+
+// min(max(value, lower_limit), upper_limit), so with crossed limits the upper one wins.
+pub fn saturate<T: AtlasScalar>(value: T, lower_limit: &T, upper_limit: &T) -> T {
+    let value = if value < *lower_limit {
+        lower_limit.clone()
+    } else {
+        value
+    };
+    if value > *upper_limit {
+        upper_limit.clone()
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::systems::framework::system::System;
+    use crate::systems::framework::system_base::SystemBase;
+
+    #[test]
+    fn test_constant_limits() {
+        let saturation = Saturation::<f64>::new(
+            na::DVector::from_vec(vec![-1.0, 0.0, 0.0]),
+            na::DVector::from_vec(vec![1.0, 2.0, 2.0]),
+        );
+        let saturation = saturation.borrow();
+        assert_eq!(saturation.num_input_ports(), 1);
+
+        let context = saturation.create_default_context();
+        saturation.input_port_u().fix_value(
+            context.borrow_mut(),
+            BasicVector::<f64>::from_vec(vec![-3.0, 1.5, 4.0]),
+        );
+        let y = saturation
+            .output_port_y()
+            .eval::<BasicVector<f64>>(&mut context.borrow_mut());
+        assert_eq!(*y.value(), na::DVector::from_vec(vec![-1.0, 1.5, 2.0]));
+    }
+
+    #[test]
+    fn test_variable_limits() {
+        let saturation = Saturation::<f64>::new_variable(2);
+        let saturation = saturation.borrow();
+        assert_eq!(
+            saturation.get_direct_feedthroughs(),
+            vec![
+                (InputPortIndex::new(0), OutputPortIndex::new(0)),
+                (InputPortIndex::new(1), OutputPortIndex::new(0)),
+                (InputPortIndex::new(2), OutputPortIndex::new(0)),
+            ]
+        );
+
+        let context = saturation.create_default_context();
+        saturation.input_port_u().fix_value(
+            context.borrow_mut(),
+            BasicVector::<f64>::from_vec(vec![-3.0, 3.0]),
+        );
+        saturation.input_port_lower_limit().fix_value(
+            context.borrow_mut(),
+            BasicVector::<f64>::from_vec(vec![-2.0, -2.0]),
+        );
+        saturation.input_port_upper_limit().fix_value(
+            context.borrow_mut(),
+            BasicVector::<f64>::from_vec(vec![2.0, 0.5]),
+        );
+        let y = saturation
+            .output_port_y()
+            .eval::<BasicVector<f64>>(&mut context.borrow_mut());
+        assert_eq!(*y.value(), na::DVector::from_vec(vec![-2.0, 0.5]));
+    }
+
+    #[test]
+    fn test_crossed_variable_limits() {
+        let saturation = Saturation::<f64>::new_variable(3);
+        let saturation = saturation.borrow();
+        let context = saturation.create_default_context();
+        saturation.input_port_u().fix_value(
+            context.borrow_mut(),
+            BasicVector::<f64>::from_vec(vec![-3.0, 0.0, 3.0]),
+        );
+        saturation.input_port_lower_limit().fix_value(
+            context.borrow_mut(),
+            BasicVector::<f64>::from_vec(vec![1.0, 1.0, 1.0]),
+        );
+        saturation.input_port_upper_limit().fix_value(
+            context.borrow_mut(),
+            BasicVector::<f64>::from_vec(vec![-1.0, -1.0, -1.0]),
+        );
+
+        // min(max(u, 1), -1) is the upper limit for any u.
+        let y = saturation
+            .output_port_y()
+            .eval::<BasicVector<f64>>(&mut context.borrow_mut());
+        assert_eq!(*y.value(), na::DVector::from_vec(vec![-1.0, -1.0, -1.0]));
+    }
+
+    #[test]
+    #[should_panic(expected = "the lower limit must not exceed the upper limit")]
+    fn test_crossed_limits() {
+        Saturation::<f64>::new(
+            na::DVector::from_vec(vec![1.0]),
+            na::DVector::from_vec(vec![-1.0]),
+        );
+    }
+}
@@ -0,0 +1,209 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use atlas_derives::leaf_system;
+
+extern crate nalgebra as na;
+
+use crate::common::atlas_error::AtlasError;
+use crate::common::atlas_scalar::AtlasScalar;
+use crate::systems::framework::basic_vector::BasicVector;
+use crate::systems::framework::context::Context;
+use crate::systems::framework::diagram::SystemLink;
+use crate::systems::framework::framework_common::OutputPortIndex;
+use crate::systems::framework::leaf_context::LeafContext;
+use crate::systems::framework::leaf_output_port::LeafOutputPort;
+use crate::systems::framework::system_scalar_converter::ScalarConvertible;
+
+// Outputs y = a sin(ω t + φ) elementwise, with its first and second time derivatives ydot and
+// yddot, for the amplitudes a, the frequencies ω in radians per second and the phases φ.
+#[leaf_system]
+#[system(scalar_convertible)]
+pub struct Sine<T: AtlasScalar> {
+    #[output(
+        vector,
+        size = "Self::calc_size(&amplitudes, &frequencies, &phases)",
+        calc = "calc_y",
+        prerequisites = "vec![DependencyTicket::Time]"
+    )]
+    y: OutputPortIndex,
+    #[output(
+        vector,
+        size = "amplitudes.len()",
+        calc = "calc_ydot",
+        prerequisites = "vec![DependencyTicket::Time]"
+    )]
+    ydot: OutputPortIndex,
+    #[output(
+        vector,
+        size = "amplitudes.len()",
+        calc = "calc_yddot",
+        prerequisites = "vec![DependencyTicket::Time]"
+    )]
+    yddot: OutputPortIndex,
+    amplitudes: na::DVector<T>,
+    frequencies: na::DVector<T>,
+    phases: na::DVector<T>,
+}
+
+impl<T: AtlasScalar> Sine<T> {
+    // The same sine wave for each of the size elements.
+    pub fn new_scalar(amplitude: T, frequency: T, phase: T, size: usize) -> Rc<RefCell<Self>> {
+        Self::new(
+            na::DVector::from_element(size, amplitude),
+            na::DVector::from_element(size, frequency),
+            na::DVector::from_element(size, phase),
+        )
+    }
+
+    fn calc_size(
+        amplitudes: &na::DVector<T>,
+        frequencies: &na::DVector<T>,
+        phases: &na::DVector<T>,
+    ) -> usize {
+        assert!(
+            amplitudes.len() == frequencies.len() && amplitudes.len() == phases.len(),
+            "Sine::new: the amplitudes, frequencies and phases must have the same size"
+        );
+        amplitudes.len()
+    }
+
+    pub fn output_port_y(&self) -> &LeafOutputPort<T> {
+        &self.output_ports[&self.y]
+    }
+
+    pub fn output_port_ydot(&self) -> &LeafOutputPort<T> {
+        &self.output_ports[&self.ydot]
+    }
+
+    pub fn output_port_yddot(&self) -> &LeafOutputPort<T> {
+        &self.output_ports[&self.yddot]
+    }
+
+    // The sines and cosines of ω t + φ, which carry the derivatives of the time, the
+    // frequencies and the phases.
+    fn calc_sin_cos(&self, context: &LeafContext<T>) -> (na::DVector<T>, na::DVector<T>) {
+        let time = context.time().clone();
+        let angles = self.frequencies.map(|frequency| frequency * time.clone()) + &self.phases;
+        let sin = angles.map(|angle| angle.sin());
+        let cos = angles.map(|angle| angle.cos());
+        (sin, cos)
+    }
+
+    fn calc_y(&self, context: &LeafContext<T>, y: &mut BasicVector<T>) {
+        let (sin, _) = self.calc_sin_cos(context);
+        y.set_value(&self.amplitudes.component_mul(&sin));
+    }
+
+    fn calc_ydot(&self, context: &LeafContext<T>, ydot: &mut BasicVector<T>) {
+        let (_, cos) = self.calc_sin_cos(context);
+        ydot.set_value(
+            &self
+                .amplitudes
+                .component_mul(&self.frequencies)
+                .component_mul(&cos),
+        );
+    }
+
+    fn calc_yddot(&self, context: &LeafContext<T>, yddot: &mut BasicVector<T>) {
+        let (sin, _) = self.calc_sin_cos(context);
+        let minus_sin = sin.map(|sin| T::zero() - sin);
+        let squared_frequencies = self.frequencies.component_mul(&self.frequencies);
+        yddot.set_value(
+            &self
+                .amplitudes
+                .component_mul(&squared_frequencies)
+                .component_mul(&minus_sin),
+        );
+    }
+}
+
+impl<T: AtlasScalar> ScalarConvertible for Sine<T> {
+    fn convert_scalar_type<U: AtlasScalar>(&self) -> Result<SystemLink<U>, AtlasError> {
+        let cast = |values: &na::DVector<T>| values.map(|value| value.cast_scalar::<U>());
+        let sine = Sine::<U>::new(
+            cast(&self.amplitudes),
+            cast(&self.frequencies),
+            cast(&self.phases),
+        );
+
+        Ok(sine.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::autodiff::AutoDiffXd;
+    use crate::systems::framework::system::System;
+
+    #[test]
+    fn test_sine() {
+        let sine = Sine::<f64>::new(
+            na::DVector::from_vec(vec![2.0, 1.0]),
+            na::DVector::from_vec(vec![std::f64::consts::PI, 2.0]),
+            na::DVector::from_vec(vec![0.0, std::f64::consts::FRAC_PI_2]),
+        );
+        let sine = sine.borrow();
+        let context = sine.create_default_context();
+        context.borrow_mut().set_time(0.5);
+
+        let eval = |output_port: &LeafOutputPort<f64>| {
+            output_port
+                .eval::<BasicVector<f64>>(&mut context.borrow_mut())
+                .value()
+                .clone()
+        };
+        let expected_y =
+            na::DVector::from_vec(vec![2.0, (1.0 + std::f64::consts::FRAC_PI_2).sin()]);
+        let expected_ydot =
+            na::DVector::from_vec(vec![0.0, 2.0 * (1.0 + std::f64::consts::FRAC_PI_2).cos()]);
+        let expected_yddot = na::DVector::from_vec(vec![
+            -2.0 * std::f64::consts::PI.powi(2),
+            -4.0 * (1.0 + std::f64::consts::FRAC_PI_2).sin(),
+        ]);
+        assert!((eval(sine.output_port_y()) - expected_y).norm() < 1e-12);
+        assert!((eval(sine.output_port_ydot()) - expected_ydot).norm() < 1e-12);
+        assert!((eval(sine.output_port_yddot()) - expected_yddot).norm() < 1e-12);
+    }
+
+    #[test]
+    fn test_autodiff() {
+        // The partials of y = a sin(ω t + φ) with respect to ω, φ and t.
+        let (amplitude, frequency, phase, time) = (2.0, 3.0, 0.5, 0.25);
+        let sine = Sine::<AutoDiffXd>::new(
+            na::DVector::from_element(1, AutoDiffXd::constant(amplitude)),
+            na::DVector::from_element(1, AutoDiffXd::variable(frequency, 0, 3)),
+            na::DVector::from_element(1, AutoDiffXd::variable(phase, 1, 3)),
+        );
+        let sine = sine.borrow();
+        let context = sine.create_default_context();
+        context
+            .borrow_mut()
+            .set_time(AutoDiffXd::variable(time, 2, 3));
+
+        let y = sine
+            .output_port_y()
+            .eval::<BasicVector<AutoDiffXd>>(&mut context.borrow_mut())
+            .value()[0]
+            .clone();
+        let angle: f64 = frequency * time + phase;
+        let expected_derivatives = na::DVector::from_vec(vec![
+            amplitude * time * angle.cos(),
+            amplitude * angle.cos(),
+            amplitude * frequency * angle.cos(),
+        ]);
+        assert!((y.value() - amplitude * angle.sin()).abs() < 1e-12);
+        assert!((y.derivatives() - expected_derivatives).norm() < 1e-12);
+    }
+
+    #[test]
+    #[should_panic(expected = "must have the same size")]
+    fn test_mismatched_sizes() {
+        Sine::<f64>::new(
+            na::DVector::from_vec(vec![1.0, 1.0]),
+            na::DVector::from_vec(vec![1.0]),
+            na::DVector::from_vec(vec![0.0, 0.0]),
+        );
+    }
+}
@@ -0,0 +1,95 @@
+use atlas_derives::leaf_system;
+
+extern crate nalgebra as na;
+
+use crate::common::atlas_error::AtlasError;
+use crate::common::atlas_scalar::AtlasScalar;
+use crate::systems::framework::basic_vector::BasicVector;
+use crate::systems::framework::context::Context;
+use crate::systems::framework::diagram::SystemLink;
+use crate::systems::framework::framework_common::OutputPortIndex;
+use crate::systems::framework::leaf_context::LeafContext;
+use crate::systems::framework::leaf_output_port::LeafOutputPort;
+use crate::systems::framework::system_scalar_converter::ScalarConvertible;
+
+// Outputs initial_value before step_time and final_value from step_time on.
+#[leaf_system]
+#[system(scalar_convertible)]
+pub struct Step<T: AtlasScalar> {
+    #[output(
+        vector,
+        size = "Self::calc_size(&initial_value, &final_value)",
+        calc = "calc_y",
+        prerequisites = "vec![DependencyTicket::Time]"
+    )]
+    y: OutputPortIndex,
+    initial_value: na::DVector<T>,
+    final_value: na::DVector<T>,
+    step_time: f64,
+}
+
+impl<T: AtlasScalar> Step<T> {
+    fn calc_size(initial_value: &na::DVector<T>, final_value: &na::DVector<T>) -> usize {
+        assert_eq!(
+            initial_value.len(),
+            final_value.len(),
+            "Step::new: the initial and final values must have the same size"
+        );
+        initial_value.len()
+    }
+
+    pub fn output_port_y(&self) -> &LeafOutputPort<T> {
+        &self.output_ports[&self.y]
+    }
+
+    fn calc_y(&self, context: &LeafContext<T>, y: &mut BasicVector<T>) {
+        if context.time().as_f64() < self.step_time {
+            y.set_value(&self.initial_value);
+        } else {
+            y.set_value(&self.final_value);
+        }
+    }
+}
+
+impl<T: AtlasScalar> ScalarConvertible for Step<T> {
+    fn convert_scalar_type<U: AtlasScalar>(&self) -> Result<SystemLink<U>, AtlasError> {
+        let cast = |values: &na::DVector<T>| values.map(|value| value.cast_scalar::<U>());
+        let step = Step::<U>::new(
+            cast(&self.initial_value),
+            cast(&self.final_value),
+            self.step_time,
+        );
+
+        Ok(step.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::systems::analysis::simulator::Simulator;
+
+    #[test]
+    fn test_step() {
+        let step = Step::<f64>::new(
+            na::DVector::from_vec(vec![0.0, 1.0]),
+            na::DVector::from_vec(vec![1.0, -1.0]),
+            0.25,
+        );
+        let mut simulator = Simulator::new(&step);
+
+        simulator.advance_to(0.2);
+        let y = step
+            .borrow()
+            .output_port_y()
+            .eval::<BasicVector<f64>>(&mut simulator.get_mutable_context());
+        assert_eq!(*y.value(), na::DVector::from_vec(vec![0.0, 1.0]));
+
+        simulator.advance_to(0.3);
+        let y = step
+            .borrow()
+            .output_port_y()
+            .eval::<BasicVector<f64>>(&mut simulator.get_mutable_context());
+        assert_eq!(*y.value(), na::DVector::from_vec(vec![1.0, -1.0]));
+    }
+}
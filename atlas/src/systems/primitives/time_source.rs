@@ -0,0 +1,63 @@
+use atlas_derives::leaf_system;
+
+extern crate nalgebra as na;
+
+use crate::common::atlas_error::AtlasError;
+use crate::common::atlas_scalar::AtlasScalar;
+use crate::systems::framework::basic_vector::BasicVector;
+use crate::systems::framework::context::Context;
+use crate::systems::framework::diagram::SystemLink;
+use crate::systems::framework::framework_common::OutputPortIndex;
+use crate::systems::framework::leaf_context::LeafContext;
+use crate::systems::framework::leaf_output_port::LeafOutputPort;
+use crate::systems::framework::system_scalar_converter::ScalarConvertible;
+
+// Outputs the time of the context.
+#[leaf_system]
+#[system(scalar_convertible)]
+pub struct TimeSource<T: AtlasScalar> {
+    #[output(
+        vector,
+        size = 1,
+        calc = "calc_time",
+        prerequisites = "vec![DependencyTicket::Time]"
+    )]
+    time: OutputPortIndex,
+}
+
+impl<T: AtlasScalar> TimeSource<T> {
+    pub fn output_port_time(&self) -> &LeafOutputPort<T> {
+        &self.output_ports[&self.time]
+    }
+
+    fn calc_time(&self, context: &LeafContext<T>, time: &mut BasicVector<T>) {
+        time.set_value(&na::DVector::from_element(1, context.time().clone()));
+    }
+}
+
+impl<T: AtlasScalar> ScalarConvertible for TimeSource<T> {
+    fn convert_scalar_type<U: AtlasScalar>(&self) -> Result<SystemLink<U>, AtlasError> {
+        Ok(TimeSource::<U>::new().into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::systems::analysis::simulator::Simulator;
+    use crate::systems::framework::system_base::SystemBase;
+
+    #[test]
+    fn test_time_source() {
+        let time_source = TimeSource::<f64>::new();
+        assert_eq!(time_source.borrow().num_input_ports(), 0);
+
+        let mut simulator = Simulator::new(&time_source);
+        simulator.advance_to(0.5);
+        let time = time_source
+            .borrow()
+            .output_port_time()
+            .eval::<BasicVector<f64>>(&mut simulator.get_mutable_context());
+        assert_eq!(*time.value(), na::DVector::from_vec(vec![0.5]));
+    }
+}
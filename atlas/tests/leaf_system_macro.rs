@@ -0,0 +1,104 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use atlas::common::atlas_scalar::AtlasScalar;
+use atlas::prelude::*;
+use atlas::systems::framework::leaf_context::LeafContext;
+use atlas::systems::framework::leaf_state::LeafState;
+use atlas::systems::framework::leaf_system::LeafSystem;
+use atlas::systems::framework::output_port_base::OutputPortBase;
+use atlas::systems::framework::system_base::SystemBase;
+use atlas_derives::leaf_system;
+
+extern crate nalgebra as na;
+
+// A system declared outside the atlas crate: y = offset + u.
+#[leaf_system]
+struct Offset<T: AtlasScalar> {
+    #[input(vector, size = "size")]
+    u: InputPortIndex,
+    #[output(vector, size = "size", calc = "calc_y")]
+    y: OutputPortIndex,
+    offset: T,
+    size: usize,
+}
+
+impl<T: AtlasScalar> Offset<T> {
+    fn calc_y(&self, context: &LeafContext<T>, y: &mut BasicVector<T>) {
+        let u = self
+            .input_port(&self.u)
+            .eval::<LeafState<T>, BasicVector<T>>(context);
+        y.set_value(&u.value().add_scalar(self.offset.clone()));
+    }
+}
+
+// A system with a number of inputs given at construction: y = u0 + u1 + ... The input ports are
+// declared by new rather than the attributes.
+#[leaf_system(constructor = "allocate")]
+struct Sum<T: AtlasScalar> {
+    #[default]
+    y: OutputPortIndex,
+    size: usize,
+}
+
+impl<T: AtlasScalar> Sum<T> {
+    fn new(num_inputs: usize, size: usize) -> Rc<RefCell<Self>> {
+        let sum = Self::allocate(size);
+        {
+            let mut sum = sum.borrow_mut();
+            for i in 0..num_inputs {
+                sum.declare_vector_input_port(format!("u{}", i), size);
+            }
+            let calc = sum.make_vector_output_calc(Self::calc_y);
+            sum.y = sum
+                .declare_vector_output_port("y".to_string(), size, calc)
+                .index()
+                .clone();
+        }
+        sum
+    }
+
+    fn calc_y(&self, context: &LeafContext<T>, y: &mut BasicVector<T>) {
+        let mut value = na::DVector::zeros(self.size);
+        for input_port in self.input_ports.iter() {
+            value += input_port
+                .eval::<LeafState<T>, BasicVector<T>>(context)
+                .value();
+        }
+        y.set_value(&value);
+    }
+}
+
+#[test]
+fn test_leaf_system_outside_atlas() {
+    let offset = Offset::<f64>::new(1.5, 2);
+    let offset = offset.borrow();
+    assert_eq!(offset.name(), "offset");
+    assert_eq!(offset.size, 2);
+
+    let context = offset.create_default_context();
+    offset.input_port(&offset.u).fix_value(
+        context.borrow_mut(),
+        BasicVector::<f64>::from_vec(vec![1.0, -1.0]),
+    );
+    let y = offset.output_ports[&offset.y].eval::<BasicVector<f64>>(&mut context.borrow_mut());
+    assert_eq!(*y.value(), na::DVector::from_vec(vec![2.5, 0.5]));
+}
+
+#[test]
+fn test_leaf_system_with_hand_written_constructor() {
+    let sum = Sum::<f64>::new(3, 1);
+    let sum = sum.borrow();
+    assert_eq!(sum.name(), "sum");
+    assert_eq!(sum.num_input_ports(), 3);
+
+    let context = sum.create_default_context();
+    for (i, input_port) in sum.input_ports.iter().enumerate() {
+        input_port.fix_value(
+            context.borrow_mut(),
+            BasicVector::<f64>::from_vec(vec![i as f64]),
+        );
+    }
+    let y = sum.output_ports[&sum.y].eval::<BasicVector<f64>>(&mut context.borrow_mut());
+    assert_eq!(*y.value(), na::DVector::from_vec(vec![3.0]));
+}